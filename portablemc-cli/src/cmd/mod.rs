@@ -7,7 +7,7 @@ mod r#gen;
 
 use std::process::{self, ExitCode};
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::io;
 
 use portablemc::{base, download, moj, fabric, forge, msa};
@@ -65,11 +65,24 @@ pub fn main(args: &CliArgs) -> ExitCode {
     let msa_azure_app_id = args.msa_azure_app_id.clone()
         .unwrap_or_else(|| DEFAULT_AZURE_APP_ID.to_string());
 
+    let mut download_config = download::Config::new();
+    download_config.set_mirror_fallback(!args.no_mirror_fallback);
+    for mirror in &args.mirror {
+        download_config.add_mirror(&*mirror.prefix, &*mirror.mirror);
+    }
+    if let Some(rate_limit) = args.rate_limit {
+        download_config.set_rate_limit(Some(download::RateLimit::new(Some(rate_limit.0))));
+    }
+    download_config.set_local_root(args.local_root.clone());
+    download_config.set_local_link(args.local_link);
+    download_config.set_offline(args.offline);
+
     let mut cli = Cli {
         out,
         main_dir,
         msa_db: msa::Database::new(msa_db_file),
         msa_azure_app_id,
+        download_config,
     };
 
     legacy_check(&mut cli);
@@ -117,6 +130,7 @@ pub struct Cli {
     pub main_dir: PathBuf,
     pub msa_db: msa::Database,
     pub msa_azure_app_id: String,
+    pub download_config: download::Config,
 }
 
 /// Generic handler for various event handlers type (download and installers).
@@ -126,6 +140,8 @@ pub struct LogHandler<'a> {
     out: &'a mut Output,
     /// If a download is running, this contains the instant it started, for speed calc.
     download_start: Option<Instant>,
+    /// The last estimated remaining time of the running download, if known.
+    download_eta: Option<Duration>,
     /// When an installer with different supported APIs (for finding game or loader 
     /// versions) is used, this defines the id used for log messages.
     api_id: &'static str,
@@ -143,6 +159,7 @@ impl<'a> LogHandler<'a> {
         Self {
             out,
             download_start: None,
+            download_eta: None,
             api_id: "",
             api_name: "",
             loaded_lwjgl_version: None,
@@ -168,7 +185,7 @@ impl<'a> LogHandler<'a> {
 }
 
 impl download::Handler for LogHandler<'_> {
-    fn on_progress(&mut self, count: u32, total_count: u32, size: u32, total_size: u32) {
+    fn on_progress(&mut self, count: u32, total_count: u32, size: u64, total_size: u64) {
         
         if self.download_start.is_none() {
             self.download_start = Some(Instant::now());
@@ -179,6 +196,7 @@ impl download::Handler for LogHandler<'_> {
 
         if count == total_count {
             self.download_start = None;
+            self.download_eta = None;
         }
 
         // No logging when no size is actually downloaded, for example when downloading
@@ -195,6 +213,8 @@ impl download::Handler for LogHandler<'_> {
         let mut log = self.out.log_background("download");
         if count == total_count {
             log.message(format_args!("{speed_fmt:.1} {speed_suffix}B/s {size_fmt:.0} {size_suffix}B ({count})"));
+        } else if let Some(eta) = self.download_eta {
+            log.message(format_args!("{speed_fmt:.1} {speed_suffix}B/s {progress:.1}% ({count}/{total_count}) {}s left", eta.as_secs()));
         } else {
             log.message(format_args!("{speed_fmt:.1} {speed_suffix}B/s {progress:.1}% ({count}/{total_count})"));
         }
@@ -205,6 +225,23 @@ impl download::Handler for LogHandler<'_> {
         log.arg(format_args!("{speed}"));
         
     }
+
+    fn on_entry_event(&mut self, index: usize, event: download::EntryEvent<'_>) {
+        let _ = index;
+        if let download::EntryEvent::Retried { url, retry, delay } = event {
+            self.out.log("download_entry_retried")
+                .arg(url)
+                .arg(retry)
+                .arg(delay.as_secs_f32())
+                .info(format_args!("Retrying {url} in {:.1}s (retry {retry})", delay.as_secs_f32()));
+        }
+    }
+
+    fn on_speed(&mut self, speed: u64, eta: Option<Duration>) {
+        let _ = speed;
+        self.download_eta = eta;
+    }
+
 }
 
 impl base::Handler for LogHandler<'_> {
@@ -279,6 +316,15 @@ impl base::Handler for LogHandler<'_> {
                     .arg(file.display())
                     .success("Loaded client");
             }
+            base::Event::LoadServer => {
+                self.out.log("load_server")
+                    .pending("Loading server");
+            }
+            base::Event::LoadedServer { file } => {
+                self.out.log("loaded_server")
+                    .arg(file.display())
+                    .success("Loaded server");
+            }
             base::Event::LoadLibraries => {
                 self.out.log("load_libraries")
                     .pending("Loading libraries");
@@ -378,6 +424,12 @@ impl base::Handler for LogHandler<'_> {
                 self.out.log("warn_jvm_missing_distribution")
                     .info("Couldn't find a Mojang JVM because the required distribution was not found");
             }
+            base::Event::WarnJvmProviderFailed { provider, error } => {
+                self.out.log("warn_jvm_provider_failed")
+                    .arg(provider)
+                    .arg(error)
+                    .warning(format_args!("Couldn't find a JVM from the {provider} provider: {error}"));
+            }
             base::Event::LoadedJvm { file, version, compatible } => {
 
                 {
@@ -403,6 +455,24 @@ impl base::Handler for LogHandler<'_> {
                     
                 }
                 
+            }
+            base::Event::VerifyProgress { category, count, total_count } => {
+
+                let category_str = match category {
+                    base::PlanCategory::Client => "client",
+                    base::PlanCategory::Server => "server",
+                    base::PlanCategory::Libraries => "libraries",
+                    base::PlanCategory::Logger => "logger",
+                    base::PlanCategory::Assets => "assets",
+                    base::PlanCategory::Jvm => "jvm",
+                };
+
+                let progress = count as f32 / total_count as f32 * 100.0;
+                self.out.log_background("verify")
+                    .arg(category_str)
+                    .arg(format_args!("{count}/{total_count}"))
+                    .message(format_args!("Verifying {category_str} {progress:.1}% ({count}/{total_count})"));
+
             }
             base::Event::DownloadResources { .. } => {
                 self.out.log("download_resources")
@@ -411,6 +481,12 @@ impl base::Handler for LogHandler<'_> {
             base::Event::DownloadProgress { count, total_count, size, total_size } => {
                 download::Handler::on_progress(self, count, total_count, size, total_size);
             }
+            base::Event::DownloadEntry { index, event } => {
+                download::Handler::on_entry_event(self, index, event);
+            }
+            base::Event::DownloadSpeed { speed, eta } => {
+                download::Handler::on_speed(self, speed, eta);
+            }
             base::Event::DownloadedResources => {
                 self.out.log("resources_downloaded")
                     .success("Downloaded");
@@ -534,6 +610,8 @@ impl forge::Handler for LogHandler<'_> {
                         ("missing_patched_client", LogLevel::Warn, "The patched client is absent, reinstalling"),
                     forge::InstallReason::MissingUniversalClient => 
                         ("missing_universal_client", LogLevel::Warn, "The universal client is absent, reinstalling"),
+                    forge::InstallReason::MissingServerLibrary => 
+                        ("missing_server_library", LogLevel::Warn, "A server library is absent, reinstalling"),
                 };
 
                 self.out.log(format_args!("{api_id}_installing"))
@@ -643,6 +721,10 @@ pub fn log_base_error(cli: &mut Cli, error: &base::Error) {
             out.log("error_client_not_found")
                 .error("Client JAR file not found and no download information is available");
         }
+        Error::ServerNotFound {  } => {
+            out.log("error_server_not_found")
+                .error("Server JAR file not found and no download information is available");
+        }
         Error::LibraryNotFound { name: gav } => {
             out.log("error_library_not_found")
                 .error(format_args!("Library {gav} not found and no download information is available"));
@@ -662,9 +744,31 @@ pub fn log_base_error(cli: &mut Cli, error: &base::Error) {
         Error::DownloadResourcesCancelled {  } => {
             panic!("should not happen because the handler does not cancel downloading");
         }
+        Error::Cancelled {  } => {
+            panic!("should not happen because the installer has no cancellation token");
+        }
         Error::Download { batch } => {
             log_download_error(cli, batch);
         }
+        Error::Offline { resources } => {
+            
+            // error_offline <missing_count>
+            out.log("error_offline")
+                .arg(resources.len())
+                .error(format_args!("Offline, {} missing resources need to be downloaded", resources.len()));
+
+            // error_offline_resource <url> [dest]
+            for resource in resources {
+                let mut log = out.log("error_offline_resource");
+                log.arg(&resource.url);
+                log.error(format_args!("{}", resource.url));
+                if let Some(file) = &resource.file {
+                    log.arg(file.display());
+                    log.additional(format_args!("-> {}", file.display()));
+                }
+            }
+
+        }
         Error::Internal { error, origin } => {
 
             cli.out.log("error_base")
@@ -868,6 +972,11 @@ pub fn log_forge_error(cli: &mut Cli, error: &forge::Error, loader: forge::Loade
                 .additional(format_args!("Expected: {:x}", BytesFmt(&expected_sha1[..])))
                 .additional(CONTACT_DEV);
         }
+        Error::InstallerServerLauncherNotFound {  } => {
+            out.log(format_args!("error_{api_id}_installer_server_launcher_not_found"))
+                .error(format_args!("{api_name} installer has no known way of launching the server"))
+                .additional("This loader version might not support server installation by PortableMC");
+        }
         _ => todo!(),
     }
 
@@ -898,6 +1007,9 @@ pub fn log_download_error(cli: &mut Cli, batch: &download::BatchResult) {
         log.arg(error.file().display());
 
         log.error(format_args!("{}", error.url()));
+        if error.source_url() != error.url() {
+            log.additional(format_args!("<- {}", error.source_url()));
+        }
         log.additional(format_args!("-> {}", error.file().display()));
         
         match error.kind() {
@@ -909,18 +1021,47 @@ pub fn log_download_error(cli: &mut Cli, batch: &download::BatchResult) {
                 log.arg("invalid_size");
                 log.additional(format_args!("   Invalid SHA-1"));
             }
+            EntryErrorKind::InvalidDigest => {
+                log.arg("invalid_digest");
+                log.additional(format_args!("   Invalid digest"));
+            }
             EntryErrorKind::InvalidStatus(status) => {
                 log.arg("invalid_status");
                 log.arg(status);
                 log.additional(format_args!("   Invalid status: {status}"));
             }
-            EntryErrorKind::Internal(error) => {
+            EntryErrorKind::SourceFileNotFound => {
+                log.arg("source_file_not_found");
+                log.additional(format_args!("   Source file not found"));
+            }
+            EntryErrorKind::Offline => {
+                log.arg("offline");
+                log.additional(format_args!("   Offline"));
+            }
+            EntryErrorKind::Cancelled => {
+                log.arg("cancelled");
+                log.additional(format_args!("   Cancelled"));
+            }
+            EntryErrorKind::Internal(_) => {
                 log.arg("any");  // Indicates that the real error comes next
-                drop(log);
-                log_any_error(&mut *cli, &**error, true, true);
             }
         }
 
+        drop(log);
+        if let EntryErrorKind::Internal(error) = error.kind() {
+            log_any_error(&mut *cli, &**error, true, true);
+        }
+
+        // error_download_entry_attempt <url> <error>
+        // ... for each previous failed attempt, from another source URL or mirror
+        let attempts_count = error.attempts().count();
+        for (url, kind) in error.attempts().take(attempts_count - 1) {
+            cli.out.log("error_download_entry_attempt")
+                .arg(url)
+                .arg(kind)
+                .additional(format_args!("   Previously attempted {url}: {kind}"));
+        }
+
     }
 
 }
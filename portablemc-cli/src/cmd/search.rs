@@ -39,7 +39,7 @@ fn search_mojang(cli: &mut Cli, args: &SearchArgs) -> ExitCode {
 
     // Initial requests...
     let mut handler = LogHandler::new(&mut cli.out);
-    let manifest = match Manifest::request(&mut handler) {
+    let manifest = match Manifest::request_with_config(&mut handler, &cli.download_config) {
         Ok(manifest) => manifest,
         Err(e) => {
             log_mojang_error(cli, &e);
@@ -194,7 +194,8 @@ fn search_fabric(cli: &mut Cli, args: &SearchArgs, loader: fabric::Loader, game:
 
     use fabric::Api;
 
-    let api = Api::new(loader);
+    let mut api = Api::new(loader);
+    api.set_download_config(cli.download_config.clone());
 
     if game {
 
@@ -281,7 +282,7 @@ fn search_forge(cli: &mut Cli, args: &SearchArgs, loader: forge::Loader) -> Exit
     use forge::Repo;
 
     // Start by requesting the repository!
-    let repo = match Repo::request(loader) {
+    let repo = match Repo::request_with_config(loader, &cli.download_config) {
         Ok(repo) => repo,
         Err(e) => {
             log_forge_error(cli, &e, loader);
@@ -2,12 +2,13 @@
 
 use std::process::{Child, Command, ExitCode, Stdio};
 use std::io::{self, BufRead, BufReader};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 use chrono::{DateTime, Local, Utc};
 
 use portablemc::moj::{self, FetchExclude, QuickPlay};
-use portablemc::base::{self, Game, JvmPolicy};
+use portablemc::base::{self, Game, InstallSide, JvmPolicy};
+use portablemc::jvm::AdoptiumProvider;
 use portablemc::{fabric, forge};
 
 use crate::parse::{StartArgs, StartResolution, StartVersion, StartJvmPolicy};
@@ -34,7 +35,7 @@ pub fn start(cli: &mut Cli, args: &StartArgs) -> ExitCode {
         StartVersion::MojangSnapshot => {
 
             let handler = LogHandler::new(&mut cli.out);
-            let repo = match moj::Manifest::request(handler) {
+            let repo = match moj::Manifest::request_with_config(handler, &cli.download_config) {
                 Ok(repo) => repo,
                 Err(e) => {
                     log_mojang_error(cli, &e);
@@ -75,7 +76,7 @@ pub fn start(cli: &mut Cli, args: &StartArgs) -> ExitCode {
                 None => {
                     
                     let handler = LogHandler::new(&mut cli.out);
-                    let manifest = match moj::Manifest::request(handler) {
+                    let manifest = match moj::Manifest::request_with_config(handler, &cli.download_config) {
                         Ok(repo) => repo,
                         Err(e) => {
                             log_mojang_error(cli, &e);
@@ -224,6 +225,7 @@ fn apply_base_args(
 ) -> bool {
 
     installer.set_main_dir(cli.main_dir.clone());
+    installer.set_download_config(cli.download_config.clone());
 
     if let Some(mc_dir) = &args.mc_dir {
         installer.set_mc_dir(mc_dir.clone());
@@ -233,15 +235,42 @@ fn apply_base_args(
         installer.set_bin_dir(bin_dir.clone());
     }
 
+    if let Some(server_dir) = &args.server_dir {
+        installer.set_server_dir(server_dir.clone());
+    }
+
+    if args.server {
+        installer.set_side(InstallSide::Server);
+    }
+
+    if args.force_verify {
+        installer.set_force_verify(true);
+    }
+
     if let Some(jvm_file) = &args.jvm {
         installer.set_jvm_policy(JvmPolicy::Static(jvm_file.into()));
     } else {
-        installer.set_jvm_policy(match args.jvm_policy {
-            StartJvmPolicy::System => JvmPolicy::System,
-            StartJvmPolicy::Mojang => JvmPolicy::Mojang,
-            StartJvmPolicy::SystemThenMojang => JvmPolicy::SystemThenMojang,
-            StartJvmPolicy::MojangThenSystem => JvmPolicy::MojangThenSystem,
+
+        let mut policies = args.jvm_policy.iter()
+            .map(|policy| match policy {
+                StartJvmPolicy::System => JvmPolicy::System,
+                StartJvmPolicy::Mojang => JvmPolicy::Mojang,
+                StartJvmPolicy::SystemThenMojang => JvmPolicy::SystemThenMojang,
+                StartJvmPolicy::MojangThenSystem => JvmPolicy::MojangThenSystem,
+                StartJvmPolicy::Adoptium => JvmPolicy::Provider(Arc::new(AdoptiumProvider::new())),
+            })
+            .collect::<Vec<_>>();
+
+        installer.set_jvm_policy(if policies.len() == 1 {
+            policies.pop().unwrap()
+        } else {
+            JvmPolicy::Chain(policies)
         });
+
+    }
+
+    for dir in &args.jvm_search_dir {
+        installer.add_jvm_search_dir(dir);
     }
 
     true
@@ -64,6 +64,47 @@ pub struct CliArgs {
     /// (and hidden) launcher's application ID is used.
     #[arg(long, env = "PMC_MSA_AZURE_APP_ID", value_name = "APP_ID")]
     pub msa_azure_app_id: Option<String>,
+    /// Add a mirror for all URLs starting with the given prefix.
+    /// 
+    /// The syntax is '<prefix>=<mirror>', any URL requested by the launcher and starting
+    /// with the prefix is first requested with the prefix replaced by the mirror, for
+    /// example: 'https://libraries.minecraft.net/=https://bmclapi2.bangbang93.com/maven/'.
+    /// If several mirrors are matching an URL, they are tried in the order they are 
+    /// given, and the original URL is tried last, unless --no-mirror-fallback.
+    /// 
+    /// This argument can be specified multiple times, or as a comma-separated list in
+    /// the environment variable.
+    #[arg(long, env = "PMC_MIRROR", value_name = "PREFIX=MIRROR", value_delimiter(','))]
+    pub mirror: Vec<CliMirror>,
+    /// Never fallback to the original URL when all of its mirrors are failing.
+    #[arg(long, env = "PMC_NO_MIRROR_FALLBACK")]
+    pub no_mirror_fallback: bool,
+    /// Limit the download bandwidth, in bytes per second, shared by all downloads.
+    /// 
+    /// The value can be suffixed by 'K', 'M' or 'G' for kibibytes, mebibytes or 
+    /// gibibytes per second, for example: '500K' or '2M'.
+    #[arg(long, env = "PMC_RATE_LIMIT", value_name = "BYTES")]
+    pub rate_limit: Option<CliByteSize>,
+    /// Set a local directory where files are looked up before being downloaded.
+    /// 
+    /// The file of an URL is looked up at '<local-root>/<host>/<path>', for example
+    /// 'https://libraries.minecraft.net/foo/bar.jar' is looked up at 
+    /// '<local-root>/libraries.minecraft.net/foo/bar.jar'. Files are checked just like
+    /// downloaded files, and are downloaded as usual if missing or invalid. This can
+    /// be used to install from a prepared copy of the upstream files, without network.
+    #[arg(long, env = "PMC_LOCAL_ROOT", value_name = "PATH")]
+    pub local_root: Option<PathBuf>,
+    /// Hard link files from the local root instead of copying them, files are copied
+    /// anyway if linking fails.
+    #[arg(long, env = "PMC_LOCAL_LINK", requires = "local_root")]
+    pub local_link: bool,
+    /// Never access the network when installing and downloading.
+    /// 
+    /// The installation fails with the list of missing resources if anything needs to
+    /// be downloaded, files of the local root are still used. Versions that are already
+    /// fully installed can be started this way.
+    #[arg(long, env = "PMC_OFFLINE")]
+    pub offline: bool,
 }
 
 #[derive(Debug, Subcommand)]
@@ -92,6 +133,66 @@ pub enum CliOutput {
     Machine,
 }
 
+/// Represent a mirror rule, replacing an URL prefix with a mirror.
+#[derive(Debug, Clone)]
+pub struct CliMirror {
+    pub prefix: String,
+    pub mirror: String,
+}
+
+impl FromStr for CliMirror {
+
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+
+        let Some((prefix, mirror)) = s.split_once('=') else {
+            return Err("invalid mirror syntax, expecting <prefix>=<mirror>".to_string());
+        };
+
+        if prefix.is_empty() || mirror.is_empty() {
+            return Err("mirror prefix and mirror should not be empty".to_string());
+        }
+
+        Ok(Self {
+            prefix: prefix.to_string(),
+            mirror: mirror.to_string(),
+        })
+
+    }
+
+}
+
+/// Represent a size in bytes, with optional binary unit suffix.
+#[derive(Debug, Clone, Copy)]
+pub struct CliByteSize(pub u64);
+
+impl FromStr for CliByteSize {
+
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+
+        let (num, factor) = match s.as_bytes().last() {
+            Some(b'k' | b'K') => (&s[..s.len() - 1], 1024),
+            Some(b'm' | b'M') => (&s[..s.len() - 1], 1024 * 1024),
+            Some(b'g' | b'G') => (&s[..s.len() - 1], 1024 * 1024 * 1024),
+            _ => (s, 1),
+        };
+
+        let Ok(num) = num.parse::<u64>() else {
+            return Err("invalid size, expecting a number optionally suffixed by K, M or G".to_string());
+        };
+
+        num.checked_mul(factor)
+            .filter(|&size| size != 0)
+            .map(Self)
+            .ok_or_else(|| "size should be positive and not too large".to_string())
+
+    }
+
+}
+
 // ================= //
 //   START COMMAND   //
 // ================= //
@@ -183,6 +284,33 @@ pub struct StartArgs {
     /// environment variables if more practical.
     #[arg(long, env = "PMC_MC_DIR", value_name = "PATH")]
     pub mc_dir: Option<PathBuf>,
+    /// Install and start the dedicated server instead of the game client.
+    /// 
+    /// The server JAR file is installed instead of the client one, assets, natives and
+    /// the logger configuration are not installed, and the server is started without
+    /// its graphical interface ('nogui') from the server directory (see 
+    /// '--server-dir'). Client-only arguments such as authentication, Quick Play or
+    /// resolution are ignored.
+    #[arg(long)]
+    pub server: bool,
+    /// Set the directory where the dedicated server is run from, the server will use
+    /// this directory to put its worlds, properties and EULA file.
+    /// 
+    /// When unspecified, this argument is derived from the '--main-dir' path:
+    /// '<main-dir>/server/'.
+    /// 
+    /// This argument might not always be used by a command, you can specify it through
+    /// environment variables if more practical.
+    #[arg(long, env = "PMC_SERVER_DIR", value_name = "PATH")]
+    pub server_dir: Option<PathBuf>,
+    /// Verify again the digest of all files, even unchanged ones.
+    /// 
+    /// Files that have been successfully verified are recorded in the verification 
+    /// index, '<main-dir>/verify_index.json', and are only verified again if their 
+    /// size, modification time or inode has changed. This argument ignores the index
+    /// and forces all files to be verified again.
+    #[arg(long)]
+    pub force_verify: bool,
     /// Disable the multiplayer buttons (>= 1.16).
     #[arg(long)]
     pub disable_multiplayer: bool,
@@ -303,8 +431,18 @@ pub struct StartArgs {
     #[arg(long, value_name = "PATH")]
     pub jvm: Option<String>,
     /// The policy for finding or installing the JVM executable.
-    #[arg(long, value_name = "POLICY", conflicts_with = "jvm", default_value = "system-then-mojang")]
-    pub jvm_policy: StartJvmPolicy,
+    /// 
+    /// Multiple policies can be given, separated by commas, in which case they are
+    /// tried in order until one of them finds a JVM, for example 
+    /// 'system,mojang,adoptium'.
+    #[arg(long, value_name = "POLICY", value_delimiter = ',', conflicts_with = "jvm", default_value = "system-then-mojang")]
+    pub jvm_policy: Vec<StartJvmPolicy>,
+    /// Add a directory to search for system JVMs, in addition to the standard ones.
+    /// 
+    /// The directory can either be a JVM home directory, or a directory containing JVM 
+    /// home directories. This argument can be specified multiple times.
+    #[arg(long, value_name = "PATH", conflicts_with = "jvm")]
+    pub jvm_search_dir: Vec<PathBuf>,
     /// Add more arguments to the JVM command line.
     /// 
     /// You can specify multiple arguments after the '--jvm-arg' option, using commas ',',
@@ -521,6 +659,11 @@ pub enum StartJvmPolicy {
     SystemThenMojang,
     /// The installer search Mojang and then system as a fallback.
     MojangThenSystem,
+    /// The installer will try to find a suitable JVM to install from Eclipse Temurin
+    /// builds, through the Adoptium API, this supports more platforms than Mojang.
+    /// The install fails if no JVM is available for the platform and the required
+    /// major version.
+    Adoptium,
 }
 
 /// Represent an optional initial resolution for the game window.
@@ -79,6 +79,9 @@ typedef enum {
     PMC_ERR_BASE_MAIN_CLASS_NOT_FOUND,
     PMC_ERR_BASE_DOWNLOAD_RESOURCES_CANCELLED,
     PMC_ERR_BASE_DOWNLOAD,
+    PMC_ERR_BASE_OFFLINE,
+    PMC_ERR_BASE_SERVER_NOT_FOUND,
+    PMC_ERR_BASE_CANCELLED,
     // Mojang installer
     PMC_ERR_MOJ_LWJGL_FIX_NOT_FOUND = 0x40,
     // Fabric installer
@@ -244,6 +247,11 @@ typedef enum {
     PMC_JVM_POLICY_MOJANG,
     PMC_JVM_POLICY_SYSTEM_THEN_MOJANG,
     PMC_JVM_POLICY_MOJANG_THEN_SYSTEM,
+    PMC_JVM_POLICY_ADOPTIUM,
+    PMC_JVM_POLICY_SYSTEM_THEN_MOJANG_THEN_ADOPTIUM,
+    /// A policy that has no equivalent in this API, it's only returned by the getter
+    /// and it's ignored by the setter.
+    PMC_JVM_POLICY_CUSTOM,
 } pmc_jvm_policy_tag;
 
 /// The JVM policy tagged union, only the static policy requires an explicit path value.
@@ -271,7 +279,7 @@ typedef struct {
 /// Represent a version loaded during the installation.
 typedef struct {
     const char *url;      // Not NULL
-    uint32_t size;        // MAX to disable
+    uint64_t size;        // MAX to disable
     const pmc_sha1 *sha1; // NULL to disable
 } pmc_library_download;
 
@@ -322,6 +330,9 @@ typedef enum {
     PMC_EVENT_BASE_DOWNLOAD_PROGRESS,
     PMC_EVENT_BASE_DOWNLOADED_RESOURCES,
     PMC_EVENT_BASE_EXTRACTED_BINARIES,
+    PMC_EVENT_BASE_LOAD_SERVER,
+    PMC_EVENT_BASE_LOADED_SERVER,
+    PMC_EVENT_BASE_WARN_JVM_PROVIDER_FAILED,
     // Mojang installer
     PMC_EVENT_MOJ_INVALIDATED_VERSION = 0x50,
     PMC_EVENT_MOJ_FETCH_VERSION,
@@ -457,8 +468,8 @@ typedef struct {
 typedef struct {
     uint32_t count;
     uint32_t total_count;
-    uint32_t size;
-    uint32_t total_size;
+    uint64_t size;
+    uint64_t total_size;
 } pmc_event_base_download_progress;
 
 /// PMC_EVENT_BASE_EXTRACTED_BINARIES
@@ -466,6 +477,17 @@ typedef struct {
     const char *dir;
 } pmc_event_base_extracted_binaries;
 
+/// PMC_EVENT_BASE_LOADED_SERVER
+typedef struct {
+    const char *file;
+} pmc_event_base_loaded_server;
+
+/// PMC_EVENT_BASE_WARN_JVM_PROVIDER_FAILED
+typedef struct {
+    const char *provider;
+    const char *message;
+} pmc_event_base_warn_jvm_provider_failed;
+
 /// PMC_EVENT_MOJ_INVALIDATED_VERSION
 typedef struct {
     const char *version;
@@ -543,6 +565,8 @@ typedef union {
     pmc_event_base_download_resources base_download_resources;
     pmc_event_base_download_progress base_download_progress;
     pmc_event_base_extracted_binaries base_extracted_binaries;
+    pmc_event_base_loaded_server base_loaded_server;
+    pmc_event_base_warn_jvm_provider_failed base_warn_jvm_provider_failed;
     pmc_event_moj_invalidated_version moj_invalidated_version;
     pmc_event_moj_fetch_version moj_fetch_version;
     pmc_event_moj_fetched_version moj_fetched_version;
@@ -2,9 +2,11 @@
 
 use std::ptr::{self, NonNull};
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::ffi::c_char;
 
 use portablemc::base::{Error, Event, Game, Handler, Installer, JvmPolicy, VersionChannel};
+use portablemc::jvm::{self, AdoptiumProvider};
 
 use crate::alloc::{extern_box, extern_cstr_from_fmt, extern_cstr_from_str};
 use crate::err::{extern_err_catch, extern_err, IntoExternErr};
@@ -42,6 +44,9 @@ impl IntoExternErr for Error {
             Error::ClientNotFound {  } => extern_err!(
                 PMC_ERR_BASE_CLIENT_NOT_FOUND,
                 c"Client not found"),
+            Error::ServerNotFound {  } => extern_err!(
+                PMC_ERR_BASE_SERVER_NOT_FOUND,
+                c"Server not found"),
             Error::LibraryNotFound { name } => extern_err!(
                 PMC_ERR_BASE_LIBRARY_NOT_FOUND,
                 format!("Library not found: {name}"),
@@ -60,16 +65,28 @@ impl IntoExternErr for Error {
             Error::DownloadResourcesCancelled {  } => extern_err!(
                 PMC_ERR_BASE_DOWNLOAD_RESOURCES_CANCELLED,
                 c"Download resources cancelled"),
+            Error::Cancelled {  } => extern_err!(
+                PMC_ERR_BASE_CANCELLED,
+                c"Installation cancelled"),
             Error::Download { batch: _ } => extern_err!(
                 PMC_ERR_BASE_DOWNLOAD,
                 c"Download error"),
+            Error::Offline { resources } => extern_err!(
+                PMC_ERR_BASE_OFFLINE,
+                format!("Offline, missing resources: {}", resources.len())),
             Error::Internal { error, origin } => extern_err!(
                 PMC_ERR_INTERNAL, 
                 error.to_string(),
                 raw::pmc_err_data_internal {
                     origin: origin => cstr
                 }),
-            _ => todo!(),
+            // Errors without a dedicated tag in the C API yet.
+            error => extern_err!(
+                PMC_ERR_INTERNAL,
+                error.to_string(),
+                raw::pmc_err_data_internal {
+                    origin: ptr::null()
+                }),
         }
     }
 
@@ -209,6 +226,14 @@ pub unsafe extern "C" fn pmc_standard_jvm_policy(inst: &Installer) -> NonNull<ra
         JvmPolicy::Mojang => PMC_JVM_POLICY_MOJANG,
         JvmPolicy::SystemThenMojang => PMC_JVM_POLICY_SYSTEM_THEN_MOJANG,
         JvmPolicy::MojangThenSystem => PMC_JVM_POLICY_MOJANG_THEN_SYSTEM,
+        JvmPolicy::Provider(provider) if is_adoptium(&**provider) => PMC_JVM_POLICY_ADOPTIUM,
+        JvmPolicy::Chain(chain) => match &chain[..] {
+            [JvmPolicy::System, JvmPolicy::Mojang, JvmPolicy::Provider(provider)] 
+            if is_adoptium(&**provider) => PMC_JVM_POLICY_SYSTEM_THEN_MOJANG_THEN_ADOPTIUM,
+            _ => PMC_JVM_POLICY_CUSTOM,
+        },
+        // Other providers can only be set from Rust.
+        JvmPolicy::Provider(_) => PMC_JVM_POLICY_CUSTOM,
     };
 
     let owned = if let JvmPolicy::Static(static_path) = inst.jvm_policy() {
@@ -242,10 +267,24 @@ pub unsafe extern "C" fn pmc_standard_set_jvm_policy(inst: &mut Installer, polic
         PMC_JVM_POLICY_MOJANG => JvmPolicy::Mojang,
         PMC_JVM_POLICY_SYSTEM_THEN_MOJANG => JvmPolicy::SystemThenMojang,
         PMC_JVM_POLICY_MOJANG_THEN_SYSTEM => JvmPolicy::MojangThenSystem,
+        PMC_JVM_POLICY_ADOPTIUM => JvmPolicy::Provider(Arc::new(AdoptiumProvider::new())),
+        PMC_JVM_POLICY_SYSTEM_THEN_MOJANG_THEN_ADOPTIUM => JvmPolicy::Chain(vec![
+            JvmPolicy::System,
+            JvmPolicy::Mojang,
+            JvmPolicy::Provider(Arc::new(AdoptiumProvider::new())),
+        ]),
+        // A custom policy can't be set through this API, the current one is kept.
+        PMC_JVM_POLICY_CUSTOM => return,
     });
 
 }
 
+/// Return true if the given provider is the Adoptium provider, the only one that can be
+/// set through this API.
+fn is_adoptium(provider: &dyn jvm::Provider) -> bool {
+    provider.name() == "adoptium"
+}
+
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn pmc_standard_launcher_name(inst: &Installer) -> NonNull<c_char> {
     extern_cstr_from_str(inst.launcher_name())
@@ -378,6 +417,15 @@ impl Handler for AdapterHandler {
                     file: file.as_ptr(),
                 });
             }
+            Event::LoadServer => {
+                self.forward(PMC_EVENT_BASE_LOAD_SERVER, raw::pmc_event_data::default());
+            }
+            Event::LoadedServer { file } => {
+                let file = cstr::from(file.to_path_buf());
+                self.forward(PMC_EVENT_BASE_LOADED_SERVER, raw::pmc_event_base_loaded_server {
+                    file: file.as_ptr(),
+                });
+            }
             Event::LoadLibraries => {
                 self.forward(PMC_EVENT_BASE_LOAD_LIBRARIES, raw::pmc_event_data::default());
             }
@@ -419,8 +467,8 @@ impl Handler for AdapterHandler {
                                 buffers.push(buf);
                                 ptr
                             },
-                            size: download.size.unwrap_or(u32::MAX),
-                            sha1: download.sha1.as_ref().map(|sha1| sha1 as *const _).unwrap_or(ptr::null()),
+                            size: download.size.unwrap_or(u64::MAX),
+                            sha1: download.digest.as_ref().and_then(|digest| digest.sha1()).map(|sha1| sha1 as *const _).unwrap_or(ptr::null()),
                         });
 
                         library_raw.download = &*download_raw;
@@ -531,6 +579,17 @@ impl Handler for AdapterHandler {
             Event::WarnJvmMissingDistribution => {
                 self.forward(PMC_EVENT_BASE_WARN_JVM_MISSING_DISTRIBUTION, raw::pmc_event_data::default());
             }
+            Event::WarnJvmProviderFailed { provider, error } => {
+
+                let (_buffer, [provider, message]) = 
+                    cstr::concat([cstr::from_ref(provider), cstr::from_ref(&error.to_string())]);
+
+                self.forward(PMC_EVENT_BASE_WARN_JVM_PROVIDER_FAILED, raw::pmc_event_base_warn_jvm_provider_failed {
+                    provider,
+                    message,
+                });
+
+            }
             Event::LoadedJvm { file, version, compatible } => {
 
                 let no_version = version.is_none();
@@ -566,7 +625,14 @@ impl Handler for AdapterHandler {
                     dir: dir.as_ptr(),
                 });
             }
-            _ => todo!(),
+            Event::VerifyProgress { .. } |
+            Event::DownloadEntry { .. } |
+            Event::DownloadSpeed { .. } => {
+                // Not yet forwarded through the C API.
+            }
+            _ => {
+                // Events added after this version of the C API are ignored.
+            }
         }
     }
 }
@@ -74,6 +74,9 @@ pub enum pmc_err_tag {
     PMC_ERR_BASE_MAIN_CLASS_NOT_FOUND = 54,
     PMC_ERR_BASE_DOWNLOAD_RESOURCES_CANCELLED = 55,
     PMC_ERR_BASE_DOWNLOAD = 56,
+    PMC_ERR_BASE_OFFLINE = 57,
+    PMC_ERR_BASE_SERVER_NOT_FOUND = 58,
+    PMC_ERR_BASE_CANCELLED = 59,
     PMC_ERR_MOJ_LWJGL_FIX_NOT_FOUND = 64,
     PMC_ERR_FABRIC_LATEST_VERSION_NOT_FOUND = 80,
     PMC_ERR_FABRIC_GAME_VERSION_NOT_FOUND = 81,
@@ -251,6 +254,10 @@ pub enum pmc_jvm_policy_tag {
     PMC_JVM_POLICY_MOJANG = 2,
     PMC_JVM_POLICY_SYSTEM_THEN_MOJANG = 3,
     PMC_JVM_POLICY_MOJANG_THEN_SYSTEM = 4,
+    PMC_JVM_POLICY_ADOPTIUM = 5,
+    PMC_JVM_POLICY_SYSTEM_THEN_MOJANG_THEN_ADOPTIUM = 6,
+    #[doc = " A policy that has no equivalent in this API, it's only returned by the getter\n and it's ignored by the setter."]
+    PMC_JVM_POLICY_CUSTOM = 7,
 }
 #[doc = " The JVM policy tagged union, only the static policy requires an explicit path value."]
 #[repr(C)]
@@ -282,7 +289,7 @@ pub struct pmc_loaded_version {
 #[derive(Debug, Copy, Clone)]
 pub struct pmc_library_download {
     pub url: *const ::std::ffi::c_char,
-    pub size: u32,
+    pub size: u64,
     pub sha1: *const pmc_sha1,
 }
 #[doc = " Represent a version loaded during the installation."]
@@ -335,6 +342,9 @@ pub enum pmc_event_tag {
     PMC_EVENT_BASE_DOWNLOAD_PROGRESS = 28,
     PMC_EVENT_BASE_DOWNLOADED_RESOURCES = 29,
     PMC_EVENT_BASE_EXTRACTED_BINARIES = 30,
+    PMC_EVENT_BASE_LOAD_SERVER = 31,
+    PMC_EVENT_BASE_LOADED_SERVER = 32,
+    PMC_EVENT_BASE_WARN_JVM_PROVIDER_FAILED = 33,
     PMC_EVENT_MOJ_INVALIDATED_VERSION = 80,
     PMC_EVENT_MOJ_FETCH_VERSION = 81,
     PMC_EVENT_MOJ_FETCHED_VERSION = 82,
@@ -486,8 +496,8 @@ pub struct pmc_event_base_download_resources {
 pub struct pmc_event_base_download_progress {
     pub count: u32,
     pub total_count: u32,
-    pub size: u32,
-    pub total_size: u32,
+    pub size: u64,
+    pub total_size: u64,
 }
 #[doc = " PMC_EVENT_BASE_EXTRACTED_BINARIES"]
 #[repr(C)]
@@ -495,6 +505,19 @@ pub struct pmc_event_base_download_progress {
 pub struct pmc_event_base_extracted_binaries {
     pub dir: *const ::std::ffi::c_char,
 }
+#[doc = " PMC_EVENT_BASE_LOADED_SERVER"]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct pmc_event_base_loaded_server {
+    pub file: *const ::std::ffi::c_char,
+}
+#[doc = " PMC_EVENT_BASE_WARN_JVM_PROVIDER_FAILED"]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct pmc_event_base_warn_jvm_provider_failed {
+    pub provider: *const ::std::ffi::c_char,
+    pub message: *const ::std::ffi::c_char,
+}
 #[doc = " PMC_EVENT_MOJ_INVALIDATED_VERSION"]
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -584,6 +607,8 @@ pub union pmc_event_data {
     pub base_download_resources: pmc_event_base_download_resources,
     pub base_download_progress: pmc_event_base_download_progress,
     pub base_extracted_binaries: pmc_event_base_extracted_binaries,
+    pub base_loaded_server: pmc_event_base_loaded_server,
+    pub base_warn_jvm_provider_failed: pmc_event_base_warn_jvm_provider_failed,
     pub moj_invalidated_version: pmc_event_moj_invalidated_version,
     pub moj_fetch_version: pmc_event_moj_fetch_version,
     pub moj_fetched_version: pmc_event_moj_fetched_version,
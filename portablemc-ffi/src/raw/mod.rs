@@ -86,6 +86,8 @@ impl_union_from_field! {
     base_download_resources: pmc_event_base_download_resources,
     base_download_progress: pmc_event_base_download_progress,
     base_extracted_binaries: pmc_event_base_extracted_binaries,
+    base_loaded_server: pmc_event_base_loaded_server,
+    base_warn_jvm_provider_failed: pmc_event_base_warn_jvm_provider_failed,
     moj_invalidated_version: pmc_event_moj_invalidated_version,
     moj_fetch_version: pmc_event_moj_fetch_version,
     moj_fetched_version: pmc_event_moj_fetched_version,
@@ -64,9 +64,9 @@ class Installer:
     def strict_jvm_check(self, strict: bool): ...
 
     @property
-    def jvm_policy(self) -> str | JvmPolicy: ...
+    def jvm_policy(self) -> str | JvmPolicy | list: ...
     @jvm_policy.setter
-    def jvm_policy(self, policy: str | PathLike[str] | JvmPolicy): ...
+    def jvm_policy(self, policy: str | PathLike[str] | JvmPolicy | list): ...
 
     @property
     def launcher_name(self) -> str: ...
@@ -86,6 +86,7 @@ class JvmPolicy(Enum):
     Mojang = auto()
     SystemThenMojang = auto()
     MojangThenSystem = auto()
+    Adoptium = auto()
 
 
 # class Handler:
@@ -6,6 +6,7 @@ use pyo3::exceptions::PyValueError;
 use pyo3::{intern, prelude::*};
 
 use portablemc::base::{default_main_dir, Installer, Game, JvmPolicy};
+use portablemc::jvm::AdoptiumProvider;
 
 use crate::installer::GenericInstaller;
 
@@ -31,12 +32,43 @@ pub enum PyJvmPolicy {
     Mojang,
     SystemThenMojang,
     MojangThenSystem,
+    Adoptium,
 }
 
 #[derive(FromPyObject, IntoPyObject)]
 pub enum PyJvmPolicyUnion {
     Static(PathBuf),
     Policy(PyJvmPolicy),
+    Chain(Vec<PyJvmPolicyUnion>),
+}
+
+impl PyJvmPolicyUnion {
+
+    fn from_policy(policy: &JvmPolicy) -> Self {
+        match policy {
+            JvmPolicy::Static(file) => Self::Static(file.clone()),
+            JvmPolicy::System => Self::Policy(PyJvmPolicy::System),
+            JvmPolicy::Mojang => Self::Policy(PyJvmPolicy::Mojang),
+            JvmPolicy::SystemThenMojang => Self::Policy(PyJvmPolicy::SystemThenMojang),
+            JvmPolicy::MojangThenSystem => Self::Policy(PyJvmPolicy::MojangThenSystem),
+            // Adoptium is the only provider that can be set from Python.
+            JvmPolicy::Provider(_) => Self::Policy(PyJvmPolicy::Adoptium),
+            JvmPolicy::Chain(policies) => Self::Chain(policies.iter().map(Self::from_policy).collect()),
+        }
+    }
+
+    fn into_policy(self) -> JvmPolicy {
+        match self {
+            Self::Static(file) => JvmPolicy::Static(file),
+            Self::Policy(PyJvmPolicy::System) => JvmPolicy::System,
+            Self::Policy(PyJvmPolicy::Mojang) => JvmPolicy::Mojang,
+            Self::Policy(PyJvmPolicy::SystemThenMojang) => JvmPolicy::SystemThenMojang,
+            Self::Policy(PyJvmPolicy::MojangThenSystem) => JvmPolicy::MojangThenSystem,
+            Self::Policy(PyJvmPolicy::Adoptium) => JvmPolicy::Provider(Arc::new(AdoptiumProvider::new())),
+            Self::Chain(policies) => JvmPolicy::Chain(policies.into_iter().map(Self::into_policy).collect()),
+        }
+    }
+
 }
 
 #[pyclass(name = "Installer", module = "portablemc.base", frozen, subclass)]
@@ -168,24 +200,12 @@ impl PyInstaller {
 
     #[getter]
     fn jvm_policy(&self) -> PyJvmPolicyUnion {
-        match self.0.lock().unwrap().base().jvm_policy() {
-            JvmPolicy::Static(file) => PyJvmPolicyUnion::Static(file.clone()),
-            JvmPolicy::System => PyJvmPolicyUnion::Policy(PyJvmPolicy::System),
-            JvmPolicy::Mojang => PyJvmPolicyUnion::Policy(PyJvmPolicy::Mojang),
-            JvmPolicy::SystemThenMojang => PyJvmPolicyUnion::Policy(PyJvmPolicy::SystemThenMojang),
-            JvmPolicy::MojangThenSystem => PyJvmPolicyUnion::Policy(PyJvmPolicy::MojangThenSystem),
-        }
+        PyJvmPolicyUnion::from_policy(self.0.lock().unwrap().base().jvm_policy())
     }
 
     #[setter]
     fn set_jvm_policy(&self, policy: PyJvmPolicyUnion) {
-        self.0.lock().unwrap().base_mut().set_jvm_policy(match policy {
-            PyJvmPolicyUnion::Static(file) => JvmPolicy::Static(file),
-            PyJvmPolicyUnion::Policy(PyJvmPolicy::System) => JvmPolicy::System,
-            PyJvmPolicyUnion::Policy(PyJvmPolicy::Mojang) => JvmPolicy::Mojang,
-            PyJvmPolicyUnion::Policy(PyJvmPolicy::SystemThenMojang) => JvmPolicy::SystemThenMojang,
-            PyJvmPolicyUnion::Policy(PyJvmPolicy::MojangThenSystem) => JvmPolicy::MojangThenSystem,
-        });
+        self.0.lock().unwrap().base_mut().set_jvm_policy(policy.into_policy());
     }
 
     #[getter]
@@ -238,7 +258,9 @@ impl PyGame {
         for arg in &game.jvm_args {
             args.append(arg)?;
         }
-        args.append(&game.main_class)?;
+        if !game.main_class.is_empty() {
+            args.append(&game.main_class)?;
+        }
         for arg in &game.game_args {
             args.append(arg)?;
         }
@@ -1,28 +1,29 @@
 //! The base installation procedure.
 
 pub(crate) mod serde;
+mod verify;
 
-use std::io::{self, BufReader, BufWriter, Seek, SeekFrom};
-use std::process::{Child, Command, ExitStatus, Stdio};
+use std::io::{self, BufReader, BufWriter, Seek, SeekFrom, Write as _};
+use std::process::{Child, Command, ExitStatus};
 use std::fmt::{self, Debug, Write as _};
 use std::path::{Path, PathBuf};
 use std::collections::HashSet;
 use std::fs::{self, File};
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 use std::time::Duration;
-use std::{env, thread};
+use std::env;
 use std::ffi::OsStr;
 
-use indexmap::IndexSet;
-
 use zip::ZipArchive;
 
-use sha1::{Digest, Sha1};
 use uuid::{uuid, Uuid};
 
 use crate::path::{PathExt, PathBufExt};
 use crate::download::{self, Batch};
 use crate::maven::Gav;
+use crate::jvm;
+
+use verify::{VerifyIndex, FileCheck};
 
 
 /// Base URL for downloading game's assets.
@@ -66,12 +67,20 @@ pub struct Installer {
     jvm_dir: PathBuf,
     bin_dir: PathBuf,
     mc_dir: PathBuf,
+    server_dir: PathBuf,
+    side: InstallSide,
     strict_assets_check: bool,
     strict_libraries_check: bool,
     strict_jvm_check: bool,
+    verify_index_file: PathBuf,
+    force_verify: bool,
     jvm_policy: JvmPolicy,
+    jvm_search_dirs: Vec<PathBuf>,
+    jvm_cache_file: PathBuf,
     launcher_name: Option<String>,
     launcher_version: Option<String>,
+    download_config: download::Config,
+    cancel_token: Option<download::CancelToken>,
 }
 
 impl Installer {
@@ -92,12 +101,20 @@ impl Installer {
             jvm_dir: mc_dir.join("jvm"),
             bin_dir: mc_dir.join("bin"),
             mc_dir: mc_dir.to_path_buf(),
-            strict_assets_check: false,
-            strict_libraries_check: false,
-            strict_jvm_check: false,
+            server_dir: mc_dir.join("server"),
+            side: InstallSide::Client,
+            strict_assets_check: true,
+            strict_libraries_check: true,
+            strict_jvm_check: true,
+            verify_index_file: mc_dir.join("verify_index.json"),
+            force_verify: false,
             jvm_policy: JvmPolicy::SystemThenMojang,
+            jvm_search_dirs: Vec::new(),
+            jvm_cache_file: mc_dir.join("jvm_cache.json"),
             launcher_name: None,
             launcher_version: None,
+            download_config: download::Config::default(),
+            cancel_token: None,
         }
 
     }
@@ -206,9 +223,25 @@ impl Installer {
         self
     }
 
+    /// The directory where the dedicated server process' working directory is set when
+    /// installing the server side, this is where the worlds, the server properties and
+    /// the EULA file are stored. The user launching the server should have read/write
+    /// permissions to this directory, it is created if missing.
+    #[inline]
+    pub fn server_dir(&self) -> &Path {
+        &self.server_dir
+    }
+
+    /// See [`Self::server_dir`].
+    #[inline]
+    pub fn set_server_dir(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.server_dir = dir.into();
+        self
+    }
+
     /// Shortcut for defining the various main directories of the game, by deriving
-    /// the given path, the directories `versions`, `assets`, `libraries` and `jvm`
-    /// are defined.
+    /// the given path, the directories `versions`, `assets`, `libraries`, `jvm` and
+    /// `server` are defined.
     /// 
     /// **Note that on Windows**, long NT UNC paths are very likely to be unsupported and
     /// you'll get unsound errors with the JVM or the game itself.
@@ -220,12 +253,33 @@ impl Installer {
         self.libraries_dir = mc_dir.join("libraries");
         self.jvm_dir = mc_dir.join("jvm");
         self.bin_dir = mc_dir.join("bin");
+        self.server_dir = mc_dir.join("server");
+        self.verify_index_file = mc_dir.join("verify_index.json");
+        self.jvm_cache_file = mc_dir.join("jvm_cache.json");
         self.mc_dir = mc_dir;
         self
     }
 
+    /// The side of the game to install, the client by default. When installing the 
+    /// server side, the server JAR file is installed instead of the client one, assets,
+    /// natives and logger configuration are not installed and the returned game runs 
+    /// the dedicated server in the [server directory](Self::server_dir).
+    #[inline]
+    pub fn side(&self) -> InstallSide {
+        self.side
+    }
+
+    /// See [`Self::side`].
+    #[inline]
+    pub fn set_side(&mut self, side: InstallSide) -> &mut Self {
+        self.side = side;
+        self
+    }
+
     /// When enabled, all assets are strictly checked against their expected SHA-1,
-    /// this is disabled by default because it's heavy on CPU.
+    /// this is enabled by default. Verified files are recorded in the 
+    /// [verification index](Self::verify_index_file) and are only hashed again when
+    /// modified.
     #[inline]
     pub fn strict_assets_check(&self) -> bool {
         self.strict_assets_check
@@ -239,7 +293,8 @@ impl Installer {
     }
 
     /// When enabled, all libraries are strictly checked against their expected SHA-1,
-    /// this is disabled by default because it's heavy on CPU.
+    /// this is enabled by default. See [`Self::strict_assets_check`] about the
+    /// verification index.
     #[inline]
     pub fn strict_libraries_check(&self) -> bool {
         self.strict_libraries_check
@@ -253,7 +308,8 @@ impl Installer {
     }
 
     /// When enabled, all files from Mojang-provided JVMs are strictly checked against
-    /// their expected SHA-1, this is disabled by default because it's heavy on CPU.
+    /// their expected SHA-1, this is enabled by default. See 
+    /// [`Self::strict_assets_check`] about the verification index.
     #[inline]
     pub fn strict_jvm_check(&self) -> bool {
         self.strict_jvm_check
@@ -266,6 +322,39 @@ impl Installer {
         self
     }
 
+    /// The file of the verification index, where the size, modification time and inode
+    /// of each file successfully verified against its expected digest is recorded. 
+    /// Files that are unchanged since their last verification are not hashed again,
+    /// removed files are dropped from the index when it's saved.
+    /// 
+    /// When unspecified, this is derived from the main directory: 
+    /// `<main>/verify_index.json`.
+    #[inline]
+    pub fn verify_index_file(&self) -> &Path {
+        &self.verify_index_file
+    }
+
+    /// See [`Self::verify_index_file`].
+    #[inline]
+    pub fn set_verify_index_file(&mut self, file: impl Into<PathBuf>) -> &mut Self {
+        self.verify_index_file = file.into();
+        self
+    }
+
+    /// When enabled, the verification index is ignored and all files are hashed again
+    /// if strictly checked, the index is then updated, disabled by default.
+    #[inline]
+    pub fn force_verify(&self) -> bool {
+        self.force_verify
+    }
+
+    /// See [`Self::force_verify`].
+    #[inline]
+    pub fn set_force_verify(&mut self, force: bool) -> &mut Self {
+        self.force_verify = force;
+        self
+    }
+
     /// The policy for finding a JVM to run the game on.
     #[inline]
     pub fn jvm_policy(&self) -> &JvmPolicy {
@@ -279,6 +368,48 @@ impl Installer {
         self
     }
 
+    /// Additional directories to search for system JVMs, in addition to the standard
+    /// locations, each directory can either be a JVM home directory, or a directory
+    /// containing JVM home directories. See [`jvm::find_candidates`].
+    #[inline]
+    pub fn jvm_search_dirs(&self) -> &[PathBuf] {
+        &self.jvm_search_dirs
+    }
+
+    /// Clear all additional JVM search directories. See [`Self::jvm_search_dirs`].
+    /// **This is the default state when constructed.**
+    #[inline]
+    pub fn clear_jvm_search_dirs(&mut self) -> &mut Self {
+        self.jvm_search_dirs.clear();
+        self
+    }
+
+    /// Append the given directory to the additional JVM search directories. See 
+    /// [`Self::jvm_search_dirs`].
+    #[inline]
+    pub fn add_jvm_search_dir(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.jvm_search_dirs.push(dir.into());
+        self
+    }
+
+    /// The file where the information of probed JVMs are cached, keyed by their path
+    /// and modification time, so that system JVMs are not probed on each installation.
+    /// See [`jvm::probe_cached`].
+    /// 
+    /// When unspecified, this is derived from the main directory: 
+    /// `<main>/jvm_cache.json`.
+    #[inline]
+    pub fn jvm_cache_file(&self) -> &Path {
+        &self.jvm_cache_file
+    }
+
+    /// See [`Self::jvm_cache_file`].
+    #[inline]
+    pub fn set_jvm_cache_file(&mut self, file: impl Into<PathBuf>) -> &mut Self {
+        self.jvm_cache_file = file.into();
+        self
+    }
+
     /// A specific launcher name to put on the command line, defaults to "portablemc".
     pub fn launcher_name(&self) -> &str {
         self.launcher_name.as_deref().unwrap_or(env!("CARGO_PKG_NAME"))
@@ -303,29 +434,122 @@ impl Installer {
         self
     }
 
+    /// The download configuration used for all downloads of the installation.
+    #[inline]
+    pub fn download_config(&self) -> &download::Config {
+        &self.download_config
+    }
+
+    /// See [`Self::download_config`].
+    #[inline]
+    pub fn set_download_config(&mut self, config: download::Config) -> &mut Self {
+        self.download_config = config;
+        self
+    }
+
+    /// True if the installation must not access the network, this is a shortcut for
+    /// [`download::Config::offline`] of the download configuration. In this mode, any
+    /// resource that is missing and would need to be downloaded makes the installation
+    /// fail with [`Error::Offline`].
+    #[inline]
+    pub fn offline(&self) -> bool {
+        self.download_config.offline()
+    }
+
+    /// See [`Self::offline`].
+    #[inline]
+    pub fn set_offline(&mut self, offline: bool) -> &mut Self {
+        self.download_config.set_offline(offline);
+        self
+    }
+
+    /// The cancellation token that is checked during the installation, when cancelled
+    /// the installation returns [`Error::Cancelled`] as soon as possible.
+    #[inline]
+    pub fn cancel_token(&self) -> Option<&download::CancelToken> {
+        self.cancel_token.as_ref()
+    }
+
+    /// See [`Self::cancel_token`].
+    #[inline]
+    pub fn set_cancel_token(&mut self, token: Option<download::CancelToken>) -> &mut Self {
+        self.cancel_token = token;
+        self
+    }
+
     /// Ensure that a the given version, from its id, is fully installed and return
     /// a game instance that can be used to run launch it.
+    /// 
+    /// This is internally starting an asynchronous Tokio runtime and block on it, so
+    /// this function will just panic if launched inside another runtime! Use 
+    /// [`Self::install_async`] instead in such case.
     #[inline]
     pub fn install(&mut self, mut handler: impl Handler) -> Result<Game> {
         self.install_dyn(&mut handler)
     }
 
+    /// Async version of [`Self::install`], to be awaited from an existing Tokio 
+    /// runtime. Note that local files are still checked and extracted synchronously.
+    pub async fn install_async(&mut self, mut handler: impl Handler + Send) -> Result<Game> {
+        self.install_impl(&mut handler as &mut (dyn Handler + Send)).await
+    }
+
+    /// Resolve the given version, from its id, like it would be installed, but instead
+    /// of downloading the missing resources, return a plan of everything that would be
+    /// downloaded. Version metadata, asset index and JVM manifests may still be 
+    /// fetched because they are needed for resolution, but no other resources are 
+    /// downloaded and nothing is extracted.
+    /// 
+    /// This only plans the version as-is, the installers wrapping this one have their
+    /// own plan function that also reflects their changes, like the libraries fixed
+    /// by the Mojang installer, see [`crate::moj::Installer::plan`].
+    #[inline]
+    pub fn plan(&mut self, mut handler: impl Handler) -> Result<Plan> {
+        self.plan_dyn(&mut handler)
+    }
+
+    /// Async version of [`Self::plan`], to be awaited from an existing Tokio runtime.
+    pub async fn plan_async(&mut self, mut handler: impl Handler + Send) -> Result<Plan> {
+        self.plan_impl(&mut handler as &mut (dyn Handler + Send)).await
+    }
+
     /// Inner install function to force dyn dispatch.
     #[inline(never)]
     fn install_dyn(&mut self, handler: &mut dyn Handler) -> Result<Game> {
+        crate::tokio::sync(self.install_impl(handler))
+    }
+
+    /// Inner plan function to force dyn dispatch.
+    #[inline(never)]
+    fn plan_dyn(&mut self, handler: &mut dyn Handler) -> Result<Plan> {
+        crate::tokio::sync(self.plan_impl(handler))
+    }
+
+    /// Internal install function, generic over the handler so that it can be used 
+    /// with both sendable or non-sendable handlers, and by the wrapping installers 
+    /// that need to fetch things asynchronously when handling some events. Steps that
+    /// don't need to be async are given the dynamic handler to avoid large 
+    /// monomorphization.
+    pub(crate) async fn install_impl<H: AsyncHandler + ?Sized>(&mut self, handler: &mut H) -> Result<Game> {
         
         // Start by setting up features.
-        let mut features = HashSet::new();
-        handler.on_event(Event::FilterFeatures { features: &mut features });
-        handler.on_event(Event::LoadedFeatures { features: &features });
+        let features = self.load_features(handler.as_dyn());
         
         // Then we have a sequence of steps that may add entries to the download batch.
         let mut batch = Batch::new();
-        let hierarchy = self.load_hierarchy(&mut *handler, &self.version)?;
-        let mut lib_files = self.load_libraries(&mut *handler, &hierarchy, &features, &mut batch)?;
-        let logger_config = self.load_logger(&mut *handler, &hierarchy, &mut batch)?;
-        let assets = self.load_assets(&mut *handler, &hierarchy, &mut batch)?;
-        let jvm = self.load_jvm(&mut *handler, &hierarchy, &mut batch)?;
+        let mut verify_index = VerifyIndex::load(self.verify_index_file.clone(), self.force_verify);
+        let hierarchy = self.load_hierarchy(&mut *handler, &self.version).await?;
+
+        // The dedicated server has its own, much simpler, installation procedure.
+        if self.side == InstallSide::Server {
+            return self.install_server(handler, &features, &hierarchy, batch, verify_index).await;
+        }
+
+        let client_file = self.load_client(handler.as_dyn(), &hierarchy, &mut batch, &mut verify_index)?;
+        let lib_files = self.load_libraries(&mut *handler, &hierarchy, &features, &mut batch, &mut verify_index, client_file).await?;
+        let logger_config = self.load_logger(handler.as_dyn(), &hierarchy, &mut batch, &mut verify_index)?;
+        let assets = self.load_assets(&mut *handler, &hierarchy, &mut batch, &mut verify_index).await?;
+        let jvm = self.load_jvm(&mut *handler, &hierarchy, &mut batch, &mut verify_index).await?;
 
         // If we don't find the main class it is impossible to launch.
         let main_class = hierarchy.iter()
@@ -333,25 +557,35 @@ impl Installer {
             .cloned()
             .ok_or(Error::MainClassNotFound {  })?;
 
-        // Only trigger download events if the batch is not empty. Note that in this
-        // module and generally in this crate we transform handlers to a dynamic download
-        // handler '&mut dyn download::Handler' to avoid large polymorphism duplications.
-        if !batch.is_empty() {
-            
-            let mut cancel = false;
-            handler.on_event(Event::DownloadResources { cancel: &mut cancel });
-
-            if cancel {
-                return Err(Error::DownloadResourcesCancelled {  });
-            }
+        self.download_resources(&mut *handler, batch, &mut verify_index).await?;
+        self.finalize_jvm_downloads(&mut *handler, &jvm, &mut verify_index).await?;
 
-            batch.download((&mut *handler).into_download())
-                .map_err(|e| Error::new_reqwest(e, "download resources"))?
-                .into_result()?;
+        self.finalize_client(handler.as_dyn(), &features, &hierarchy, LoadedClientState {
+            lib_files,
+            logger_config,
+            assets,
+            jvm,
+            main_class,
+        })
 
-            handler.on_event(Event::DownloadedResources);
+    }
 
-        }
+    /// Internal function to finalize the client installation once all resources have
+    /// been downloaded, and then resolve its arguments.
+    fn finalize_client(&self,
+        handler: &mut dyn Handler,
+        features: &HashSet<String>,
+        hierarchy: &[LoadedVersion],
+        state: LoadedClientState,
+    ) -> Result<Game> {
+
+        let LoadedClientState {
+            mut lib_files,
+            logger_config,
+            assets,
+            jvm,
+            main_class,
+        } = state;
 
         // Finalization of libraries to create a unique bin dir and extract them into.
         let bin_dir = self.finalize_libraries(&mut *handler, &mut lib_files)?;
@@ -368,10 +602,10 @@ impl Installer {
         let mut jvm_args = Vec::new();
         let mut game_args = Vec::new();
 
-        for version in &hierarchy {
+        for version in hierarchy {
             if let Some(version_args) = &version.metadata.arguments {
-                self.check_args(&mut jvm_args, &version_args.jvm, &features, None);
-                self.check_args(&mut game_args, &version_args.game, &features, None);
+                self.check_args(&mut jvm_args, &version_args.jvm, features, None);
+                self.check_args(&mut game_args, &version_args.game, features, None);
             } else if let Some(version_legacy_args) = &version.metadata.legacy_arguments {
                 // Legacy args are overwriting everything and abort child version.
                 jvm_args = LEGACY_JVM_ARGS.iter().copied().map(str::to_string).collect::<Vec<_>>();
@@ -444,9 +678,235 @@ impl Installer {
 
     }
 
+    /// Internal plan function, see [`Self::install_impl`], this function should follow 
+    /// the same steps as the install functions, but each step's entries are added to 
+    /// the plan.
+    pub(crate) async fn plan_impl<H: AsyncHandler + ?Sized>(&mut self, handler: &mut H) -> Result<Plan> {
+
+        let features = self.load_features(handler.as_dyn());
+
+        let mut plan = Plan::default();
+        let mut batch = Batch::new();
+        let mut verify_index = VerifyIndex::load(self.verify_index_file.clone(), self.force_verify);
+        let hierarchy = self.load_hierarchy(&mut *handler, &self.version).await?;
+
+        // Note that the verification index is not saved when planning.
+        if self.side == InstallSide::Server {
+            let (server_index, server_file) = self.load_server(handler.as_dyn(), &hierarchy, &mut batch, &mut verify_index)?;
+            plan.push_batch(PlanCategory::Server, &batch, &self.download_config);
+            self.load_libraries(&mut *handler, &hierarchy[..server_index], &features, &mut batch, &mut verify_index, server_file).await?;
+            plan.push_batch(PlanCategory::Libraries, &batch, &self.download_config);
+        } else {
+            let client_file = self.load_client(handler.as_dyn(), &hierarchy, &mut batch, &mut verify_index)?;
+            plan.push_batch(PlanCategory::Client, &batch, &self.download_config);
+            self.load_libraries(&mut *handler, &hierarchy, &features, &mut batch, &mut verify_index, client_file).await?;
+            plan.push_batch(PlanCategory::Libraries, &batch, &self.download_config);
+            self.load_logger(handler.as_dyn(), &hierarchy, &mut batch, &mut verify_index)?;
+            plan.push_batch(PlanCategory::Logger, &batch, &self.download_config);
+            self.load_assets(&mut *handler, &hierarchy, &mut batch, &mut verify_index).await?;
+            plan.push_batch(PlanCategory::Assets, &batch, &self.download_config);
+        }
+
+        self.load_jvm(&mut *handler, &hierarchy, &mut batch, &mut verify_index).await?;
+        plan.push_batch(PlanCategory::Jvm, &batch, &self.download_config);
+
+        Ok(plan)
+
+    }
+
+    /// Internal install function for the dedicated server side, the hierarchy has
+    /// already been loaded. The server JAR embeds (or bundles, in recent versions) all
+    /// of its libraries, so the libraries, arguments and main class of the version 
+    /// providing the server download and its parents are client-only and ignored. 
+    /// Only versions inheriting from it, such as mod loaders, may add libraries and a
+    /// main class, in such case the server JAR is added to their class path, if not the
+    /// server JAR is directly run. The class path is only given with `-cp` if these 
+    /// versions don't give any JVM argument.
+    async fn install_server<H: AsyncHandler + ?Sized>(&self,
+        handler: &mut H,
+        features: &HashSet<String>,
+        hierarchy: &[LoadedVersion],
+        mut batch: Batch,
+        mut verify_index: VerifyIndex,
+    ) -> Result<Game> {
+
+        let (server_index, server_file) = self.load_server(handler.as_dyn(), hierarchy, &mut batch, &mut verify_index)?;
+        let server_hierarchy = &hierarchy[..server_index];
+
+        let lib_files = self.load_libraries(&mut *handler, server_hierarchy, features, &mut batch, &mut verify_index, server_file.clone()).await?;
+        let jvm = self.load_jvm(&mut *handler, hierarchy, &mut batch, &mut verify_index).await?;
+
+        // The main class is optional here, because the server JAR has its own.
+        let main_class = server_hierarchy.iter()
+            .find_map(|v| v.metadata.main_class.as_ref())
+            .cloned();
+
+        self.download_resources(&mut *handler, batch, &mut verify_index).await?;
+        self.finalize_jvm_downloads(&mut *handler, &jvm, &mut verify_index).await?;
+        self.finalize_server(features, hierarchy, LoadedServerState {
+            server_index,
+            server_file,
+            lib_files,
+            jvm,
+            main_class,
+        })
+
+    }
+
+    /// Internal function to finalize the server installation once all resources have
+    /// been downloaded, and then resolve its arguments.
+    fn finalize_server(&self,
+        features: &HashSet<String>,
+        hierarchy: &[LoadedVersion],
+        state: LoadedServerState,
+    ) -> Result<Game> {
+
+        let LoadedServerState {
+            server_index,
+            server_file,
+            lib_files,
+            jvm,
+            main_class,
+        } = state;
+
+        let server_hierarchy = &hierarchy[..server_index];
+        self.finalize_jvm(&jvm)?;
+
+        fs::create_dir_all(&self.server_dir)
+            .map_err(|e| Error::new_io(e, format!("create dir: {}", self.server_dir.display())))?;
+
+        // The server may have no library, but the directory can be given in arguments.
+        fs::create_dir_all(&self.libraries_dir)
+            .map_err(|e| Error::new_io(e, format!("create dir: {}", self.libraries_dir.display())))?;
+
+        let server_dir = canonicalize_file(&self.server_dir)?;
+        let libraries_dir = canonicalize_file(&self.libraries_dir)?;
+        let jvm_file = canonicalize_file(&jvm.file)?;
+
+        let mut jvm_args = Vec::new();
+        let mut game_args = Vec::new();
+        let mut class_files = Vec::new();
+
+        if let Some(main_class) = &main_class {
+
+            for version in server_hierarchy {
+                if let Some(version_args) = &version.metadata.arguments {
+                    self.check_args(&mut jvm_args, &version_args.jvm, features, None);
+                    self.check_args(&mut game_args, &version_args.game, features, None);
+                }
+            }
+
+            // We know that everything has been downloaded, natives are ignored.
+            for file in &lib_files.class_files {
+                class_files.push(canonicalize_file(file)?);
+            }
+
+            // Versions that are not giving any JVM argument are expecting the class path
+            // to be given, versions giving them are responsible for the class path.
+            if jvm_args.is_empty() {
+                jvm_args.push("-cp".to_string());
+                jvm_args.push("${classpath}".to_string());
+            }
+
+            debug_assert!(!main_class.is_empty());
+
+        } else {
+            jvm_args.push("-jar".to_string());
+            jvm_args.push(canonicalize_file(&server_file)?.display().to_string());
+        }
+
+        // The server's graphical interface is not wanted when launched from here.
+        game_args.push("nogui".to_string());
+
+        let repl_arg = |arg: &str| {
+            Some(match arg {
+                #[cfg(windows)]      "classpath_separator" => ";".to_string(),
+                #[cfg(not(windows))] "classpath_separator" => ":".to_string(),
+                "classpath" => env::join_paths(class_files.iter())
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned(),
+                "launcher_name" => self.launcher_name().to_string(),
+                "launcher_version" => self.launcher_version().to_string(),
+                "version_name" => hierarchy[0].name.clone(),
+                "version_type" => return hierarchy.iter()
+                    .filter_map(|v| v.metadata.r#type.as_ref())
+                    .map(|t| t.as_str().to_string())
+                    .next(),
+                "game_directory" => server_dir.display().to_string(),
+                "library_directory" => libraries_dir.display().to_string(),
+                _ => return None
+            })
+        };
+
+        replace_strings_args(&mut jvm_args, repl_arg);
+        replace_strings_args(&mut game_args, repl_arg);
+
+        Ok(Game {
+            jvm_file,
+            mc_dir: server_dir,
+            main_class: main_class.unwrap_or_default(),
+            jvm_args,
+            game_args,
+        })
+
+    }
+
+    /// Internal function to download the given batch, if not empty, after the handler
+    /// agreed to. Downloaded files are recorded in the verification index, which is
+    /// then saved.
+    async fn download_resources<H: AsyncHandler + ?Sized>(&self, handler: &mut H, mut batch: Batch, verify_index: &mut VerifyIndex) -> Result<()> {
+
+        // Last chance to check for cancellation if no download is needed.
+        if self.cancel_token.as_ref().is_some_and(download::CancelToken::is_cancelled) {
+            return Err(Error::Cancelled {  });
+        }
+
+        // Only trigger download events if the batch is not empty. Note that in this
+        // module and generally in this crate we transform handlers to a dynamic download
+        // handler '&mut dyn download::Handler' to avoid large polymorphism duplications.
+        if batch.is_empty() {
+            return verify_index.save();
+        }
+        
+        let mut cancel = false;
+        handler.on_event(Event::DownloadResources { cancel: &mut cancel });
+
+        if cancel {
+            return Err(Error::DownloadResourcesCancelled {  });
+        }
+
+        batch.set_config(self.download_config.clone());
+        batch.set_cancel_token(self.cancel_token.clone());
+        let result = batch.download_impl(&mut (&mut *handler).into_download()).await
+            .map_err(|e| Error::new_reqwest(e, "download resources"))?;
+
+        // Downloaded files have been verified while downloading, the index is saved
+        // even if some entries failed, so that verified files are not hashed again.
+        for success in result.iter_successes() {
+            verify_index.record_file(success.file(), &success.digest());
+        }
+
+        verify_index.save()?;
+        result.into_result()?;
+
+        handler.on_event(Event::DownloadedResources);
+
+        Ok(())
+
+    }
+
+    /// Internal function to let the handler choose the enabled features.
+    fn load_features(&self, handler: &mut dyn Handler) -> HashSet<String> {
+        let mut features = HashSet::new();
+        handler.on_event(Event::FilterFeatures { features: &mut features });
+        handler.on_event(Event::LoadedFeatures { features: &features });
+        features
+    }
+
     /// Internal function that loads the version hierarchy from their JSON metadata files.
-    fn load_hierarchy(&self, 
-        handler: &mut dyn Handler, 
+    async fn load_hierarchy<H: AsyncHandler + ?Sized>(&self, 
+        handler: &mut H, 
         root_version: &str
     ) -> Result<Vec<LoadedVersion>> {
 
@@ -467,7 +927,7 @@ impl Installer {
                 return Err(Error::HierarchyLoop { version: version_name });
             }
 
-            let version = self.load_version(handler, version_name)?;
+            let version = self.load_version(&mut *handler, version_name).await?;
             if let Some(next_name) = &version.metadata.inherits_from {
                 current_name = Some(next_name.clone());
             }
@@ -482,8 +942,8 @@ impl Installer {
     }
 
     /// Internal function that loads a version from its JSON metadata file.
-    fn load_version(&self, 
-        handler: &mut dyn Handler, 
+    async fn load_version<H: AsyncHandler + ?Sized>(&self, 
+        handler: &mut H, 
         version: String,
     ) -> Result<LoadedVersion> {
 
@@ -494,7 +954,7 @@ impl Installer {
         let dir = self.versions_dir.join(&version);
         let file = dir.join_with_extension(&version, "json");
 
-        handler.on_event(Event::LoadVersion { version: &version, file: &file });
+        handler.on_event_async(Event::LoadVersion { version: &version, file: &file }).await;
 
         // Try a second time if retry is requested...
         for i in 0..2 {
@@ -504,7 +964,7 @@ impl Installer {
                 Err(e) if e.kind() == io::ErrorKind::NotFound => {
                     let mut retry = false;
                     if i == 0 {
-                        handler.on_event(Event::NeedVersion { version: &version, file: &file, retry: &mut retry });
+                        handler.on_event_async(Event::NeedVersion { version: &version, file: &file, retry: &mut retry }).await;
                     }
                     if retry {
                         continue;
@@ -535,6 +995,7 @@ impl Installer {
         handler: &mut dyn Handler, 
         hierarchy: &[LoadedVersion], 
         batch: &mut Batch,
+        verify_index: &mut VerifyIndex,
     ) -> Result<PathBuf> {
         
         let root_version = &hierarchy[0];
@@ -547,11 +1008,11 @@ impl Installer {
             .next();
 
         if let Some(dl) = dl {
-            let check_client_sha1 = dl.sha1.as_deref().filter(|_| self.strict_libraries_check);
-            if !check_file(&file, dl.size, check_client_sha1)? {
+            let check_client_digest = dl.digest().filter(|_| self.strict_libraries_check);
+            if !verify_index.check_file(&file, dl.size, check_client_digest.as_ref())? {
                 batch.push(dl.url.clone(), file.clone())
                     .set_expected_size(dl.size)
-                    .set_expected_sha1(dl.sha1.as_deref().copied());
+                    .set_expected_digest(dl.digest());
             }
         } else if !file.is_file() {
             return Err(Error::ClientNotFound {  });
@@ -563,16 +1024,57 @@ impl Installer {
 
     }
 
-    /// Load libraries required to run the game.
-    fn load_libraries(&self,
+    /// Load the dedicated server JAR file, returning the index of the version that
+    /// provides its download information in the hierarchy, with the server file. If no
+    /// version provides it, the server file must already exist and the last version
+    /// of the hierarchy is considered to be the one providing it.
+    fn load_server(&self,
         handler: &mut dyn Handler,
+        hierarchy: &[LoadedVersion],
+        batch: &mut Batch,
+        verify_index: &mut VerifyIndex,
+    ) -> Result<(usize, PathBuf)> {
+
+        handler.on_event(Event::LoadServer);
+
+        let dl = hierarchy.iter()
+            .enumerate()
+            .filter_map(|(index, version)| Some((index, version.metadata.downloads.get("server")?)))
+            .next();
+
+        // The server JAR is stored in the directory of the version providing it, so 
+        // it can be shared between the vanilla version and all versions inheriting it.
+        let index = dl.map(|(index, _)| index).unwrap_or(hierarchy.len() - 1);
+        let version = &hierarchy[index];
+        let file = version.dir.join_with_extension(format!("{}-server", version.name), "jar");
+
+        if let Some((_, dl)) = dl {
+            let check_server_digest = dl.digest().filter(|_| self.strict_libraries_check);
+            if !verify_index.check_file(&file, dl.size, check_server_digest.as_ref())? {
+                batch.push(dl.url.clone(), file.clone())
+                    .set_expected_size(dl.size)
+                    .set_expected_digest(dl.digest());
+            }
+        } else if !file.is_file() {
+            return Err(Error::ServerNotFound {  });
+        }
+
+        handler.on_event(Event::LoadedServer { file: &file });
+
+        Ok((index, file))
+
+    }
+
+    /// Load libraries required to run the game.
+    async fn load_libraries<H: AsyncHandler + ?Sized>(&self,
+        handler: &mut H,
         hierarchy: &[LoadedVersion], 
         features: &HashSet<String>,
         batch: &mut Batch,
+        verify_index: &mut VerifyIndex,
+        main_file: PathBuf,
     ) -> Result<LibrariesFiles> {
 
-        let client_file = self.load_client(&mut *handler, &hierarchy, &mut *batch)?;
-
         handler.on_event(Event::LoadLibraries);
 
         // Tracking libraries that are already defined and should not be overridden.
@@ -580,7 +1082,7 @@ impl Installer {
         let mut libraries = Vec::new();
 
         // True if modern arguments are present (approx > 1.12.2), used to know where
-        // to put the client file in the class path.
+        // to put the main (client or server) file in the class path.
         let mut modern_args = false;
 
         for version in hierarchy {
@@ -667,7 +1169,7 @@ impl Installer {
                     lib_obj.download = Some(LibraryDownload {
                         url: lib_dl.download.url.to_string(),
                         size: lib_dl.download.size,
-                        sha1: lib_dl.download.sha1.as_deref().copied(),
+                        digest: lib_dl.download.digest(),
                     });
                 } else if let Some(repo_url) = &lib.url {
                     
@@ -684,7 +1186,7 @@ impl Installer {
                     lib_obj.download = Some(LibraryDownload {
                         url,
                         size: None,
-                        sha1: None,
+                        digest: None,
                     });
 
                 }
@@ -708,6 +1210,11 @@ impl Installer {
         // default here we put it first, but it may be modified by later versions.
         let mut lib_files = LibrariesFiles::default();
 
+        // Files to verify in parallel, with the download of the library and the index
+        // of the file in the class or natives files.
+        let mut lib_checks = Vec::new();
+        let mut lib_downloads = Vec::new();
+
         // After possible filtering by event handler, verify libraries and download 
         // missing ones.
         for lib in libraries {
@@ -727,13 +1234,13 @@ impl Installer {
             // not be downloaded from their repository, and this was confusing to
             // get a download error for such libraries.
             if let Some(download) = lib.download {
-                // Only check SHA-1 if strict checking is enabled.
-                let check_source_sha1 = download.sha1.as_ref().filter(|_| self.strict_libraries_check);
-                if !check_file(&lib_file, download.size, check_source_sha1)? {
-                    batch.push(download.url, lib_file.clone())
-                        .set_expected_size(download.size)
-                        .set_expected_sha1(download.sha1);
-                }
+                // Only check digest if strict checking is enabled.
+                lib_checks.push(FileCheck {
+                    file: lib_file.clone(),
+                    size: download.size,
+                    digest: download.digest.filter(|_| self.strict_libraries_check),
+                });
+                lib_downloads.push(download);
             } else if !lib_file.is_file() {
                 return Err(Error::LibraryNotFound { name: lib.name })
             }
@@ -746,6 +1253,18 @@ impl Installer {
 
         }
 
+        let lib_checks = verify_index.check_files(lib_checks, |count, total_count| {
+            handler.on_event(Event::VerifyProgress { category: PlanCategory::Libraries, count, total_count });
+        }).await?;
+
+        for ((check, valid), download) in lib_checks.into_iter().zip(lib_downloads) {
+            if !valid {
+                batch.push(download.url, check.file)
+                    .set_expected_size(download.size)
+                    .set_expected_digest(download.digest);
+            }
+        }
+
         handler.on_event(Event::FilterLibrariesFiles { 
             class_files: &mut lib_files.class_files, 
             natives_files: &mut lib_files.natives_files });
@@ -756,10 +1275,10 @@ impl Installer {
         // Note that this is purely arbitrary fix for old and recent versions.
         if modern_args {
             // Modern versions seems to prefer having the main class last in class path.
-            lib_files.class_files.push(client_file);
+            lib_files.class_files.push(main_file);
         } else {
             // Old versions seems to prefer having the main class first in class path.
-            lib_files.class_files.insert(0, client_file);
+            lib_files.class_files.insert(0, main_file);
         }
 
         Ok(lib_files)
@@ -901,6 +1420,7 @@ impl Installer {
         handler: &mut dyn Handler,
         hierarchy: &[LoadedVersion], 
         batch: &mut Batch,
+        verify_index: &mut VerifyIndex,
     ) -> Result<Option<LoggerConfig>> {
 
         let config = hierarchy.iter()
@@ -918,10 +1438,10 @@ impl Installer {
             .join("log_configs")
             .joined(config.file.id.as_str());
 
-        if !check_file(&file, config.file.download.size, config.file.download.sha1.as_deref())? {
+        if !verify_index.check_file(&file, config.file.download.size, config.file.download.digest().as_ref())? {
             batch.push(config.file.download.url.clone(), file.clone())
                 .set_expected_size(config.file.download.size)
-                .set_expected_sha1(config.file.download.sha1.as_deref().copied());
+                .set_expected_digest(config.file.download.digest());
         }
 
         handler.on_event(Event::LoadedLogger { id: &config.file.id });
@@ -935,10 +1455,11 @@ impl Installer {
     }
 
     /// Load and verify all assets of the game.
-    fn load_assets(&self, 
-        handler: &mut dyn Handler, 
+    async fn load_assets<H: AsyncHandler + ?Sized>(&self, 
+        handler: &mut H, 
         hierarchy: &[LoadedVersion], 
         batch: &mut Batch,
+        verify_index: &mut VerifyIndex,
     ) -> Result<Option<Assets>> {
 
         /// Internal description of asset information first found in hierarchy.
@@ -983,11 +1504,14 @@ impl Installer {
         // download this single file. If the file has no download info
         let mut index_downloaded = false;
         if let Some(dl) = index_info.download {
-            if !check_file(&index_file, dl.size, dl.sha1.as_deref())? {
-                download::single(dl.url.clone(), index_file.clone())
+            if !verify_index.check_file(&index_file, dl.size, dl.digest().as_ref())? {
+                let entry = download::single(dl.url.clone(), index_file.clone())
                     .set_expected_size(dl.size)
-                    .set_expected_sha1(dl.sha1.as_deref().copied())
-                    .download((&mut *handler).into_download())?;
+                    .set_expected_digest(dl.digest())
+                    .set_config(self.download_config.clone())
+                    .set_cancel_token(self.cancel_token.clone())
+                    .download_impl(&mut (&mut *handler).into_download()).await?;
+                verify_index.record_file(entry.file(), &entry.digest());
                 index_downloaded = true;
             }
         }
@@ -1008,9 +1532,22 @@ impl Installer {
                 .map_err(|e| Error::new_json_file(e, &index_file))?
 
         };
+
+        self.load_assets_objects(&mut *handler, index_info.id, &asset_index, batch, verify_index).await.map(Some)
+
+    }
+
+    /// Load and verify all objects of the given asset index.
+    async fn load_assets_objects<H: AsyncHandler + ?Sized>(&self,
+        handler: &mut H,
+        id: &str,
+        asset_index: &serde::AssetIndex,
+        batch: &mut Batch,
+        verify_index: &mut VerifyIndex,
+    ) -> Result<Assets> {
         
         handler.on_event(Event::LoadedAssets { 
-            id: index_info.id, 
+            id, 
             count: asset_index.objects.len(),
         });
 
@@ -1018,9 +1555,11 @@ impl Installer {
         let objects_dir = self.assets_dir.join("objects");
         let mut asset_file_name = String::new();
         let mut unique_hashes = HashSet::new();
+        let mut asset_checks = Vec::new();
+        let mut asset_downloads = Vec::new();
 
         let mut assets = Assets {
-            id: index_info.id.to_string(),
+            id: id.to_string(),
             mapping: None,
         };
 
@@ -1065,21 +1604,33 @@ impl Installer {
             }
 
             // Only check SHA-1 if strict checking.
-            let check_asset_sha1 = self.strict_assets_check.then_some(&*asset.hash);
-            if !check_file(&asset_hash_file, Some(asset.size), check_asset_sha1)? {
-                batch.push(format!("{RESOURCES_URL}{asset_hash_prefix}/{asset_file_name}"), asset_hash_file)
-                    .set_expected_size(Some(asset.size))
-                    .set_expected_sha1(Some(*asset.hash));
-            }
+            asset_checks.push(FileCheck {
+                file: asset_hash_file,
+                size: Some(asset.size),
+                digest: self.strict_assets_check.then_some(download::Digest::Sha1(*asset.hash)),
+            });
+            asset_downloads.push((format!("{RESOURCES_URL}{asset_hash_prefix}/{asset_file_name}"), *asset.hash));
+
+        }
 
+        let asset_checks = verify_index.check_files(asset_checks, |count, total_count| {
+            handler.on_event(Event::VerifyProgress { category: PlanCategory::Assets, count, total_count });
+        }).await?;
+
+        for ((check, valid), (url, sha1)) in asset_checks.into_iter().zip(asset_downloads) {
+            if !valid {
+                batch.push(url, check.file)
+                    .set_expected_size(check.size)
+                    .set_expected_sha1(Some(sha1));
+            }
         }
 
         handler.on_event(Event::VerifiedAssets { 
-            id: index_info.id, 
+            id, 
             count: asset_index.objects.len(),
         });
 
-        Ok(Some(assets))
+        Ok(assets)
 
     }
 
@@ -1148,10 +1699,11 @@ impl Installer {
     }
     
     /// The goal of this step is to find a valid JVM to run the game on.
-    fn load_jvm(&self, 
-        handler: &mut dyn Handler, 
+    async fn load_jvm<H: AsyncHandler + ?Sized>(&self, 
+        handler: &mut H, 
         hierarchy: &[LoadedVersion], 
         batch: &mut Batch,
+        verify_index: &mut VerifyIndex,
     ) -> Result<Jvm> {
 
         let version = hierarchy.iter()
@@ -1174,43 +1726,33 @@ impl Installer {
         
         handler.on_event(Event::LoadJvm { major_version });
 
-        // We simplify the code with this condition and duplicated match, because in the
-        // 'else' case we can simplify any policy that contains Mojang and System to
-        // System, because we don't have instructions for finding Mojang version.
-        let jvm = if let Some(distribution) = distribution {
-            match self.jvm_policy {
-                JvmPolicy::Static(ref file) => 
-                    Some(self.load_static_jvm(handler, &file, major_version)?),
-                JvmPolicy::System => 
-                    self.load_system_jvm(handler, major_version)?,
-                JvmPolicy::Mojang => 
-                    self.load_mojang_jvm(handler, distribution, batch)?,
-                JvmPolicy::SystemThenMojang => {
-                    let mut jvm = self.load_system_jvm(handler, major_version)?;
-                    if jvm.is_none() {
-                        jvm = self.load_mojang_jvm(handler, distribution, batch)?;
-                    }
-                    jvm
-                }
-                JvmPolicy::MojangThenSystem => {
-                    let mut jvm = self.load_mojang_jvm(handler, distribution, batch)?;
-                    if jvm.is_none() {
-                        jvm = self.load_system_jvm(handler, major_version)?;
-                    }
-                    jvm
-                }
-            }
-        } else {
-            match self.jvm_policy {
-                JvmPolicy::Static(ref file) => 
-                    Some(self.load_static_jvm(handler, &file, major_version)?),
-                JvmPolicy::System | 
-                JvmPolicy::SystemThenMojang | 
-                JvmPolicy::MojangThenSystem => 
-                    self.load_system_jvm(handler, major_version)?,
-                JvmPolicy::Mojang => None,
+        // The policy is flattened into the sources to try in order, until one is found.
+        let mut sources = Vec::new();
+        self.jvm_policy.push_sources(&mut sources);
+
+        let mut jvm = None;
+        for source in sources {
+
+            jvm = match source {
+                JvmSource::Static(file) => 
+                    Some(self.load_static_jvm(handler.as_dyn(), file, major_version)?),
+                JvmSource::System => 
+                    self.load_system_jvm(handler.as_dyn(), major_version)?,
+                // We don't have instructions for finding a Mojang JVM without distribution.
+                JvmSource::Mojang => match distribution {
+                    Some(distribution) => 
+                        self.load_mojang_jvm(&mut *handler, distribution, batch, verify_index).await?,
+                    None => None,
+                },
+                JvmSource::Provider(provider) => 
+                    self.load_provider_jvm(&mut *handler, provider, major_version, batch, verify_index).await?,
+            };
+
+            if jvm.is_some() {
+                break;
             }
-        };
+
+        }
 
         let Some(jvm) = jvm else {
             return Err(Error::JvmNotFound { major_version });
@@ -1240,88 +1782,37 @@ impl Installer {
         major_version: u32,
     ) -> Result<Jvm> {
 
-        let mut jvm = Jvm {
-            file: file.to_path_buf(),
-            version: None,
-            mojang: None,
-        };
+        let file = file.to_path_buf();
+        let info = jvm::probe_cached(std::slice::from_ref(&file), &self.jvm_cache_file).pop().flatten();
 
-        self.find_jvm_versions(std::slice::from_mut(&mut jvm), major_version);
-        Ok(jvm)
+        Ok(Jvm {
+            file,
+            version: info.map(|info| JvmVersion::new(info, major_version)),
+            mojang: None,
+            archive: None,
+        })
 
     }
 
     /// Try to find a JVM executable installed on the system in standard paths, depending
-    /// on the OS.
+    /// on the OS, and in the additional search directories.
     fn load_system_jvm(&self,
         handler: &mut dyn Handler,
         major_version: u32,
     ) -> Result<Option<Jvm>> {
 
-        let mut candidates = IndexSet::new();
-        let exec_name = jvm_exec_name();
-
-        // Check every JVM available in PATH.
-        if let Some(path) = env::var_os("PATH") {
-            for mut path in env::split_paths(&path) {
-                path.push(exec_name);
-                if path.is_file() {
-                    candidates.insert(path);
-                }
-            }
-        }
-
-        // On Linux distributions the different JVMs are in '/usr/lib/jvm/'.
-        #[cfg(target_os = "linux")] {
-            if let Ok(read_dir) = fs::read_dir("/usr/lib/jvm/") {
-                for entry in read_dir {
-                    let Ok(entry) = entry else { continue };
-                    let path = entry.path()
-                        .joined("bin")
-                        .joined(exec_name);
-                    if path.is_file() {
-                        candidates.insert(path);
-                    }
-                }
-            }
-        }
-
-        // On windows we can search in registry.
-        #[cfg(windows)] {
-
-            const REG_PATHS: [&str; 4] = [
-                "SOFTWARE\\JavaSoft\\Java Development Kit",
-                "SOFTWARE\\JavaSoft\\Java Runtime Environment",
-                "SOFTWARE\\JavaSoft\\JDK",
-                "SOFTWARE\\JavaSoft\\JRE",
-            ];
-
-            // Here we silently ignore any error.
-            for path in REG_PATHS {
-                let Ok(key) = windows_registry::LOCAL_MACHINE.open(path) else { continue };
-                let Ok(keys) = key.keys() else { continue };
-                for sub_key in keys {
-                    let Ok(sub_key) = key.open(&sub_key) else { continue };
-                    let Ok(java_home) = sub_key.get_string("JavaHome") else { continue };
-                    let path = PathBuf::from(java_home)
-                        .joined("bin")
-                        .joined(exec_name);
-                    if path.is_file() {
-                        candidates.insert(path);
-                    }
-                }
-            }
-
-        }
-
-        // Convert unique file paths to JVM, to be fed to JVM versions.
-        let mut jvms = candidates.into_iter().map(|file| Jvm {
-            file,
-            version: None,
-            mojang: None,
-        }).collect::<Vec<_>>();
-
-        self.find_jvm_versions(&mut jvms, major_version);
+        let candidates = jvm::find_candidates(&self.jvm_search_dirs);
+        let infos = jvm::probe_cached(&candidates, &self.jvm_cache_file);
+
+        // Convert unique file paths to JVM with their probed version.
+        let jvms = candidates.into_iter()
+            .zip(infos)
+            .map(|(file, info)| Jvm {
+                file,
+                version: info.map(|info| JvmVersion::new(info, major_version)),
+                mojang: None,
+                archive: None,
+            });
 
         let mut min_score_jvm = None;
         for jvm in jvms {
@@ -1358,10 +1849,11 @@ impl Installer {
 
     }
 
-    fn load_mojang_jvm(&self,
-        handler: &mut dyn Handler,
+    async fn load_mojang_jvm<H: AsyncHandler + ?Sized>(&self,
+        handler: &mut H,
         distribution: &str,
         batch: &mut Batch,
+        verify_index: &mut VerifyIndex,
     ) -> Result<Option<Jvm>> {
 
         // On Linux, only glibc dynamic linkage is supported by Mojang-provided JVMs.
@@ -1381,7 +1873,9 @@ impl Installer {
 
             let mut entry = download::single_cached(JVM_META_MANIFEST_URL)
                 .set_keep_open()
-                .download((&mut *handler).into_download())?;
+                .set_config(self.download_config.clone())
+                .set_cancel_token(self.cancel_token.clone())
+                .download_impl(&mut (&mut *handler).into_download()).await?;
 
             let reader = BufReader::new(entry.take_handle().unwrap());
             let mut deserializer = serde_json::Deserializer::from_reader(reader);
@@ -1413,18 +1907,20 @@ impl Installer {
         let bin_file = if cfg!(target_os = "macos") {
             dir.join("jre.bundle/Contents/Home/bin/java")
         } else {
-            dir.join("bin").joined(jvm_exec_name())
+            dir.join("bin").joined(jvm::exec_name())
         };
 
         // Check the manifest, download it, read and parse it...
         let manifest = {
             
-            if !check_file(&manifest_file, meta_variant.manifest.size, meta_variant.manifest.sha1.as_deref())? {
-                download::single(meta_variant.manifest.url.clone(), manifest_file.clone())
+            if !verify_index.check_file(&manifest_file, meta_variant.manifest.size, meta_variant.manifest.digest().as_ref())? {
+                let entry = download::single(meta_variant.manifest.url.clone(), manifest_file.clone())
                     .set_expected_size(meta_variant.manifest.size)
-                    .set_expected_sha1(meta_variant.manifest.sha1.as_deref().copied())
-                    .set_keep_open()
-                    .download((&mut *handler).into_download())?;
+                    .set_expected_digest(meta_variant.manifest.digest())
+                    .set_config(self.download_config.clone())
+                    .set_cancel_token(self.cancel_token.clone())
+                    .download_impl(&mut (&mut *handler).into_download()).await?;
+                verify_index.record_file(entry.file(), &entry.digest());
             }
             
             let reader = File::open(&manifest_file)
@@ -1437,7 +1933,31 @@ impl Installer {
 
         };
 
+        Ok(Some(Jvm {
+            file: bin_file,
+            version: Some(JvmVersion {
+                full: meta_variant.version.name.clone(),
+                major_compatibility: Some(0),  // Likely perfect compact
+            }),
+            mojang: Some(self.load_mojang_jvm_files(&mut *handler, &dir, &manifest, batch, verify_index).await?),
+            archive: None,
+        }))
+
+    }
+
+    /// Check all files of the given Mojang JVM manifest, adding the missing ones to the
+    /// batch.
+    async fn load_mojang_jvm_files<H: AsyncHandler + ?Sized>(&self,
+        handler: &mut H,
+        dir: &Path,
+        manifest: &serde::JvmManifest,
+        batch: &mut Batch,
+        verify_index: &mut VerifyIndex,
+    ) -> Result<MojangJvm> {
+
         let mut mojang_jvm = MojangJvm::default();
+        let mut checks = Vec::new();
+        let mut dls = Vec::new();
         
         // Here we only check files because it's too early to assert symlinks.
         for (rel_file, manifest_file) in &manifest.files {
@@ -1462,13 +1982,13 @@ impl Installer {
                     
                     let dl = &downloads.raw;
                     
-                    // Only check SHA-1 if strict checking is enabled.
-                    let check_dl_sha1 = dl.sha1.as_deref().filter(|_| self.strict_jvm_check);
-                    if !check_file(&file, dl.size, check_dl_sha1)? {
-                        batch.push(dl.url.clone(), file)
-                            .set_expected_size(dl.size)
-                            .set_expected_sha1(dl.sha1.as_deref().copied());
-                    }
+                    // Only check digest if strict checking is enabled.
+                    checks.push(FileCheck {
+                        file,
+                        size: dl.size,
+                        digest: dl.digest().filter(|_| self.strict_jvm_check),
+                    });
+                    dls.push(downloads);
 
                 }
                 serde::JvmManifestFile::Link { 
@@ -1483,104 +2003,190 @@ impl Installer {
 
         }
 
-        Ok(Some(Jvm {
-            file: bin_file,
-            version: Some(JvmVersion {
-                full: meta_variant.version.name.clone(),
-                major_compatibility: Some(0),  // Likely perfect compact
-            }),
-            mojang: Some(mojang_jvm),
-        }))
+        let checks = verify_index.check_files(checks, |count, total_count| {
+            handler.on_event(Event::VerifyProgress { category: PlanCategory::Jvm, count, total_count });
+        }).await?;
+
+        for ((check, valid), downloads) in checks.into_iter().zip(dls) {
+
+            if valid {
+                continue;
+            }
+
+            let dl = &downloads.raw;
+
+            // Prefer the LZMA-compressed file if available, it's downloaded next to the
+            // file and decompressed after download, see 'finalize_jvm_downloads'.
+            if let Some(lzma_dl) = &downloads.lzma {
+                let lzma_file = check.file.append(".lzma");
+                batch.push(lzma_dl.url.clone(), lzma_file.clone())
+                    .set_expected_size(lzma_dl.size)
+                    .set_expected_digest(lzma_dl.digest());
+                mojang_jvm.lzma_files.push(MojangJvmLzmaFile {
+                    lzma_file: lzma_file.into_boxed_path(),
+                    file: check.file.into_boxed_path(),
+                    url: dl.url.clone(),
+                    size: dl.size,
+                    digest: dl.digest(),
+                });
+            } else {
+                batch.push(dl.url.clone(), check.file)
+                    .set_expected_size(dl.size)
+                    .set_expected_digest(dl.digest());
+            }
+
+        }
+
+        Ok(mojang_jvm)
 
     }
 
-    /// Given a slice of multiple JVMs, update their detected version when possible, by
-    /// executing '-version' flag command.
-    fn find_jvm_versions(&self, jvms: &mut [Jvm], major_version: u32) {
+    /// Try to find a JVM from the given provider, if it's not already installed then its
+    /// archive is added to the batch, to be extracted after download.
+    async fn load_provider_jvm<H: AsyncHandler + ?Sized>(&self,
+        handler: &mut H,
+        provider: &dyn jvm::Provider,
+        major_version: u32,
+        batch: &mut Batch,
+        verify_index: &mut VerifyIndex,
+    ) -> Result<Option<Jvm>> {
 
-        // We put the resulting JVM inside this vector so that we have the same
-        // ordering as the given exec files.
-        let mut children = Vec::new();
-        let mut remaining = 0usize;
+        let provider_dir = self.jvm_dir.join(check_path_relative_and_safe(provider.name())?);
+        let record_file = provider_dir.join_with_extension(major_version.to_string(), "json");
+
+        // The JVM last installed for this major version is used without requesting the
+        // provider again. The JVM is considered installed if its executable exists, 
+        // because the archive is always fully extracted before being moved to its 
+        // directory.
+        let record = File::open(&record_file).ok()
+            .and_then(|reader| serde_json::from_reader::<_, serde::ProviderJvm>(BufReader::new(reader)).ok());
+
+        if let Some(record) = record {
+            let bin_file = provider_jvm_bin_file(&provider_dir.join(check_path_relative_and_safe(record.name.as_str())?));
+            if bin_file.is_file() {
+                return Ok(Some(Jvm {
+                    file: bin_file,
+                    version: Some(JvmVersion {
+                        full: record.version,
+                        major_compatibility: Some(0),  // Provided for the major version
+                    }),
+                    mojang: None,
+                    archive: None,
+                }));
+            }
+        }
 
-        // The standard doc says that -version outputs version on stderr. This
-        // argument -version is also practical because the version is given between
-        // double quotes.
-        for jvm in jvms.iter_mut() {
-            
-            let child = Command::new(&jvm.file)
-                .arg("-version")
-                .stdout(Stdio::null())
-                .stderr(Stdio::piped())
-                .spawn()
-                .ok();
-            
-            if child.is_some() {
-                remaining += 1;
+        let archive = match provider.find_archive(major_version, &self.download_config, self.cancel_token.as_ref()).await {
+            Ok(Some(archive)) => archive,
+            Ok(None) => return Ok(None),
+            // Being offline or cancelled is not a failure of the provider.
+            Err(error @ (Error::Offline { .. } | Error::Cancelled {  })) => return Err(error),
+            Err(error) => {
+                handler.on_event(Event::WarnJvmProviderFailed { provider: provider.name(), error: &error });
+                return Ok(None);
             }
+        };
+
+        let dir = provider_dir.join(check_path_relative_and_safe(archive.name.as_str())?);
+        let bin_file = provider_jvm_bin_file(&dir);
+
+        // The record is written before the JVM is installed, it's ignored until then.
+        fs::create_dir_all(&provider_dir)
+            .map_err(|e| Error::new_io(e, format!("create dir: {}", provider_dir.display())))?;
+        let record = serde::ProviderJvm { name: archive.name.clone(), version: archive.version.clone() };
+        File::create(&record_file)
+            .map(BufWriter::new)
+            .and_then(|mut writer| {
+                serde_json::to_writer(&mut writer, &record)?;
+                writer.flush()
+            })
+            .map_err(|e| Error::new_io_file(e, &record_file))?;
+
+        let mut archive_jvm = None;
+        if !bin_file.is_file() {
 
-            children.push(child);
+            let file = dir.append(archive.format.extension());
+            if !verify_index.check_file(&file, archive.size, archive.digest.as_ref())? {
+                batch.push(archive.url.clone(), file.clone())
+                    .set_expected_size(archive.size)
+                    .set_expected_digest(archive.digest);
+            }
+
+            archive_jvm = Some(ArchiveJvm {
+                file: file.into_boxed_path(),
+                format: archive.format,
+                dir: dir.into_boxed_path(),
+            });
 
         }
 
-        const TRIES_COUNT: usize = 30;  // 3 second maximum.
-        const TRIES_SLEEP: Duration = Duration::from_millis(100);
-        
-        for _ in 0..TRIES_COUNT {
+        Ok(Some(Jvm {
+            file: bin_file,
+            version: Some(JvmVersion {
+                full: archive.version,
+                major_compatibility: Some(0),  // Provided for the major version
+            }),
+            mojang: None,
+            archive: archive_jvm,
+        }))
 
-            for (child_idx, child_opt) in children.iter_mut().enumerate() {
+    }
 
-                let Some(child) = child_opt else { continue };
-                let Ok(status) = child.try_wait() else {
-                    // If an error happens we just forget the child: don't check it again.
-                    let _ = child.kill();
-                    *child_opt = None;
-                    remaining -= 1;
-                    continue;
-                };
+    /// Finalize the JVM files that have just been downloaded. The archive of a JVM from
+    /// a provider is extracted. The LZMA-compressed files of a Mojang-provided JVM are
+    /// decompressed, each file is verified against the size and digest of the raw file,
+    /// if the decompression or the verification fails then the raw file is downloaded
+    /// instead.
+    async fn finalize_jvm_downloads<H: AsyncHandler + ?Sized>(&self, 
+        handler: &mut H, 
+        jvm: &Jvm, 
+        verify_index: &mut VerifyIndex,
+    ) -> Result<()> {
+
+        // Extracting, decompressing and hashing files is blocking, so it's done on 
+        // blocking tasks.
+        if let Some(archive_jvm) = &jvm.archive {
+            let (file, format, dir) = (archive_jvm.file.clone(), archive_jvm.format, archive_jvm.dir.clone());
+            tokio::task::spawn_blocking(move || extract_jvm_archive(&file, format, &dir)).await
+                .expect("JVM archive extraction task should not be cancelled nor panicking")?;
+            verify_index.forget_file(&archive_jvm.file);
+        }
 
-                // If child has terminated, we take child to not check it again.
-                let Some(status) = status else { continue };
-                let child = child_opt.take().unwrap();
-                remaining -= 1;
-                
-                // Not a success, just forget this child.
-                if !status.success() {
-                    continue;
-                }
+        let lzma_files = jvm.mojang.as_ref()
+            .map(|mojang_jvm| &mojang_jvm.lzma_files[..])
+            .unwrap_or_default();
 
-                // If successful, get the output (it should not error nor block)...
-                let output = child.wait_with_output().unwrap();
-                let Ok(output) = String::from_utf8(output.stderr) else { 
-                    continue; // Ignore if stderr is not UTF-8.
-                };
-
-                jvms[child_idx].version = output.lines()
-                    .filter_map(|line| line.split_once('"'))
-                    .filter_map(|(_, line)| line.split_once('"'))
-                    .map(|(version, _)| version)
-                    .next()
-                    .and_then(|version| {
-                        
-                        let actual_major_version = parse_jvm_major_version(version)?;
+        let decompress_files = lzma_files.iter()
+            .map(|lzma_file| (lzma_file.lzma_file.clone(), lzma_file.file.clone(), lzma_file.size, lzma_file.digest))
+            .collect::<Vec<_>>();
 
-                        Some(JvmVersion {
-                            full: version.to_string(),
-                            major_compatibility: calc_jvm_major_compatibility(major_version, actual_major_version),
-                        })
+        let valids = tokio::task::spawn_blocking(move || {
+            decompress_files.iter()
+                .map(|(lzma_file, file, size, digest)| decompress_lzma_file(lzma_file, file, *size, digest.as_ref()))
+                .collect::<Result<Vec<_>>>()
+        }).await.expect("LZMA decompression task should not be cancelled nor panicking")?;
 
-                    });
-                
-            }
+        let mut batch = Batch::new();
 
-            if remaining == 0 {
-                break;
-            }
+        for (lzma_file, valid) in lzma_files.iter().zip(valids) {
 
-            thread::sleep(TRIES_SLEEP);
+            verify_index.forget_file(&lzma_file.lzma_file);
+
+            if valid {
+                if let Some(digest) = &lzma_file.digest {
+                    verify_index.record_file(&lzma_file.file, digest);
+                }
+            } else {
+                batch.push(lzma_file.url.clone(), lzma_file.file.to_path_buf())
+                    .set_expected_size(lzma_file.size)
+                    .set_expected_digest(lzma_file.digest);
+            }
 
         }
 
+        // This also saves the verification index, even if there is nothing to download.
+        self.download_resources(handler, batch, verify_index).await
+
     }
 
     /// Finalize the setup of any Mojang-provided JVM, doing nothing if not Mojang.
@@ -1774,6 +2380,10 @@ pub enum Event<'a> {
     LoadClient,
     /// The client JAR file has been loaded successfully at the given path.
     LoadedClient { file: &'a Path },
+    /// The dedicated server JAR file will be loaded, only when installing the server.
+    LoadServer,
+    /// The dedicated server JAR file has been loaded successfully at the given path.
+    LoadedServer { file: &'a Path },
     /// The game required libraries are going to be loaded.
     LoadLibraries,
     /// Filter versions before their verification.
@@ -1823,17 +2433,33 @@ pub enum Event<'a> {
     /// architecture are supported but the distribution (the java version packaged and
     /// distributed by Mojang) is not found.
     WarnJvmMissingDistribution,
+    /// When trying to find a JVM to install from the given provider, the provider 
+    /// failed with the given error, it's considered as if it had no JVM, so any next
+    /// policy of a chain is tried. This is not triggered when offline or cancelled,
+    /// such errors are returned.
+    WarnJvmProviderFailed { provider: &'a str, error: &'a Error },
     /// The JVM has been loaded, if the version is known. The compatible flag 
     /// indicates if this JVM is **likely** compatible with the game version, 
     /// when false it indicates that it will likely be incompatible.
     LoadedJvm { file: &'a Path, version: Option<&'a str>, compatible: bool },
+    /// Progress of the verification of already installed files for the given category,
+    /// files are verified in parallel so this is regularly triggered with the count of
+    /// verified files, until it reaches the total count. This happens for libraries,
+    /// assets and Mojang JVM files, only if there are files to verify.
+    VerifyProgress { category: PlanCategory, count: usize, total_count: usize },
     /// Resources will be downloaded. This function returns a boolean that indicates
     /// if the download should proceed, this can be used to abort 
     DownloadResources { cancel: &'a mut bool },
     /// Resources have been successfully downloaded.
     DownloadedResources,
     /// A download progress forwarded from a download handler.
-    DownloadProgress { count: u32, total_count: u32, size: u32, total_size: u32 },
+    DownloadProgress { count: u32, total_count: u32, size: u64, total_size: u64 },
+    /// An event of a single download entry forwarded from a download handler, the 
+    /// index is the one of the entry in the download batch.
+    DownloadEntry { index: usize, event: download::EntryEvent<'a> },
+    /// The download speed, in bytes per second, and the estimated remaining time, 
+    /// forwarded from a download handler.
+    DownloadSpeed { speed: u64, eta: Option<Duration> },
     /// All binaries has been successfully extracted to the given binary directory.
     ExtractedBinaries { dir: &'a Path },
 }
@@ -1865,9 +2491,15 @@ pub(crate) trait HandlerInto: Handler + Sized {
     fn into_download(self) -> impl download::Handler {
         pub(crate) struct Adapter<H: Handler>(pub H);
         impl<H: Handler> download::Handler for Adapter<H> {
-            fn on_progress(&mut self, count: u32, total_count: u32, size: u32, total_size: u32) {
+            fn on_progress(&mut self, count: u32, total_count: u32, size: u64, total_size: u64) {
                 self.0.on_event(Event::DownloadProgress { count, total_count, size, total_size });
             }
+            fn on_entry_event(&mut self, index: usize, event: download::EntryEvent<'_>) {
+                self.0.on_event(Event::DownloadEntry { index, event });
+            }
+            fn on_speed(&mut self, speed: u64, eta: Option<Duration>) {
+                self.0.on_event(Event::DownloadSpeed { speed, eta });
+            }
         }
         Adapter(self)
     }
@@ -1876,6 +2508,50 @@ pub(crate) trait HandlerInto: Handler + Sized {
 
 impl<H: Handler> HandlerInto for H {}
 
+/// Internal macro defining the `AsyncHandler` extension of the `Handler` trait of 
+/// an installer module, for its `Event` type, this allows wrapping installers to 
+/// handle some events asynchronously, such as fetching missing versions. Only some 
+/// events are given through `on_event_async`.
+macro_rules! def_async_handler {
+    () => {
+
+        /// Internal extension of the handler used by the installers, see 
+        /// `base::def_async_handler`.
+        pub(crate) trait AsyncHandler: Handler {
+
+            /// Return this handler as a dynamic one, used for synchronous installation
+            /// steps in order to avoid large monomorphization.
+            fn as_dyn(&mut self) -> &mut dyn Handler;
+
+            /// Handle a single event asynchronously, by default it's just forwarded 
+            /// to the synchronous handling function.
+            async fn on_event_async(&mut self, event: Event<'_>) {
+                self.on_event(event);
+            }
+
+        }
+
+        impl AsyncHandler for dyn Handler + '_ {
+            #[inline]
+            fn as_dyn(&mut self) -> &mut dyn Handler {
+                self
+            }
+        }
+
+        impl AsyncHandler for dyn Handler + Send + '_ {
+            #[inline]
+            fn as_dyn(&mut self) -> &mut dyn Handler {
+                self
+            }
+        }
+
+    };
+}
+
+pub(crate) use def_async_handler;
+
+def_async_handler!();
+
 
 /// The base installer could not proceed to the installation of a version.
 #[derive(thiserror::Error, Debug)]
@@ -1900,6 +2576,10 @@ pub enum Error {
     /// already existing, is is mandatory to build the class path.
     #[error("client not found")]
     ClientNotFound {  },
+    /// When installing the server side, the server JAR file that is required has no 
+    /// download information and is not already existing.
+    #[error("server not found")]
+    ServerNotFound {  },
     /// A library has no download information and is missing the libraries directory.
     #[error("library not found: {name}")]
     LibraryNotFound {
@@ -1916,11 +2596,21 @@ pub enum Error {
     /// procedure can't continue because it needs resources to be downloaded.
     #[error("download resources cancelled")]
     DownloadResourcesCancelled {  },
+    /// The installation has been cancelled through the installer's cancellation token,
+    /// see [`Installer::set_cancel_token`].
+    #[error("cancelled")]
+    Cancelled {  },
     /// There are some errors in the given download batch.
     #[error("download: {} errors over {} entries", batch.errors_count(), batch.len())]
     Download {
         batch: download::BatchResult,
     },
+    /// The installation is offline, see [`Installer::set_offline`], and the given 
+    /// resources are missing and would need to be downloaded.
+    #[error("offline: {} missing resources", resources.len())]
+    Offline {
+        resources: Vec<MissingResource>,
+    },
     /// A generic error that originates from internal or third-party dependencies. The
     /// goal of this is to provide a backward-compatible error variant that can be 
     /// dynamically checked and downcast if needed, the actual error types are not
@@ -1947,13 +2637,26 @@ pub enum Error {
 
 impl From<download::BatchResult> for Error {
     fn from(batch: download::BatchResult) -> Self {
-        Self::Download { batch }
+        if batch.is_cancelled() {
+            Self::Cancelled {  }
+        } else if batch.has_errors() && batch.iter_errors().all(|e| matches!(e.kind(), download::EntryErrorKind::Offline)) {
+            Self::Offline { 
+                resources: batch.iter_errors()
+                    .map(|e| MissingResource { 
+                        url: e.url().to_string(), 
+                        file: Some(e.file().to_path_buf()),
+                    })
+                    .collect(),
+            }
+        } else {
+            Self::Download { batch }
+        }
     }
 }
 
 impl From<download::EntryError> for Error {
     fn from(value: download::EntryError) -> Self {
-        Self::Download { batch: download::BatchResult::from(value) }
+        Self::from(download::BatchResult::from(value))
     }
 }
 
@@ -2022,6 +2725,59 @@ pub enum JvmPolicy {
     SystemThenMojang,
     /// The installer search Mojang and then system as a fallback.
     MojangThenSystem,
+    /// The installer will try to find a suitable JVM to install from the given provider,
+    /// such as [`jvm::AdoptiumProvider`], its archive is downloaded and extracted into a
+    /// sub-directory of the JVM directory named after the provider. The JVM last 
+    /// installed from the provider for the required major version is used without
+    /// requesting the provider again. If the provider has no JVM for the platform and
+    /// the required major version, or if it fails (see 
+    /// [`Event::WarnJvmProviderFailed`]), then a [`Error::JvmNotFound`] error is 
+    /// returned.
+    Provider(Arc<dyn jvm::Provider>),
+    /// The installer tries each of the given policies in order, until one of them finds
+    /// a JVM, this can be used to fall back to JVM providers if there is no suitable JVM
+    /// on the system nor from Mojang. If none of them finds a JVM then a 
+    /// [`Error::JvmNotFound`] error is returned.
+    Chain(Vec<JvmPolicy>),
+}
+
+impl JvmPolicy {
+
+    /// Push the sources of JVM to try, in order, for this policy.
+    fn push_sources<'a>(&'a self, dest: &mut Vec<JvmSource<'a>>) {
+        match self {
+            JvmPolicy::Static(file) => dest.push(JvmSource::Static(file)),
+            JvmPolicy::System => dest.push(JvmSource::System),
+            JvmPolicy::Mojang => dest.push(JvmSource::Mojang),
+            JvmPolicy::SystemThenMojang => dest.extend([JvmSource::System, JvmSource::Mojang]),
+            JvmPolicy::MojangThenSystem => dest.extend([JvmSource::Mojang, JvmSource::System]),
+            JvmPolicy::Provider(provider) => dest.push(JvmSource::Provider(&**provider)),
+            JvmPolicy::Chain(policies) => {
+                for policy in policies {
+                    policy.push_sources(dest);
+                }
+            }
+        }
+    }
+
+}
+
+/// Internal single source of JVM, a JVM policy is flattened into these sources.
+#[derive(Debug, Clone, Copy)]
+enum JvmSource<'a> {
+    Static(&'a Path),
+    System,
+    Mojang,
+    Provider(&'a dyn jvm::Provider),
+}
+
+/// The side of the game to install.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstallSide {
+    /// The game client, the default.
+    Client,
+    /// The dedicated server, launched without its graphical interface.
+    Server,
 }
 
 /// Represent a loaded version.
@@ -2112,8 +2868,8 @@ pub struct LoadedLibrary {
 #[derive(Debug, Clone)]
 pub struct LibraryDownload {
     pub url: String,
-    pub size: Option<u32>,
-    pub sha1: Option<[u8; 20]>,
+    pub size: Option<u64>,
+    pub digest: Option<download::Digest>,
 }
 
 /// An abstract filter for libraries and their resolved files.
@@ -2148,7 +2904,8 @@ pub struct Game {
     pub jvm_file: PathBuf,
     /// Working directory where the JVM process should be running.
     pub mc_dir: PathBuf,
-    /// The main class that contains the JVM entrypoint.
+    /// The main class that contains the JVM entrypoint, it may be empty if the JVM 
+    /// arguments already specify the entrypoint, such as with `-jar` for the server.
     pub main_class: String,
     /// List of JVM arguments (before the main class in the command line).
     pub jvm_args: Vec<String>,
@@ -2174,9 +2931,11 @@ impl Game {
         let mut command = Command::new(&self.jvm_file);
         command
             .current_dir(&self.mc_dir)
-            .args(&self.jvm_args)
-            .arg(&self.main_class)
-            .args(&self.game_args);
+            .args(&self.jvm_args);
+        if !self.main_class.is_empty() {
+            command.arg(&self.main_class);
+        }
+        command.args(&self.game_args);
         command
     }
 
@@ -2192,10 +2951,130 @@ impl Game {
 
 }
 
+/// A plan of all resources that would be downloaded when installing a version, see
+/// [`Installer::plan`].
+#[derive(Debug, Clone, Default)]
+pub struct Plan {
+    /// All planned entries, ordered by category.
+    entries: Vec<PlanEntry>,
+}
+
+impl Plan {
+
+    /// Return all the planned entries, grouped by category in the order of installation.
+    #[inline]
+    pub fn entries(&self) -> &[PlanEntry] {
+        &self.entries
+    }
+
+    /// Return an iterator over all the planned entries of the given category.
+    pub fn iter_category(&self, category: PlanCategory) -> impl Iterator<Item = &PlanEntry> + use<'_> {
+        self.entries.iter().filter(move |entry| entry.category == category)
+    }
+
+    /// Return true if there is nothing to download.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Return the total size in bytes of all planned entries, entries without expected
+    /// size are not accounted.
+    pub fn total_size(&self) -> u64 {
+        self.entries.iter()
+            .filter_map(|entry| entry.size)
+            .sum()
+    }
+
+    /// Internal function to push all the batch entries that are not yet planned, they 
+    /// are all associated to the given category. The mirror rules of the given download
+    /// configuration are applied to their URL.
+    fn push_batch(&mut self, category: PlanCategory, batch: &Batch, config: &download::Config) {
+        for index in self.entries.len()..batch.len() {
+            let entry = batch.entry(index);
+            self.entries.push(PlanEntry {
+                category,
+                url: config.mirror_urls(entry.url()).swap_remove(0),
+                file: entry.file().to_path_buf(),
+                size: entry.expected_size(),
+                digest: entry.expected_digest().copied(),
+            });
+        }
+    }
+
+}
+
+/// A single resource that would be downloaded when installing.
+#[derive(Debug, Clone)]
+pub struct PlanEntry {
+    /// The category of resource.
+    pub category: PlanCategory,
+    /// The URL the resource would be downloaded from, this is its first mirror if some
+    /// mirror rule applies to it, see [`download::Config::add_mirror`].
+    pub url: String,
+    /// The destination file of the resource.
+    pub file: PathBuf,
+    /// The expected size of the resource, if known.
+    pub size: Option<u64>,
+    /// The expected digest of the resource, if known.
+    pub digest: Option<download::Digest>,
+}
+
+/// The category of a planned resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlanCategory {
+    /// The client JAR file.
+    Client,
+    /// The dedicated server JAR file.
+    Server,
+    /// Libraries, including natives.
+    Libraries,
+    /// The logger configuration file.
+    Logger,
+    /// Assets objects.
+    Assets,
+    /// JVM files, if a Mojang JVM needs to be installed.
+    Jvm,
+}
+
+/// A resource that is missing but can't be downloaded because the installation is 
+/// offline, see [`Error::Offline`].
+#[derive(Debug, Clone)]
+pub struct MissingResource {
+    /// The URL the resource would be downloaded from.
+    pub url: String,
+    /// The destination file of the resource, if it's stored to a file.
+    pub file: Option<PathBuf>,
+}
+
 // ========================== //
 // Following code is internal //
 // ========================== //
 
+/// Internal state of a client installation, loaded before downloading the resources
+/// and given to its finalization.
+#[derive(Debug)]
+struct LoadedClientState {
+    lib_files: LibrariesFiles,
+    logger_config: Option<LoggerConfig>,
+    assets: Option<Assets>,
+    jvm: Jvm,
+    main_class: String,
+}
+
+/// Internal state of a server installation, loaded before downloading the resources
+/// and given to its finalization.
+#[derive(Debug)]
+struct LoadedServerState {
+    /// Index, in the hierarchy, of the version providing the server JAR.
+    server_index: usize,
+    server_file: PathBuf,
+    lib_files: LibrariesFiles,
+    jvm: Jvm,
+    /// The main class, if any version inheriting from the server one gives it.
+    main_class: Option<String>,
+}
+
 /// Internal resolved libraries file paths.
 #[derive(Debug, Default)]
 struct LibrariesFiles {
@@ -2242,7 +3121,7 @@ struct AssetsMapping {
 struct AssetObject {
     rel_file: Box<Path>,
     object_file: Box<Path>,
-    size: u32,
+    size: u64,
 }
 
 /// Internal resolved JVM.
@@ -2254,6 +3133,9 @@ struct Jvm {
     version: Option<JvmVersion>,
     /// If this JVM originate from a mojang JVM, it contains the post-installation infos.
     mojang: Option<MojangJvm>,
+    /// If this JVM originate from a provider and isn't installed yet, it contains the
+    /// archive to extract.
+    archive: Option<ArchiveJvm>,
 }
 
 /// When a JVM version is known, this contains the information and compatibility score of
@@ -2263,11 +3145,26 @@ struct JvmVersion {
     /// The full version string.
     full: String,
     /// A compatibility score for the major version of that JVM with the required major
-    /// version, none when the version is **likely** incompatible. The lower the score is,
-    /// the better the JVM is compatible, zero being the **most likely** compatible JVM.
+    /// version, none when the version or architecture is **likely** incompatible. The 
+    /// lower the score is, the better the JVM is compatible, zero being the **most 
+    /// likely** compatible JVM.
     major_compatibility: Option<u32>,
 }
 
+impl JvmVersion {
+
+    /// Create the version of a probed JVM, computing its compatibility score with the
+    /// required major version, a JVM with a different architecture is incompatible.
+    fn new(info: jvm::JvmInfo, major_version: u32) -> Self {
+        Self {
+            major_compatibility: info.compatibility(major_version)
+                .filter(|_| info.is_host_arch()),
+            full: info.version,
+        }
+    }
+
+}
+
 /// Internal optional to the resolve JVM in case of Mojang-provided JVM where files
 /// needs to be made executable and links added.
 #[derive(Debug, Default)]
@@ -2276,6 +3173,8 @@ struct MojangJvm {
     executables: Vec<Box<Path>>,
     /// List of links to add given `(link_file, target_file)`.
     links: Vec<MojangJvmLink>,
+    /// List of LZMA-compressed files that are downloaded and should be decompressed.
+    lzma_files: Vec<MojangJvmLzmaFile>,
 }
 
 #[derive(Debug)]
@@ -2284,6 +3183,37 @@ struct MojangJvmLink {
     target_file: Box<Path>,
 }
 
+/// Internal optional to the resolved JVM in case of a provided JVM that should be 
+/// extracted from its downloaded archive.
+#[derive(Debug)]
+struct ArchiveJvm {
+    /// The archive file, removed once extracted.
+    file: Box<Path>,
+    format: jvm::ArchiveFormat,
+    /// The directory where the archive is extracted.
+    dir: Box<Path>,
+}
+
+/// A LZMA-compressed file to decompress, with the raw file's download as fallback.
+#[derive(Debug)]
+struct MojangJvmLzmaFile {
+    lzma_file: Box<Path>,
+    file: Box<Path>,
+    url: String,
+    size: Option<u64>,
+    digest: Option<download::Digest>,
+}
+
+/// Return the JVM executable of a JVM installed from a provider in the given directory,
+/// on macOS the JVM home is inside of the bundle structure.
+fn provider_jvm_bin_file(dir: &Path) -> PathBuf {
+    if cfg!(target_os = "macos") {
+        dir.join("Contents/Home/bin/java")
+    } else {
+        dir.join("bin").joined(jvm::exec_name())
+    }
+}
+
 /// Check that the given path does not contain any root or parent directory component.
 pub(crate) fn check_path_relative_and_safe<P: AsRef<Path>>(path: P) -> Result<P> {
     if path.as_ref().is_relative_and_safe() {
@@ -2295,39 +3225,33 @@ pub(crate) fn check_path_relative_and_safe<P: AsRef<Path>>(path: P) -> Result<P>
 
 /// Check if a file at a given path has the corresponding properties (size and/or SHA-1), 
 /// returning true if it is valid, so false is returned anyway if the file doesn't exists.
-pub(crate) fn check_file(file: &Path, size: Option<u32>, sha1: Option<&[u8; 20]>) -> Result<bool> {
-    check_file_advanced(file, size, sha1, false)
+pub(crate) fn check_file(file: &Path, size: Option<u64>, digest: Option<&download::Digest>) -> Result<bool> {
+    check_file_advanced(file, size, digest, false)
 }
 
-/// Check if a file at a given path has the corresponding properties (size and/or SHA-1), 
+/// Check if a file at a given path has the corresponding properties (size and/or digest), 
 /// returning true if it is valid, you can choose if a file not found is considered valid
 /// or not.
-pub(crate) fn check_file_advanced(file: &Path, size: Option<u32>, sha1: Option<&[u8; 20]>, not_found_valid: bool) -> Result<bool> {
+pub(crate) fn check_file_advanced(file: &Path, size: Option<u64>, digest: Option<&download::Digest>, not_found_valid: bool) -> Result<bool> {
 
-    fn inner(file: &Path, size: Option<u32>, sha1: Option<&[u8; 20]>, not_found_valid: bool) -> io::Result<bool> {
+    fn inner(file: &Path, size: Option<u64>, digest: Option<&download::Digest>, not_found_valid: bool) -> io::Result<bool> {
     
-        if let Some(sha1) = sha1 {
-            // If we want to check SHA-1 we need to open the file and compute it...
+        if let Some(digest) = digest {
+            // If we want to check digest we need to open the file and compute it...
             match File::open(file) {
                 Ok(mut reader) => {
     
                     // If relevant, start by checking the actual size of the file.
                     if let Some(size) = size {
                         let actual_size = reader.seek(SeekFrom::End(0))?;
-                        if size as u64 != actual_size {
+                        if size != actual_size {
                             return Ok(false);
                         }
                         reader.seek(SeekFrom::Start(0))?;
                     }
                     
                     // Only after we compute hash...
-                    let mut digest = Sha1::new();
-                    io::copy(&mut reader, &mut digest)?;
-                    if digest.finalize().as_slice() != sha1 {
-                        return Ok(false);
-                    }
-                    
-                    Ok(true)
+                    digest.check_reader(reader)
     
                 }
                 Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(not_found_valid),
@@ -2336,7 +3260,7 @@ pub(crate) fn check_file_advanced(file: &Path, size: Option<u32>, sha1: Option<&
         } else {
             match (file.metadata(), size) {
                 // File is existing and we want to check size...
-                (Ok(metadata), Some(size)) => Ok(metadata.len() == size as u64),
+                (Ok(metadata), Some(size)) => Ok(metadata.len() == size),
                 // File is existing but we don't have size to check, no need to download.
                 (Ok(_metadata), None) => Ok(true),
                 (Err(e), _) if e.kind() == io::ErrorKind::NotFound => Ok(not_found_valid),
@@ -2346,11 +3270,107 @@ pub(crate) fn check_file_advanced(file: &Path, size: Option<u32>, sha1: Option<&
     
     }
 
-    inner(file, size, sha1, not_found_valid)
+    inner(file, size, digest, not_found_valid)
         .map_err(|e| Error::new_io(e, format!("check file: {}", file.display())))
 
 }
 
+/// Decompress the given LZMA file into the destination file, in a streaming way through a 
+/// temporary sibling file that is only renamed into place if its size and digest are
+/// valid, the LZMA file is always removed. Returning false if the LZMA file is invalid.
+fn decompress_lzma_file(lzma_file: &Path, file: &Path, size: Option<u64>, digest: Option<&download::Digest>) -> Result<bool> {
+
+    let reader = File::open(lzma_file)
+        .map_err(|e| Error::new_io_file(e, lzma_file))
+        .map(BufReader::new)?;
+
+    let tmp_file = file.temp_sibling();
+    let mut writer = File::create(&tmp_file)
+        .map_err(|e| Error::new_io_file(e, &tmp_file))
+        .map(BufWriter::new)?;
+
+    // Any decompression error is considered as an invalid file.
+    let decompressed = lzma_rust2::LzmaReader::new_mem_limit(reader, u32::MAX, None)
+        .and_then(|mut reader| io::copy(&mut reader, &mut writer))
+        .and_then(|_| writer.flush());
+    drop(writer);
+
+    let valid = decompressed.is_ok() && check_file(&tmp_file, size, digest)?;
+    if valid {
+        fs::rename(&tmp_file, file)
+            .map_err(|e| Error::new_io_file(e, file))?;
+    } else {
+        let _ = fs::remove_file(&tmp_file);
+    }
+
+    fs::remove_file(lzma_file)
+        .map_err(|e| Error::new_io_file(e, lzma_file))?;
+
+    Ok(valid)
+
+}
+
+/// Extract the given JVM archive into the given directory, through a temporary sibling
+/// directory that is only renamed into place once fully extracted, any previous 
+/// directory is replaced. If the archive has a single top-level directory, as most
+/// JVM archives, then its content is extracted directly into the directory. The 
+/// archive is removed once extracted.
+fn extract_jvm_archive(archive_file: &Path, format: jvm::ArchiveFormat, dir: &Path) -> Result<()> {
+
+    let reader = File::open(archive_file)
+        .map_err(|e| Error::new_io_file(e, archive_file))
+        .map(BufReader::new)?;
+
+    let tmp_dir = dir.temp_sibling();
+    fs::create_dir_all(&tmp_dir)
+        .map_err(|e| Error::new_io(e, format!("create dir: {}", tmp_dir.display())))?;
+
+    let extracted = match format {
+        jvm::ArchiveFormat::TarGz => 
+            tar::Archive::new(flate2::bufread::GzDecoder::new(reader))
+                .unpack(&tmp_dir)
+                .map_err(|e| Error::new_io(e, format!("extract: {}", archive_file.display()))),
+        jvm::ArchiveFormat::Zip => 
+            ZipArchive::new(reader)
+                .and_then(|mut archive| archive.extract(&tmp_dir))
+                .map_err(|e| Error::new_zip_file(e, archive_file)),
+    };
+
+    if let Err(e) = extracted {
+        let _ = fs::remove_dir_all(&tmp_dir);
+        return Err(e);
+    }
+
+    let mut entries = fs::read_dir(&tmp_dir)
+        .and_then(|entries| entries.map(|entry| entry.map(|entry| entry.path())).collect::<io::Result<Vec<_>>>())
+        .map_err(|e| Error::new_io(e, format!("read dir: {}", tmp_dir.display())))?;
+
+    let content_dir = match entries.pop() {
+        Some(entry) if entries.is_empty() && entry.is_dir() => entry,
+        _ => tmp_dir.clone(),
+    };
+
+    match fs::remove_dir_all(dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(Error::new_io(e, format!("remove dir: {}", dir.display()))),
+    }
+
+    fs::rename(&content_dir, dir)
+        .map_err(|e| Error::new_io_file(e, dir))?;
+
+    if content_dir != tmp_dir {
+        fs::remove_dir_all(&tmp_dir)
+            .map_err(|e| Error::new_io(e, format!("remove dir: {}", tmp_dir.display())))?;
+    }
+
+    fs::remove_file(archive_file)
+        .map_err(|e| Error::new_io_file(e, archive_file))?;
+
+    Ok(())
+
+}
+
 /// Apply arguments replacement for each string, explained in [`replace_string_args`].
 fn replace_strings_args<'input, F>(ss: &mut [String], mut func: F)
 where 
@@ -2392,46 +3412,6 @@ where
 
 }
 
-/// Parse a JVM major version, this supports pre-v9 versions.
-fn parse_jvm_major_version(version: &str) -> Option<u32> {
-    
-    // Special case for parsing versions such as '8u51'.
-    if !version.contains('.') {
-        if let Some((major, _patch)) = version.split_once('u') {
-            return major.parse::<u32>().ok();
-        }
-    }
-
-    let mut comp = version.split('.');
-    let mut major = comp.next()?.parse::<u32>().ok()?;
-    if major == 1 {
-        major = comp.next()?.parse::<u32>().ok()?;
-    }
-    Some(major)
-
-}
-
-/// This function compute the compatibility between a given JVM version and the expected
-/// one, returning None if the versions are fully incompatible. If versions are compatible
-/// then a score is returned, the less this score is, the higher if the compatibility,
-/// a score of zero means that the versions are fully compatible.
-fn calc_jvm_major_compatibility(expected_version: u32, version: u32) -> Option<u32> {
-    if expected_version <= 8 {
-        // Because of huge breakings in the internal APIs between Java 8 and 9 (and onward),
-        // we require strict equality for Java 8 and before.
-        (expected_version == version).then_some(0)
-    } else {
-        // After Java 8, we allow any greater JVM version to run, the score is computed
-        // to privilege versions that are close to another, thus reducing potential 
-        // breakings between version, even if it shouldn't happen.
-        if version >= expected_version {
-            Some(version - expected_version)
-        } else {
-            None
-        }
-    }
-}
-
 /// Internal shortcut to canonicalize a file or directory and map error into an 
 /// installer error.
 #[inline]
@@ -2523,15 +3503,32 @@ pub(crate) fn write_version_metadata(file: &Path, metadata: &serde::VersionMetad
     fs::create_dir_all(dir)
         .map_err(|e| Error::new_io_file(e, dir))?;
 
-    let writer = File::create(file)
-        .map_err(|e| Error::new_io_file(e, file))
-        .map(BufWriter::new)?;
+    // The metadata is first written to a temporary file and then moved in place, so
+    // that a concurrent launcher never reads a partially written file.
+    let tmp_file = file.temp_sibling();
+    let res = (|| {
 
-    let mut serializer = serde_json::Serializer::new(writer);
-    serde_path_to_error::serialize(&metadata, &mut serializer)
-        .map_err(|e| Error::new_json_file(e, file))?;
+        let writer = File::create(&tmp_file)
+            .map_err(|e| Error::new_io_file(e, &tmp_file))
+            .map(BufWriter::new)?;
 
-    Ok(())
+        let mut serializer = serde_json::Serializer::new(writer);
+        serde_path_to_error::serialize(&metadata, &mut serializer)
+            .map_err(|e| Error::new_json_file(e, file))?;
+        
+        serializer.into_inner().into_inner()
+            .map_err(|e| Error::new_io_file(e.into_error(), &tmp_file))?;
+
+        fs::rename(&tmp_file, file)
+            .map_err(|e| Error::new_io_file(e, file))
+
+    })();
+
+    if res.is_err() {
+        let _ = fs::remove_file(&tmp_file);
+    }
+
+    res
 
 }
 
@@ -2613,12 +3610,6 @@ fn os_version() -> Option<&'static str> {
 
 }
 
-/// Return the JVM exec file name. 
-#[inline]
-fn jvm_exec_name() -> &'static str {
-    if cfg!(windows) { "javaw.exe" } else { "java" }
-}
-
 #[inline]
 fn mojang_jvm_platform() -> Option<&'static str> {
     Some(match (env::consts::OS, env::consts::ARCH) {
@@ -2652,41 +3643,58 @@ mod tests {
     }
 
     #[test]
-    fn parse_jvm_major_version() {
+    fn decompress_lzma_file() {
+
+        use std::io::Write;
+        use std::fs;
 
-        use super::parse_jvm_major_version;
+        use sha1::{Digest as _, Sha1};
+        use lzma_rust2::{LzmaOptions, LzmaWriter};
 
-        assert_eq!(parse_jvm_major_version("7u80"), Some(7));
-        assert_eq!(parse_jvm_major_version("8u51"), Some(8));
-        assert_eq!(parse_jvm_major_version("17"), Some(17));
-        assert_eq!(parse_jvm_major_version("17.0"), Some(17));
-        assert_eq!(parse_jvm_major_version("17.0.2"), Some(17));
-        assert_eq!(parse_jvm_major_version("1.8.0_111"), Some(8));
-        assert_eq!(parse_jvm_major_version("10.0.2"), Some(10));
+        use crate::download::Digest;
+        use super::decompress_lzma_file;
 
-        // Corner cases
-        assert_eq!(parse_jvm_major_version("10.foo"), Some(10));
-        assert_eq!(parse_jvm_major_version("1.foo"), None);
-        assert_eq!(parse_jvm_major_version("foou51"), None);
-        assert_eq!(parse_jvm_major_version("8ufoo"), Some(8));
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("modules");
+        let lzma_file = dir.path().join("modules.lzma");
+
+        let data = b"Hello world!".repeat(1000);
+        let digest = Digest::Sha1(Sha1::digest(&data).into());
+        let size = Some(data.len() as u64);
+
+        let mut writer = LzmaWriter::new_use_header(Vec::new(), &LzmaOptions::default(), None).unwrap();
+        writer.write_all(&data).unwrap();
+        let lzma_data = writer.finish().unwrap();
+
+        fs::write(&lzma_file, &lzma_data).unwrap();
+        assert!(decompress_lzma_file(&lzma_file, &file, size, Some(&digest)).unwrap());
+        assert_eq!(fs::read(&file).unwrap(), data);
+        assert!(!lzma_file.exists());
+
+        // Wrong digest, the file is not put in place.
+        fs::remove_file(&file).unwrap();
+        fs::write(&lzma_file, &lzma_data).unwrap();
+        assert!(!decompress_lzma_file(&lzma_file, &file, size, Some(&Digest::Sha1([0; 20]))).unwrap());
+        assert!(!file.exists());
+        assert!(!lzma_file.exists());
+
+        // Corrupted LZMA data.
+        fs::write(&lzma_file, &lzma_data[..lzma_data.len() / 2]).unwrap();
+        assert!(!decompress_lzma_file(&lzma_file, &file, size, Some(&digest)).unwrap());
+        assert!(!file.exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
 
     }
 
     #[test]
-    fn calc_jvm_major_compatibility() {
+    fn download_size() {
 
-        use super::calc_jvm_major_compatibility;
-        
-        assert_eq!(calc_jvm_major_compatibility(7, 7), Some(0));
-        assert_eq!(calc_jvm_major_compatibility(8, 8), Some(0));
-        assert_eq!(calc_jvm_major_compatibility(8, 7), None);
-
-        assert_eq!(calc_jvm_major_compatibility(9, 8), None);
-        assert_eq!(calc_jvm_major_compatibility(9, 9), Some(0));
-        assert_eq!(calc_jvm_major_compatibility(9, 11), Some(2));
-        assert_eq!(calc_jvm_major_compatibility(9, 17), Some(8));
-        assert_eq!(calc_jvm_major_compatibility(17, 17), Some(0));
-        assert_eq!(calc_jvm_major_compatibility(17, 11), None);
+        use super::serde::Download;
+
+        let download = serde_json::from_str::<Download>(r#"{"url": "", "size": 1234}"#).unwrap();
+        assert_eq!(download.size, Some(1234));
+        let download = serde_json::from_str::<Download>(r#"{"url": "", "size": 5000000000}"#).unwrap();
+        assert_eq!(download.size, Some(5_000_000_000));
 
     }
 
@@ -10,6 +10,7 @@ use std::path::PathBuf;
 use chrono::{DateTime, FixedOffset};
 
 use crate::serde::{HexString, RegexString};
+use crate::download;
 use crate::maven::Gav;
 
 
@@ -114,7 +115,7 @@ pub struct VersionJavaVersion {
 #[serde(rename_all = "camelCase")]
 pub struct VersionAssetIndex {
     pub id: String,
-    pub total_size: u32,
+    pub total_size: u64,
     #[serde(flatten)]
     pub download: Download,
 }
@@ -226,7 +227,7 @@ pub struct AssetIndex {
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 pub struct AssetObject {
-    pub size: u32,
+    pub size: u64,
     pub hash: HexString<20>,
 }
 
@@ -296,6 +297,14 @@ pub struct JvmManifestFileDownloads {
     pub lzma: Option<Download>,
 }
 
+/// The JVM last installed from a provider for a major version, this is specific to
+/// this launcher.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct ProviderJvm {
+    pub name: String,
+    pub version: String,
+}
+
 // ================== //
 //       COMMON       //
 // ================== //
@@ -329,8 +338,24 @@ pub enum RuleAction {
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 pub struct Download {
     pub url: String,
-    pub size: Option<u32>,
+    pub size: Option<u64>,
     pub sha1: Option<HexString<20>>,
+    /// Not used by Mojang, but some third-party metadata provide stronger digests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<Box<HexString<32>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha512: Option<Box<HexString<64>>>,
+}
+
+impl Download {
+
+    /// Return the strongest digest available for this download.
+    pub fn digest(&self) -> Option<download::Digest> {
+        self.sha512.as_deref().map(|sha512| download::Digest::Sha512(sha512.0))
+            .or_else(|| self.sha256.as_deref().map(|sha256| download::Digest::Sha256(sha256.0)))
+            .or_else(|| self.sha1.as_deref().copied().map(download::Digest::Sha1))
+    }
+
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
@@ -0,0 +1,523 @@
+//! Persistent index of verified files, used to avoid computing the digest of files that
+//! have not been modified since their last successful verification.
+//!
+//! This module is internal to the crate and should not be exposed because the format
+//! might change with increasing version and bug fixes.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs::{self, File, Metadata};
+use std::io::{self, BufReader, BufWriter, Write as _};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use std::path::{Path, PathBuf};
+use std::num::NonZero;
+use std::sync::Arc;
+use std::thread;
+
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+
+use crate::path::PathExt;
+use crate::download;
+
+use super::{Error, Result};
+
+
+/// Maximum number of blocking tasks checking files in parallel.
+const MAX_WORKERS: usize = 16;
+
+/// Minimum interval between two progress notifications when checking files.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+
+/// The verification index, loaded from its file and saved back once the installation
+/// has verified and downloaded all files.
+#[derive(Debug)]
+pub(crate) struct VerifyIndex {
+    /// The file where the index is stored.
+    file: PathBuf,
+    /// The verification stamps, shared with the blocking tasks checking files.
+    stamps: Arc<Stamps>,
+    /// Keys of the files checked or recorded since the index has been loaded, these
+    /// files are known to exist when saving the index.
+    touched: HashSet<String>,
+    /// True when the index has been modified and needs to be saved.
+    dirty: bool,
+}
+
+impl VerifyIndex {
+
+    /// Load the verification index from the given file, if the file doesn't exist or is
+    /// invalid then the index is just empty, it will be overwritten when saved.
+    pub fn load(file: PathBuf, force: bool) -> Self {
+
+        let files = File::open(&file).ok()
+            .and_then(|reader| serde_json::from_reader::<_, serde::Index>(BufReader::new(reader)).ok())
+            .map(|index| index.files)
+            .unwrap_or_default();
+
+        Self {
+            file,
+            stamps: Arc::new(Stamps { force, files }),
+            touched: HashSet::new(),
+            dirty: false,
+        }
+
+    }
+
+    /// Check if a file at a given path has the corresponding properties (size and/or
+    /// digest), just like [`super::check_file`], but the digest is only computed if the
+    /// file has been modified since its last successful verification.
+    pub fn check_file(&mut self, file: &Path, size: Option<u64>, digest: Option<&download::Digest>) -> Result<bool> {
+        let (valid, update) = self.stamps.check(file, size, digest)?;
+        self.update(file, update);
+        Ok(valid)
+    }
+
+    /// Check all the given files in parallel on blocking tasks, just like 
+    /// [`Self::check_file`], and return each file with its validity, in the same
+    /// order. The progress closure is regularly called from the calling task with the
+    /// number of checked files and the total, it's never called if there are no files
+    /// to check.
+    pub async fn check_files(&mut self, checks: Vec<FileCheck>, mut progress: impl FnMut(usize, usize)) -> Result<Vec<(FileCheck, bool)>> {
+
+        let total_count = checks.len();
+        let mut valids = vec![false; total_count];
+        let mut updates = Vec::new();
+        let mut error = None;
+
+        if total_count == 0 {
+            return Ok(Vec::new());
+        }
+
+        progress(0, total_count);
+
+        let workers = thread::available_parallelism()
+            .map(NonZero::get)
+            .unwrap_or(1)
+            .min(MAX_WORKERS)
+            .min(total_count);
+
+        let checks = Arc::new(checks);
+        let next = Arc::new(AtomicUsize::new(0));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut tasks = JoinSet::new();
+
+        for _ in 0..workers {
+            let stamps = Arc::clone(&self.stamps);
+            let checks = Arc::clone(&checks);
+            let next = Arc::clone(&next);
+            let tx = tx.clone();
+            tasks.spawn_blocking(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(check) = checks.get(index) else {
+                    break;
+                };
+                let res = stamps.check(&check.file, check.size, check.digest.as_ref());
+                if tx.send((index, res)).is_err() {
+                    break;
+                }
+            });
+        }
+
+        // Drop our sender so that the receiver below terminates with the workers.
+        drop(tx);
+
+        let mut count = 0;
+        let mut last_progress = Instant::now();
+        while let Some((index, res)) = rx.recv().await {
+            match res {
+                Ok((valid, update)) => {
+                    valids[index] = valid;
+                    updates.push((index, update));
+                }
+                Err(e) => {
+                    // Stop all workers on the first error.
+                    next.store(total_count, Ordering::Relaxed);
+                    error.get_or_insert(e);
+                }
+            }
+            count += 1;
+            if count == total_count || last_progress.elapsed() >= PROGRESS_INTERVAL {
+                progress(count, total_count);
+                last_progress = Instant::now();
+            }
+        }
+
+        // All workers have dropped their sender, wait for them to release the shared
+        // stamps and checks.
+        while let Some(res) = tasks.join_next().await {
+            res.expect("file check task should not be cancelled nor panicking");
+        }
+
+        let checks = Arc::into_inner(checks).unwrap();
+
+        // Updates are applied even on error, for files that have been checked.
+        for (index, update) in updates {
+            self.update(&checks[index].file, update);
+        }
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(checks.into_iter().zip(valids).collect()),
+        }
+
+    }
+
+    /// Apply the given update to the stamp of the given file.
+    fn update(&mut self, file: &Path, update: StampUpdate) {
+        let Some(key) = file.to_str() else {
+            return;
+        };
+        if !self.touched.contains(key) {
+            self.touched.insert(key.to_string());
+        }
+        match update {
+            StampUpdate::Keep => {}
+            StampUpdate::Insert(stamp) => {
+                Arc::make_mut(&mut self.stamps).files.insert(key.to_string(), stamp);
+                self.dirty = true;
+            }
+            StampUpdate::Remove => {
+                if Arc::make_mut(&mut self.stamps).files.remove(key).is_some() {
+                    self.dirty = true;
+                }
+            }
+        }
+    }
+
+    /// Record a file that has just been downloaded and verified against the given
+    /// digest, so it doesn't need to be hashed on the next verification.
+    pub fn record_file(&mut self, file: &Path, digest: &download::Digest) {
+        if let Ok(metadata) = file.metadata() 
+        && let Some(stamp) = serde::Stamp::new(&metadata, digest) {
+            self.update(file, StampUpdate::Insert(stamp));
+        }
+    }
+
+    /// Forget the stamp of the given file, if any, this is used for temporary files.
+    pub fn forget_file(&mut self, file: &Path) {
+        self.update(file, StampUpdate::Remove);
+    }
+
+    /// Save the index to its file, only if it has been modified. The stamps of files
+    /// that no longer exist are dropped, so that the index doesn't grow with removed
+    /// files, the stamps of files used by other versions are kept. The index is first
+    /// written to a temporary file that is then renamed, so that the index is never
+    /// partially written.
+    pub fn save(&mut self) -> Result<()> {
+
+        let touched = &self.touched;
+        let files = &mut Arc::make_mut(&mut self.stamps).files;
+        let stamps_count = files.len();
+        files.retain(|key, _| touched.contains(key) || Path::new(key).is_file());
+        if files.len() != stamps_count {
+            self.dirty = true;
+        }
+
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if let Some(parent) = self.file.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| Error::new_io_file(e, parent))?;
+        }
+
+        let tmp_file = self.file.temp_sibling();
+        let mut writer = File::create(&tmp_file)
+            .map_err(|e| Error::new_io_file(e, &tmp_file))
+            .map(BufWriter::new)?;
+
+        serde_json::to_writer(&mut writer, &serde::IndexRef { files: &self.stamps.files })
+            .map_err(io::Error::from)
+            .and_then(|()| writer.flush())
+            .map_err(|e| Error::new_io_file(e, &tmp_file))?;
+        drop(writer);
+
+        fs::rename(&tmp_file, &self.file)
+            .map_err(|e| Error::new_io_file(e, &self.file))?;
+
+        self.dirty = false;
+        Ok(())
+
+    }
+
+}
+
+/// The verification stamps of an index, shared with the blocking tasks checking files
+/// in parallel.
+#[derive(Debug, Clone)]
+struct Stamps {
+    /// If true, stamps are ignored and all files are hashed again, stamps are still
+    /// recorded for later verifications.
+    force: bool,
+    /// All verification stamps, mapped by the verified file.
+    files: HashMap<String, serde::Stamp>,
+}
+
+impl Stamps {
+
+    /// Check a file without modifying the stamps, returning its validity and how its
+    /// stamp needs to be updated in the index.
+    fn check(&self, file: &Path, size: Option<u64>, digest: Option<&download::Digest>) -> Result<(bool, StampUpdate)> {
+
+        // Without digest there is nothing to hash, so nothing to record.
+        let Some(digest) = digest else {
+            return Ok((super::check_file(file, size, None)?, StampUpdate::Keep));
+        };
+
+        // Non UTF-8 paths are never recorded, they are always checked.
+        let Some(key) = file.to_str() else {
+            return Ok((super::check_file(file, size, Some(digest))?, StampUpdate::Keep));
+        };
+
+        self.check_inner(file, key, size, digest)
+            .map_err(|e| Error::new_io(e, format!("check file: {}", file.display())))
+
+    }
+
+    fn check_inner(&self, file: &Path, key: &str, size: Option<u64>, digest: &download::Digest) -> io::Result<(bool, StampUpdate)> {
+
+        let reader = match File::open(file) {
+            Ok(reader) => reader,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((false, StampUpdate::Remove)),
+            Err(e) => return Err(e),
+        };
+
+        let metadata = reader.metadata()?;
+        if size.is_some_and(|size| size != metadata.len()) {
+            return Ok((false, StampUpdate::Remove));
+        }
+
+        // The stamp may be absent if the modification time is not supported, in such 
+        // case the file is always hashed.
+        let stamp = serde::Stamp::new(&metadata, digest);
+        if !self.force && stamp.is_some() && self.files.get(key) == stamp.as_ref() {
+            return Ok((true, StampUpdate::Keep));
+        }
+
+        if digest.check_reader(reader)? {
+            Ok((true, stamp.map(StampUpdate::Insert).unwrap_or(StampUpdate::Keep)))
+        } else {
+            Ok((false, StampUpdate::Remove))
+        }
+
+    }
+
+}
+
+/// A file to check, see [`VerifyIndex::check_files`].
+#[derive(Debug)]
+pub(crate) struct FileCheck {
+    pub file: PathBuf,
+    pub size: Option<u64>,
+    pub digest: Option<download::Digest>,
+}
+
+/// How the stamp of a checked file should be updated.
+#[derive(Debug)]
+enum StampUpdate {
+    Keep,
+    Insert(serde::Stamp),
+    Remove,
+}
+
+/// Return the inode of the file, or zero on platforms where it's not supported.
+fn metadata_inode(metadata: &Metadata) -> u64 {
+    #[cfg(unix)] {
+        std::os::unix::fs::MetadataExt::ino(metadata)
+    }
+    #[cfg(not(unix))] {
+        let _ = metadata;
+        0
+    }
+}
+
+/// Return the modification time of the file, in nanoseconds since the UNIX epoch, if
+/// supported.
+fn metadata_mtime(metadata: &Metadata) -> Option<u64> {
+    metadata.modified().ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos() as u64)
+}
+
+/// Format the given digest as `<algorithm>:<hex>`.
+fn format_digest(digest: &download::Digest) -> String {
+    let mut buf = format!("{}:", digest.algorithm());
+    for b in digest.as_bytes() {
+        write!(buf, "{b:02x}").unwrap();
+    }
+    buf
+}
+
+mod serde {
+
+    use std::collections::HashMap;
+    use std::fs::Metadata;
+
+    use crate::download;
+
+    #[derive(Debug, serde::Deserialize)]
+    pub struct Index {
+        pub files: HashMap<String, Stamp>,
+    }
+
+    #[derive(Debug, serde::Serialize)]
+    pub struct IndexRef<'a> {
+        pub files: &'a HashMap<String, Stamp>,
+    }
+
+    /// The verification stamp of a file, the file is considered unchanged if all these
+    /// properties are equal.
+    #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+    pub struct Stamp {
+        /// Size of the file.
+        pub size: u64,
+        /// Modification time of the file, in nanoseconds since the UNIX epoch.
+        pub mtime: u64,
+        /// Inode of the file, zero if not supported.
+        pub inode: u64,
+        /// The digest the file has been verified against, `<algorithm>:<hex>`.
+        pub digest: String,
+    }
+
+    impl Stamp {
+        pub fn new(metadata: &Metadata, digest: &download::Digest) -> Option<Self> {
+            Some(Self {
+                size: metadata.len(),
+                mtime: super::metadata_mtime(metadata)?,
+                inode: super::metadata_inode(metadata),
+                digest: super::format_digest(digest),
+            })
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs::{self, File};
+
+    use crate::download::Digest;
+
+    use super::{VerifyIndex, FileCheck};
+
+    #[test]
+    fn verify_index() {
+
+        let dir = tempfile::tempdir().unwrap();
+        let index_file = dir.path().join("verify_index.json");
+        let file = dir.path().join("file");
+        let digest = Digest::Sha1(*b"\xd3\x48\x6a\xe9\x13\x6e\x78\x56\xbc\x42\x21\x23\x85\xea\x79\x70\x94\x47\x58\x02");
+        fs::write(&file, "Hello world!").unwrap();
+
+        let mut index = VerifyIndex::load(index_file.clone(), false);
+        assert!(index.check_file(&file, Some(12), Some(&digest)).unwrap());
+        assert!(!index.check_file(&file, Some(13), Some(&digest)).unwrap());
+        assert!(index.check_file(&file, Some(12), Some(&digest)).unwrap());
+        index.save().unwrap();
+        assert!(index_file.is_file());
+
+        // Modify the file content in place without changing its size and modification
+        // time, the file is not hashed again so it's still considered valid...
+        let mtime = fs::metadata(&file).unwrap().modified().unwrap();
+        fs::write(&file, "Hello world?").unwrap();
+        File::options().write(true).open(&file).unwrap().set_modified(mtime).unwrap();
+
+        let mut index = VerifyIndex::load(index_file.clone(), false);
+        assert!(index.check_file(&file, Some(12), Some(&digest)).unwrap());
+
+        // ... but it is when forcing verification.
+        let mut index = VerifyIndex::load(index_file.clone(), true);
+        assert!(!index.check_file(&file, Some(12), Some(&digest)).unwrap());
+        index.save().unwrap();
+
+        // The stamp has been removed because the file is invalid.
+        let mut index = VerifyIndex::load(index_file.clone(), false);
+        assert!(!index.check_file(&file, Some(12), Some(&digest)).unwrap());
+
+    }
+
+    #[test]
+    fn verify_index_parallel() {
+
+        let dir = tempfile::tempdir().unwrap();
+        let digest = Digest::Sha1(*b"\xd3\x48\x6a\xe9\x13\x6e\x78\x56\xbc\x42\x21\x23\x85\xea\x79\x70\x94\x47\x58\x02");
+
+        // Every third file is invalid, and every fifth file is missing.
+        let checks = (0..100usize).map(|i| {
+            let file = dir.path().join(format!("file{i}"));
+            if i % 5 != 0 {
+                fs::write(&file, if i % 3 == 0 { "Hello world?" } else { "Hello world!" }).unwrap();
+            }
+            FileCheck { file, size: Some(12), digest: Some(digest) }
+        }).collect::<Vec<_>>();
+
+        let mut index = VerifyIndex::load(dir.path().join("verify_index.json"), false);
+        let mut last_progress = (0, 0);
+        let results = crate::tokio::sync(index.check_files(checks, |count, total_count| {
+            assert!(count >= last_progress.0);
+            last_progress = (count, total_count);
+        })).unwrap();
+
+        assert_eq!(last_progress, (100, 100));
+        for (i, (check, valid)) in results.iter().enumerate() {
+            assert_eq!(check.file, dir.path().join(format!("file{i}")));
+            assert_eq!(*valid, i % 5 != 0 && i % 3 != 0, "file{i}");
+        }
+
+        // Stamps of valid files have been recorded.
+        assert!(index.check_file(&results[1].0.file, Some(12), Some(&digest)).unwrap());
+        assert_eq!(index.stamps.files.len(), 100 - 20 - 27);
+
+        // No progress is reported when there is nothing to check.
+        assert!(crate::tokio::sync(index.check_files(Vec::new(), |_, _| panic!())).unwrap().is_empty());
+
+    }
+
+    #[test]
+    fn verify_index_prune() {
+
+        let dir = tempfile::tempdir().unwrap();
+        let index_file = dir.path().join("verify_index.json");
+        let digest = Digest::Sha1(*b"\xd3\x48\x6a\xe9\x13\x6e\x78\x56\xbc\x42\x21\x23\x85\xea\x79\x70\x94\x47\x58\x02");
+
+        let used_file = dir.path().join("used");
+        let untouched_file = dir.path().join("untouched");
+        let removed_file = dir.path().join("removed");
+        for file in [&used_file, &untouched_file, &removed_file] {
+            fs::write(file, "Hello world!").unwrap();
+        }
+
+        let mut index = VerifyIndex::load(index_file.clone(), false);
+        index.record_file(&used_file, &digest);
+        index.record_file(&untouched_file, &digest);
+        index.record_file(&removed_file, &digest);
+        index.save().unwrap();
+
+        // Only the used file is checked, the untouched file still exists so its stamp
+        // is kept, as if it was used by another version, but the removed file's stamp
+        // is dropped.
+        fs::remove_file(&removed_file).unwrap();
+        let mut index = VerifyIndex::load(index_file.clone(), false);
+        assert_eq!(index.stamps.files.len(), 3);
+        assert!(index.check_file(&used_file, Some(12), Some(&digest)).unwrap());
+        index.save().unwrap();
+
+        let mut index = VerifyIndex::load(index_file.clone(), false);
+        assert_eq!(index.stamps.files.len(), 2);
+        assert!(index.stamps.files.contains_key(used_file.to_str().unwrap()));
+        assert!(index.stamps.files.contains_key(untouched_file.to_str().unwrap()));
+
+        // The untouched file is still not hashed again, even if modified in place.
+        let mtime = fs::metadata(&untouched_file).unwrap().modified().unwrap();
+        fs::write(&untouched_file, "Hello world?").unwrap();
+        File::options().write(true).open(&untouched_file).unwrap().set_modified(mtime).unwrap();
+        assert!(index.check_file(&untouched_file, Some(12), Some(&digest)).unwrap());
+
+    }
+
+}
@@ -5,76 +5,124 @@
 
 use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
 use std::iter::FusedIterator;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
+use std::time::{Duration, Instant};
 use std::cmp::Ordering;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::{env, mem};
-use std::sync::Arc;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::{Arc, Mutex};
 use std::error;
 
-use sha1::{Digest, Sha1};
+use sha1::{Digest as _, Sha1};
+use sha2::{Sha256, Sha512};
 
-use reqwest::{Client, StatusCode, header};
+use reqwest::{Certificate, Client, Proxy, StatusCode, Url, header};
 
-use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use once_cell::sync::OnceCell;
+
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use tokio::fs::{self, File};
 use tokio::task::JoinSet;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Notify, Semaphore};
 
-use crate::path::PathBufExt;
+use crate::path::{PathBufExt, PathExt};
 
 
 /// Download a single entry from the given URL to the given file.
 pub fn single(url: impl Into<Box<str>>, file: impl Into<Box<Path>>) -> Single {
-    Single(Entry::new(url.into(), file.into()))
+    Single { entry: Entry::new(url.into(), file.into()), config: Config::default(), cancel_token: None }
 }
 
 /// Download a single cached entry.
 pub fn single_cached(url: impl Into<Box<str>>) -> Single {
-    Single(Entry::new_cached(url.into()))
+    Single { entry: Entry::new_cached(url.into()), config: Config::default(), cancel_token: None }
 }
 
 #[derive(Debug)]
-pub struct Single(Entry);
+pub struct Single {
+    /// The entry to be downloaded.
+    entry: Entry,
+    /// Configuration of the download.
+    config: Config,
+    /// Optional cancellation token that is checked while downloading.
+    cancel_token: Option<CancelToken>,
+}
 
 impl Single {
 
     #[inline]
     pub fn url(&self) -> &str {
-        self.0.url()
+        self.entry.url()
     }
 
     #[inline]
     pub fn file(&self) -> &Path {
-        self.0.file()
+        self.entry.file()
     }
 
     #[inline]
-    pub fn set_expected_size(&mut self, size: Option<u32>) -> &mut Self {
-        self.0.set_expected_size(size);
+    pub fn set_expected_size(&mut self, size: Option<u64>) -> &mut Self {
+        self.entry.set_expected_size(size);
         self
     }
 
     #[inline]
     pub fn set_expected_sha1(&mut self, sha1: Option<[u8; 20]>) -> &mut Self {
-        self.0.set_expected_sha1(sha1);
+        self.entry.set_expected_sha1(sha1);
+        self
+    }
+
+    #[inline]
+    pub fn set_expected_digest(&mut self, digest: Option<Digest>) -> &mut Self {
+        self.entry.set_expected_digest(digest);
         self
     }
 
     #[inline]
     pub fn set_keep_open(&mut self) -> &mut Self {
-        self.0.set_keep_open();
+        self.entry.set_keep_open();
         self
     }
 
     #[inline]
     pub fn set_use_cache(&mut self) -> &mut Self {
-        self.0.set_use_cache();
+        self.entry.set_use_cache();
         self
     }
 
     #[inline]
     pub fn set_max_retry(&mut self, count: u8) -> &mut Self {
-        self.0.set_max_retry(count);
+        self.entry.set_max_retry(count);
+        self
+    }
+
+    #[inline]
+    pub fn set_retry_policy(&mut self, policy: Option<RetryPolicy>) -> &mut Self {
+        self.entry.set_retry_policy(policy);
+        self
+    }
+
+    #[inline]
+    pub fn add_fallback_url(&mut self, url: impl Into<Box<str>>) -> &mut Self {
+        self.entry.add_fallback_url(url);
+        self
+    }
+
+    /// Set the download configuration, see [`Batch::set_config`], the concurrency 
+    /// settings are ignored when downloading a single entry.
+    #[inline]
+    pub fn set_config(&mut self, config: Config) -> &mut Self {
+        self.config = config;
+        self
+    }
+
+    /// Set the cancellation token to check while downloading, see 
+    /// [`Batch::set_cancel_token`].
+    #[inline]
+    pub fn set_cancel_token(&mut self, token: Option<CancelToken>) -> &mut Self {
+        self.cancel_token = token;
         self
     }
 
@@ -82,17 +130,30 @@ impl Single {
     /// result.
     /// 
     /// This is internally starting an asynchronous Tokio runtime and block on it, so
-    /// this function will just panic if launched inside another runtime!
+    /// this function will just panic if launched inside another runtime! Use 
+    /// [`Self::download_async`] instead in such case.
     #[must_use]
     pub fn download(&mut self, mut handler: impl Handler) -> Result<EntrySuccess, EntryError> {
+        crate::tokio::sync(self.download_impl(&mut handler as &mut dyn Handler))
+    }
+
+    /// Async version of [`Self::download`], to be awaited from an existing Tokio 
+    /// runtime.
+    pub async fn download_async(&mut self, mut handler: impl Handler + Send) -> Result<EntrySuccess, EntryError> {
+        self.download_impl(&mut handler as &mut (dyn Handler + Send)).await
+    }
 
-        let client = crate::http::client()
-            .map_err(|e| EntryError { 
-                core: self.0.core.clone(), 
-                kind: EntryErrorKind::new_reqwest(e),
-            })?;
+    /// Internal download function, generic over the handler so that it can be used 
+    /// with both sendable or non-sendable handlers.
+    pub(crate) async fn download_impl<H: Handler + ?Sized>(&mut self, handler: &mut H) -> Result<EntrySuccess, EntryError> {
 
-        crate::tokio::sync(download_single(client, &mut handler, &self.0))
+        let client = self.config.http_client()
+            .map_err(|e| EntryError::new(
+                self.entry.core.clone(), 
+                EntryErrorInner::new(EntryErrorKind::new_reqwest(e), &self.entry.core.url),
+            ))?;
+
+        download_single(client, handler, &self.config, &self.entry, self.cancel_token.as_ref()).await
 
     }
 
@@ -103,6 +164,10 @@ impl Single {
 pub struct Batch {
     /// All entries to be downloaded.
     entries: Vec<Entry>,
+    /// Configuration of the download.
+    config: Config,
+    /// Optional cancellation token that is checked while downloading.
+    cancel_token: Option<CancelToken>,
 }
 
 impl Batch {
@@ -112,6 +177,8 @@ impl Batch {
     pub fn new() -> Self {
         Self {
             entries: Vec::new(),
+            config: Config::default(),
+            cancel_token: None,
         }
     }
 
@@ -121,45 +188,748 @@ impl Batch {
         self.entries.len()
     }
 
-    /// Return true if this batch has no entry.
-    #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.entries.is_empty()
+    /// Return true if this batch has no entry.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Insert a new entry to be downloaded in this download batch.
+    pub fn push(&mut self, url: impl Into<Box<str>>, file: impl Into<Box<Path>>) -> &mut Entry {
+        self.entries.push(Entry::new(url.into(), file.into()));
+        self.entries.last_mut().unwrap()
+    }
+
+    /// Insert a new entry to be downloaded in this download batch, this entry don't
+    /// need a file because it is purely cached and so the file is derived from the URL.
+    /// It is constructed from a standard cache directory called `portablemc-cache` 
+    /// located in a standard user cache directory (or system tmp as a fallback), 
+    /// the file name in that directory is the hash of the URL.
+    pub fn push_cached(&mut self, url: impl Into<Box<str>>) -> &mut Entry {
+        self.entries.push(Entry::new_cached(url.into()));
+        self.entries.last_mut().unwrap()
+    }
+
+    pub fn entry(&self, index: usize) -> &Entry {
+        &self.entries[index]
+    }
+
+    pub fn entry_mut(&mut self, index: usize) -> &mut Entry {
+        &mut self.entries[index]
+    }
+
+    /// Get the download configuration of this batch.
+    #[inline]
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Set the download configuration of this batch, see [`Config`].
+    #[inline]
+    pub fn set_config(&mut self, config: Config) -> &mut Self {
+        self.config = config;
+        self
+    }
+
+    /// Get the cancellation token checked while downloading, if any.
+    #[inline]
+    pub fn cancel_token(&self) -> Option<&CancelToken> {
+        self.cancel_token.as_ref()
+    }
+
+    /// Set the cancellation token to check while downloading. When cancelled, all
+    /// in-flight entries are aborted and their partial files are removed, entries 
+    /// that were not yet started are not downloaded, all of these entries are 
+    /// returned with the [`EntryErrorKind::Cancelled`] error.
+    #[inline]
+    pub fn set_cancel_token(&mut self, token: Option<CancelToken>) -> &mut Self {
+        self.cancel_token = token;
+        self
+    }
+
+    /// Download this whole batch, the batch is cleared if returning ok. It's left 
+    /// untouched if it returns an error and no file is downloaded.
+    /// 
+    /// This is internally starting an asynchronous Tokio runtime and block on it, so
+    /// this function will just panic if launched inside another runtime! Use 
+    /// [`Self::download_async`] instead in such case.
+    pub fn download(&mut self, mut handler: impl Handler) -> reqwest::Result<BatchResult> {
+        crate::tokio::sync(self.download_impl(&mut handler as &mut dyn Handler))
+    }
+
+    /// Async version of [`Self::download`], to be awaited from an existing Tokio 
+    /// runtime.
+    pub async fn download_async(&mut self, mut handler: impl Handler + Send) -> reqwest::Result<BatchResult> {
+        self.download_impl(&mut handler as &mut (dyn Handler + Send)).await
+    }
+
+    /// Internal download function, see [`Single::download_impl`].
+    pub(crate) async fn download_impl<H: Handler + ?Sized>(&mut self, handler: &mut H) -> reqwest::Result<BatchResult> {
+        let client = self.config.http_client()?;
+        let entries = mem::take(&mut self.entries);
+        Ok(download_many(client, handler, &self.config, entries, self.cancel_token.clone()).await)
+    }
+
+}
+
+/// Configuration of the download engine and its underlying HTTP client, it can be set
+/// on [`Batch`] and [`Single`] downloads and it's also used by installers and 
+/// authentication. Cloning this configuration is cheap and the lazily built HTTP 
+/// client is shared between clones.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Maximum number of entries being downloaded concurrently.
+    concurrency: usize,
+    /// Optional maximum number of entries being downloaded concurrently from the same
+    /// host.
+    host_concurrency: Option<usize>,
+    /// Optional timeout of the whole request, including the response body.
+    timeout: Option<Duration>,
+    /// Optional timeout for establishing the connection.
+    connect_timeout: Option<Duration>,
+    /// Optional timeout for each read operation on the response.
+    read_timeout: Option<Duration>,
+    /// Optional proxy to use for all requests.
+    proxy: Option<Proxy>,
+    /// Additional root certificates trusted by the HTTP client.
+    root_certificates: Vec<Certificate>,
+    /// Optional user agent that overrides the default one.
+    user_agent: Option<String>,
+    /// A user-supplied client, if present all other client settings are ignored.
+    client: Option<Client>,
+    /// The client built from these settings, shared between clones and reset when
+    /// a client setting is modified.
+    built_client: Arc<OnceCell<Client>>,
+    /// The mirror rules used to rewrite URLs, shared between clones.
+    mirrors: Arc<Mirrors>,
+    /// The default retry policy of entries.
+    retry_policy: RetryPolicy,
+    /// Optional bandwidth limit shared by all entries.
+    rate_limit: Option<RateLimit>,
+    /// Optional local directory where URLs are first looked up.
+    local_root: Option<PathBuf>,
+    /// True to hard link local files instead of copying them.
+    local_link: bool,
+    /// True to forbid any network access.
+    offline: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            concurrency: 40,
+            host_concurrency: None,
+            timeout: None,
+            connect_timeout: None,
+            read_timeout: None,
+            proxy: None,
+            root_certificates: Vec::new(),
+            user_agent: None,
+            client: None,
+            built_client: Arc::default(),
+            mirrors: Arc::default(),
+            retry_policy: RetryPolicy::default(),
+            rate_limit: None,
+            local_root: None,
+            local_link: false,
+            offline: false,
+        }
+    }
+}
+
+impl Config {
+
+    /// Create a new default configuration.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of entries being downloaded concurrently in a batch, defaults 
+    /// to 40.
+    #[inline]
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// See [`Self::concurrency`], a zero concurrency is interpreted as one.
+    #[inline]
+    pub fn set_concurrency(&mut self, count: usize) -> &mut Self {
+        self.concurrency = count.max(1);
+        self
+    }
+
+    /// Maximum number of entries being downloaded concurrently from the same host, 
+    /// this is unlimited by default, and it's always bounded by [`Self::concurrency`].
+    #[inline]
+    pub fn host_concurrency(&self) -> Option<usize> {
+        self.host_concurrency
+    }
+
+    /// See [`Self::host_concurrency`], a zero concurrency is interpreted as one.
+    #[inline]
+    pub fn set_host_concurrency(&mut self, count: Option<usize>) -> &mut Self {
+        self.host_concurrency = count.map(|count| count.max(1));
+        self
+    }
+
+    /// Timeout of the whole request, from the connection to the end of the response 
+    /// body, there is no timeout by default. Note that this includes the time taken to
+    /// download big files, you should prefer [`Self::read_timeout`].
+    #[inline]
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// See [`Self::timeout`].
+    #[inline]
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.timeout = timeout;
+        self.reset_client()
+    }
+
+    /// Timeout for establishing the connection, there is no timeout by default.
+    #[inline]
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    /// See [`Self::connect_timeout`].
+    #[inline]
+    pub fn set_connect_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.connect_timeout = timeout;
+        self.reset_client()
+    }
+
+    /// Timeout for each read operation on the response, the timer is reset after each
+    /// successful read, there is no timeout by default.
+    #[inline]
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.read_timeout
+    }
+
+    /// See [`Self::read_timeout`].
+    #[inline]
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.read_timeout = timeout;
+        self.reset_client()
+    }
+
+    /// The proxy used for all requests, by default the system proxy is used, as 
+    /// defined by the `HTTP_PROXY`, `HTTPS_PROXY` and `ALL_PROXY` environment variables.
+    #[inline]
+    pub fn proxy(&self) -> Option<&Proxy> {
+        self.proxy.as_ref()
+    }
+
+    /// See [`Self::proxy`].
+    #[inline]
+    pub fn set_proxy(&mut self, proxy: Option<Proxy>) -> &mut Self {
+        self.proxy = proxy;
+        self.reset_client()
+    }
+
+    /// Additional root certificates trusted by the HTTP client, in addition to the 
+    /// system ones, this can be used to trust a private certificate authority.
+    #[inline]
+    pub fn root_certificates(&self) -> &[Certificate] {
+        &self.root_certificates
+    }
+
+    /// See [`Self::root_certificates`].
+    #[inline]
+    pub fn add_root_certificate(&mut self, certificate: Certificate) -> &mut Self {
+        self.root_certificates.push(certificate);
+        self.reset_client()
+    }
+
+    /// The user agent given on each request, defaults to `portablemc/<version>`.
+    #[inline]
+    pub fn user_agent(&self) -> &str {
+        self.user_agent.as_deref().unwrap_or(crate::http::USER_AGENT)
+    }
+
+    /// See [`Self::user_agent`].
+    #[inline]
+    pub fn set_user_agent(&mut self, user_agent: impl Into<String>) -> &mut Self {
+        self.user_agent = Some(user_agent.into());
+        self.reset_client()
+    }
+
+    /// The user-supplied HTTP client, if any.
+    #[inline]
+    pub fn client(&self) -> Option<&Client> {
+        self.client.as_ref()
+    }
+
+    /// Set a user-supplied HTTP client to use for all requests, when set, all other
+    /// client settings (timeouts, proxy, root certificates, user agent) are ignored
+    /// and should be configured on that client.
+    #[inline]
+    pub fn set_client(&mut self, client: Option<Client>) -> &mut Self {
+        self.client = client;
+        self
+    }
+
+    /// Iterator over all mirror rules, in order, as `(prefix, mirror)` tuples.
+    pub fn mirrors(&self) -> impl Iterator<Item = (&str, &str)> + use<'_> {
+        self.mirrors.rules.iter()
+            .map(|rule| (&*rule.prefix, &*rule.mirror))
+    }
+
+    /// Add a mirror rule: any URL starting with the given prefix is first requested
+    /// with that prefix replaced by the given mirror. Several mirrors can be added for
+    /// the same prefix, or overlapping prefixes, they are all tried in the order they
+    /// were added, and the original URL is tried last if mirror fallback is enabled.
+    /// 
+    /// This applies to all downloads and to all requests made by the installers, for 
+    /// example `https://libraries.minecraft.net/` can be mirrored to 
+    /// `https://bmclapi2.bangbang93.com/maven/`.
+    #[inline]
+    pub fn add_mirror(&mut self, prefix: impl Into<Box<str>>, mirror: impl Into<Box<str>>) -> &mut Self {
+        Arc::make_mut(&mut self.mirrors).rules.push(MirrorRule { 
+            prefix: prefix.into(), 
+            mirror: mirror.into(),
+        });
+        self
+    }
+
+    /// Remove all mirror rules.
+    #[inline]
+    pub fn clear_mirrors(&mut self) -> &mut Self {
+        Arc::make_mut(&mut self.mirrors).rules.clear();
+        self
+    }
+
+    /// When enabled, the original URL is tried after all of its mirrors have failed,
+    /// this is enabled by default. When disabled, mirror rules just rewrite the URLs.
+    #[inline]
+    pub fn mirror_fallback(&self) -> bool {
+        self.mirrors.fallback
+    }
+
+    /// See [`Self::mirror_fallback`].
+    #[inline]
+    pub fn set_mirror_fallback(&mut self, fallback: bool) -> &mut Self {
+        Arc::make_mut(&mut self.mirrors).fallback = fallback;
+        self
+    }
+
+    /// Return the ordered list of URLs to request for the given URL, according to the
+    /// mirror rules, this list is never empty and only contains the given URL if no
+    /// rule applies to it.
+    #[inline]
+    pub fn mirror_urls(&self, url: &str) -> Vec<String> {
+        self.mirrors.urls(url)
+    }
+
+    /// The default retry policy for all entries that don't have their own policy, see
+    /// [`Entry::set_retry_policy`].
+    #[inline]
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// See [`Self::retry_policy`].
+    #[inline]
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// The bandwidth limit shared by all entries downloaded with this configuration, 
+    /// there is no limit by default. The limit can be changed at any time through the
+    /// [`RateLimit`] handle, even while downloading.
+    #[inline]
+    pub fn rate_limit(&self) -> Option<&RateLimit> {
+        self.rate_limit.as_ref()
+    }
+
+    /// See [`Self::rate_limit`].
+    #[inline]
+    pub fn set_rate_limit(&mut self, rate_limit: Option<RateLimit>) -> &mut Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// A local directory where the files to download are first looked up, before 
+    /// requesting them, this can be used to install from a prepared copy of the 
+    /// upstream files without network access. The file of an URL is located at 
+    /// `<root>/<host>/<path>`, for example the URL 
+    /// `https://libraries.minecraft.net/foo/bar.jar` is looked up at 
+    /// `<root>/libraries.minecraft.net/foo/bar.jar`. If the file is missing or invalid,
+    /// the URL is requested as usual. There is no local root by default.
+    /// 
+    /// Note that entries can also directly have `file://` URLs.
+    #[inline]
+    pub fn local_root(&self) -> Option<&Path> {
+        self.local_root.as_deref()
+    }
+
+    /// See [`Self::local_root`].
+    #[inline]
+    pub fn set_local_root(&mut self, root: Option<PathBuf>) -> &mut Self {
+        self.local_root = root;
+        self
+    }
+
+    /// When enabled, local files (from `file://` URLs or the local root) are hard 
+    /// linked to the entry file after being checked, instead of being copied, if the
+    /// link fails the file is copied anyway. The handle of a linked entry kept open is
+    /// read-only. Disabled by default.
+    #[inline]
+    pub fn local_link(&self) -> bool {
+        self.local_link
+    }
+
+    /// See [`Self::local_link`].
+    #[inline]
+    pub fn set_local_link(&mut self, link: bool) -> &mut Self {
+        self.local_link = link;
+        self
+    }
+
+    /// True if no network access should be made, `file://` URLs and the local root are
+    /// still used, cached entries are returned as-is and any entry that would need a 
+    /// network request fails with [`EntryErrorKind::Offline`], defaults to false.
+    #[inline]
+    pub fn offline(&self) -> bool {
+        self.offline
+    }
+
+    /// See [`Self::offline`].
+    #[inline]
+    pub fn set_offline(&mut self, offline: bool) -> &mut Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Return the local file of the given URL in the local root, if any and if the URL
+    /// has a host, the file may not exist.
+    pub fn local_file(&self, url: &str) -> Option<PathBuf> {
+        
+        let root = self.local_root.as_deref()?;
+        let url = Url::parse(url).ok()?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return None;
+        }
+
+        let mut file = root.join(url.host_str()?);
+        for segment in url.path_segments()? {
+            if !segment.is_empty() {
+                file.push(segment);
+            }
+        }
+
+        Some(file)
+
+    }
+
+    /// Internal function to invalidate the built client after a settings change.
+    #[inline]
+    fn reset_client(&mut self) -> &mut Self {
+        self.built_client = Arc::default();
+        self
+    }
+
+    /// Get the HTTP client to use with this configuration, the user-supplied one, the
+    /// one built from the settings or the default one if there are no settings.
+    pub(crate) fn http_client(&self) -> reqwest::Result<Client> {
+        
+        if let Some(client) = &self.client {
+            return Ok(client.clone());
+        }
+
+        let default = self.timeout.is_none()
+            && self.connect_timeout.is_none()
+            && self.read_timeout.is_none()
+            && self.proxy.is_none()
+            && self.root_certificates.is_empty()
+            && self.user_agent.is_none();
+
+        if default {
+            return crate::http::client();
+        }
+
+        self.built_client.get_or_try_init(|| {
+
+            let mut builder = crate::http::builder()
+                .user_agent(self.user_agent());
+
+            if let Some(timeout) = self.timeout {
+                builder = builder.timeout(timeout);
+            }
+
+            if let Some(timeout) = self.connect_timeout {
+                builder = builder.connect_timeout(timeout);
+            }
+
+            if let Some(timeout) = self.read_timeout {
+                builder = builder.read_timeout(timeout);
+            }
+
+            if let Some(proxy) = &self.proxy {
+                builder = builder.proxy(proxy.clone());
+            }
+
+            for certificate in &self.root_certificates {
+                builder = builder.add_root_certificate(certificate.clone());
+            }
+
+            builder.build()
+
+        }).cloned()
+
+    }
+
+}
+
+/// Retry policy of a download entry, the entry is retried on transient errors: 
+/// connection errors, timeouts, interrupted response body and the HTTP status codes 408, 
+/// 429, 500, 502, 503 and 504. Between each try, the delay grows exponentially from the 
+/// base delay up to the maximum delay, the `Retry-After` header is respected, if 
+/// present, within the maximum delay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retry: u8,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retry: 2,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+
+    /// Create a new default retry policy.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of retries, defaults to 2, it can be overridden per entry with
+    /// [`Entry::set_max_retry`].
+    #[inline]
+    pub fn max_retry(&self) -> u8 {
+        self.max_retry
+    }
+
+    /// See [`Self::max_retry`].
+    #[inline]
+    pub fn set_max_retry(&mut self, count: u8) -> &mut Self {
+        self.max_retry = count;
+        self
+    }
+
+    /// The delay before the first retry, that is doubled on each retry, defaults to 
+    /// 500 milliseconds.
+    #[inline]
+    pub fn base_delay(&self) -> Duration {
+        self.base_delay
+    }
+
+    /// See [`Self::base_delay`].
+    #[inline]
+    pub fn set_base_delay(&mut self, delay: Duration) -> &mut Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// The maximum delay between two tries, defaults to 30 seconds.
+    #[inline]
+    pub fn max_delay(&self) -> Duration {
+        self.max_delay
+    }
+
+    /// See [`Self::max_delay`].
+    #[inline]
+    pub fn set_max_delay(&mut self, delay: Duration) -> &mut Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// When enabled, the delay is randomized between half and the full delay, this
+    /// avoids all entries retrying at the same time, enabled by default.
+    #[inline]
+    pub fn jitter(&self) -> bool {
+        self.jitter
+    }
+
+    /// See [`Self::jitter`].
+    #[inline]
+    pub fn set_jitter(&mut self, jitter: bool) -> &mut Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Compute the delay before the given retry, starting at zero for the first retry.
+    pub fn delay(&self, retry_num: u8) -> Duration {
+        
+        let delay = self.base_delay
+            .saturating_mul(1u32.checked_shl(retry_num as u32).unwrap_or(u32::MAX))
+            .min(self.max_delay);
+
+        if self.jitter {
+            // We don't need a strong randomness, so we use the random keys of the std.
+            let random = RandomState::new().hash_one(retry_num) as u32;
+            delay / 2 + (delay / 2).mul_f64(random as f64 / u32::MAX as f64)
+        } else {
+            delay
+        }
+
+    }
+
+}
+
+/// A bandwidth limit handle that can be cloned and shared across threads, all clones 
+/// share the same state. The limit is given in bytes per second and is shared by all
+/// entries being downloaded with it, it can be changed at any time and is immediately
+/// applied to running downloads.
+#[derive(Debug, Clone)]
+pub struct RateLimit(Arc<Mutex<RateLimitState>>);
+
+#[derive(Debug)]
+struct RateLimitState {
+    /// The limit in bytes per second, none if unlimited.
+    limit: Option<u64>,
+    /// The number of bytes that can be read without waiting, negative when the 
+    /// downloads are ahead of the limit.
+    available: f64,
+    /// The last time the available bytes were refilled.
+    last_refill: Instant,
+}
+
+impl RateLimit {
+
+    /// The maximum time to sleep at once before checking the limit again, so that
+    /// changes of the limit are quickly applied.
+    const MAX_WAIT: Duration = Duration::from_millis(100);
+
+    /// Create a new bandwidth limit, in bytes per second, a zero limit is interpreted
+    /// as one byte per second.
+    pub fn new(limit: Option<u64>) -> Self {
+        Self(Arc::new(Mutex::new(RateLimitState {
+            limit: limit.map(|limit| limit.max(1)),
+            available: 0.0,
+            last_refill: Instant::now(),
+        })))
+    }
+
+    /// Get the current limit, in bytes per second, none if unlimited.
+    pub fn limit(&self) -> Option<u64> {
+        self.0.lock().unwrap().limit
+    }
+
+    /// Change the limit of this handle and all of its clones, see [`Self::new`].
+    pub fn set_limit(&self, limit: Option<u64>) {
+        let mut state = self.0.lock().unwrap();
+        state.refill();
+        state.limit = limit.map(|limit| limit.max(1));
+        if let Some(limit) = state.limit {
+            state.available = state.available.min(limit as f64);
+        }
+    }
+
+    /// Consume the given number of bytes and wait until the limit allows it.
+    pub(crate) async fn acquire(&self, size: u64) {
+        
+        let mut size = size as f64;
+        
+        loop {
+
+            let wait = {
+                
+                let mut state = self.0.lock().unwrap();
+                state.refill();
+
+                let Some(limit) = state.limit else {
+                    return;
+                };
+
+                // Consume the bytes only once, we then wait for the debt to be paid.
+                state.available -= mem::take(&mut size);
+                if state.available >= 0.0 {
+                    return;
+                }
+
+                Duration::from_secs_f64(-state.available / limit as f64).min(Self::MAX_WAIT)
+
+            };
+
+            tokio::time::sleep(wait).await;
+
+        }
+
+    }
+
+}
+
+impl RateLimitState {
+
+    /// Refill the available bytes depending on the time elapsed since last refill, 
+    /// it's capped to one second of transfer to avoid bursts after idle times.
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+        self.available = match self.limit {
+            Some(limit) => (self.available + elapsed * limit as f64).min(limit as f64),
+            None => 0.0,
+        };
+    }
+
+}
+
+/// Internal mirror rules of a download configuration.
+#[derive(Debug, Clone)]
+struct Mirrors {
+    /// All rules, in order.
+    rules: Vec<MirrorRule>,
+    /// True if the original URL should be tried after the mirrors.
+    fallback: bool,
+}
+
+/// A single mirror rule, replacing the given prefix with the mirror.
+#[derive(Debug, Clone)]
+struct MirrorRule {
+    prefix: Box<str>,
+    mirror: Box<str>,
+}
+
+impl Default for Mirrors {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            fallback: true,
+        }
     }
+}
 
-    /// Insert a new entry to be downloaded in this download batch.
-    pub fn push(&mut self, url: impl Into<Box<str>>, file: impl Into<Box<Path>>) -> &mut Entry {
-        self.entries.push(Entry::new(url.into(), file.into()));
-        self.entries.last_mut().unwrap()
-    }
+impl Mirrors {
 
-    /// Insert a new entry to be downloaded in this download batch, this entry don't
-    /// need a file because it is purely cached and so the file is derived from the URL.
-    /// It is constructed from a standard cache directory called `portablemc-cache` 
-    /// located in a standard user cache directory (or system tmp as a fallback), 
-    /// the file name in that directory is the hash of the URL.
-    pub fn push_cached(&mut self, url: impl Into<Box<str>>) -> &mut Entry {
-        self.entries.push(Entry::new_cached(url.into()));
-        self.entries.last_mut().unwrap()
-    }
+    /// See [`Config::mirror_urls`].
+    fn urls(&self, url: &str) -> Vec<String> {
 
-    pub fn entry(&self, index: usize) -> &Entry {
-        &self.entries[index]
-    }
+        let mut urls = self.rules.iter()
+            .filter_map(|rule| url.strip_prefix(&*rule.prefix)
+                .map(|suffix| format!("{}{suffix}", rule.mirror)))
+            .collect::<Vec<_>>();
 
-    pub fn entry_mut(&mut self, index: usize) -> &mut Entry {
-        &mut self.entries[index]
-    }
+        if urls.is_empty() || self.fallback {
+            urls.push(url.to_string());
+        }
+
+        urls
 
-    /// Download this whole batch, the batch is cleared if returning ok. It's left 
-    /// untouched if it returns an error and no file is downloaded.
-    /// 
-    /// This is internally starting an asynchronous Tokio runtime and block on it, so
-    /// this function will just panic if launched inside another runtime!
-    pub fn download(&mut self, mut handler: impl Handler) -> reqwest::Result<BatchResult> {
-        let client = crate::http::client()?;
-        let entries = mem::take(&mut self.entries);
-        Ok(crate::tokio::sync(download_many(client, &mut handler, 40, entries)))
     }
 
 }
@@ -181,9 +951,9 @@ pub struct Entry {
     /// Core information.
     core: EntryCore,
     /// Optional expected size of the file.
-    expected_size: Option<u32>,
-    /// Optional expected SHA-1 of the file.
-    expected_sha1: Option<[u8; 20]>,
+    expected_size: Option<u64>,
+    /// Optional expected digest of the file.
+    expected_digest: Option<Digest>,
     /// Use a file next to the entry file to keep track of the last-modified and entity
     /// tag HTTP informations, that will be used in next downloads to actually download
     /// the data only if needed. This means that the entry will not always be downloaded,
@@ -194,8 +964,12 @@ pub struct Entry {
     /// True to keep the file open after it has been downloaded, and store the handle
     /// in the completed entry.
     keep_open: bool,
-    /// A parametric retry count for that entry, default to 2 retries, but it can be zero.
-    max_retry: u8,
+    /// A parametric retry count for that entry, overriding the retry policy's one.
+    max_retry: Option<u8>,
+    /// The retry policy of that entry, overriding the one of the configuration.
+    retry_policy: Option<RetryPolicy>,
+    /// Ordered list of fallback URLs, tried in turn after the main URL has failed.
+    fallback_urls: Vec<Box<str>>,
 }
 
 impl Entry {
@@ -207,10 +981,12 @@ impl Entry {
                 file,
             },
             expected_size: None,
-            expected_sha1: None,
+            expected_digest: None,
             use_cache: false,
             keep_open: false,
-            max_retry: 2,
+            max_retry: None,
+            retry_policy: None,
+            fallback_urls: Vec::new(),
         }
     }
 
@@ -246,24 +1022,41 @@ impl Entry {
     }
 
     #[inline]
-    pub fn expected_size(&self) -> Option<u32> {
+    pub fn expected_size(&self) -> Option<u64> {
         self.expected_size
     }
 
     #[inline]
-    pub fn set_expected_size(&mut self, size: Option<u32>) -> &mut Self {
+    pub fn set_expected_size(&mut self, size: Option<u64>) -> &mut Self {
         self.expected_size = size;
         self
     }
 
+    /// The expected SHA-1 of the file, only if the expected digest is SHA-1.
     #[inline]
     pub fn expected_sha1(&self) -> Option<&[u8; 20]> {
-        self.expected_sha1.as_ref()
+        self.expected_digest.as_ref().and_then(Digest::sha1)
     }
 
+    /// Set the expected SHA-1 of the file, this replaces any expected digest, see
+    /// [`Self::set_expected_digest`].
     #[inline]
     pub fn set_expected_sha1(&mut self, sha1: Option<[u8; 20]>) -> &mut Self {
-        self.expected_sha1 = sha1;
+        self.expected_digest = sha1.map(Digest::Sha1);
+        self
+    }
+
+    #[inline]
+    pub fn expected_digest(&self) -> Option<&Digest> {
+        self.expected_digest.as_ref()
+    }
+
+    /// Set the expected digest of the file, that is checked once fully downloaded, 
+    /// the download fails with [`EntryErrorKind::InvalidSha1`] or 
+    /// [`EntryErrorKind::InvalidDigest`] on mismatch.
+    #[inline]
+    pub fn set_expected_digest(&mut self, digest: Option<Digest>) -> &mut Self {
+        self.expected_digest = digest;
         self
     }
 
@@ -301,15 +1094,176 @@ impl Entry {
     }
 
     /// Change the maximum retry count, this is used to automatically retry upon 
-    /// client-side connection problems.
+    /// connection problems and transient server errors, see [`RetryPolicy`]. If the 
+    /// server supports range requests and provides a strong ETag or a Last-Modified 
+    /// date, the download is resumed where it stopped, the partial file is also kept 
+    /// next to the entry file (with a `.part` extension) in order to resume it in a 
+    /// later download.
     /// 
-    /// The default value is 2 retries, which is purely arbitral.
+    /// This overrides the maximum retry count of the retry policy, which default value
+    /// is 2 retries, which is purely arbitral.
     #[inline]
     pub fn set_max_retry(&mut self, count: u8) -> &mut Self {
-        self.max_retry = count;
+        self.max_retry = Some(count);
+        self
+    }
+
+    /// The retry policy of this entry, if none the one of the download configuration is
+    /// used, see [`Config::set_retry_policy`].
+    #[inline]
+    pub fn retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry_policy.as_ref()
+    }
+
+    /// See [`Self::retry_policy`].
+    #[inline]
+    pub fn set_retry_policy(&mut self, policy: Option<RetryPolicy>) -> &mut Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// The ordered list of fallback URLs of this entry, see [`Self::add_fallback_url`].
+    #[inline]
+    pub fn fallback_urls(&self) -> impl Iterator<Item = &str> + use<'_> {
+        self.fallback_urls.iter().map(|url| &**url)
+    }
+
+    /// Add a fallback URL to download this entry from, the main URL is tried first 
+    /// and then every fallback URL, in the order they were added, until one succeeds.
+    /// A fallback URL is tried on HTTP errors, connection errors and size or SHA-1 
+    /// mismatches, but not on local I/O errors. 
+    /// 
+    /// Note that the mirrors of the download configuration also apply to each URL, see
+    /// [`Config::add_mirror`].
+    #[inline]
+    pub fn add_fallback_url(&mut self, url: impl Into<Box<str>>) -> &mut Self {
+        self.fallback_urls.push(url.into());
         self
     }
 
+    /// Iterator over all the source URLs of this entry, main URL first.
+    fn source_urls(&self) -> impl Iterator<Item = &str> + use<'_> {
+        std::iter::once(&*self.core.url).chain(self.fallback_urls())
+    }
+
+}
+
+/// A digest of a file's content, used to verify its integrity, several algorithms are
+/// supported because SHA-1 is used by Mojang but some repositories provide stronger
+/// digests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Digest {
+    Sha1([u8; 20]),
+    Sha256([u8; 32]),
+    Sha512([u8; 64]),
+}
+
+impl Digest {
+
+    /// Return the name of the algorithm of this digest: `sha1`, `sha256` or `sha512`.
+    pub fn algorithm(&self) -> &'static str {
+        match self {
+            Digest::Sha1(_) => "sha1",
+            Digest::Sha256(_) => "sha256",
+            Digest::Sha512(_) => "sha512",
+        }
+    }
+
+    /// Return the raw bytes of this digest.
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Digest::Sha1(bytes) => bytes,
+            Digest::Sha256(bytes) => bytes,
+            Digest::Sha512(bytes) => bytes,
+        }
+    }
+
+    /// Return the SHA-1 bytes, only if this is a SHA-1 digest.
+    pub fn sha1(&self) -> Option<&[u8; 20]> {
+        match self {
+            Digest::Sha1(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Compute the digest of the given reader, using the same algorithm as this digest,
+    /// and return true if the computed digest is equal to this one.
+    pub fn check_reader(&self, mut reader: impl Read) -> io::Result<bool> {
+        let mut hasher = DigestHasher::new(self);
+        io::copy(&mut reader, &mut hasher)?;
+        Ok(hasher.finalize() == *self)
+    }
+
+}
+
+impl From<[u8; 20]> for Digest {
+    #[inline]
+    fn from(value: [u8; 20]) -> Self {
+        Self::Sha1(value)
+    }
+}
+
+impl From<[u8; 32]> for Digest {
+    #[inline]
+    fn from(value: [u8; 32]) -> Self {
+        Self::Sha256(value)
+    }
+}
+
+impl From<[u8; 64]> for Digest {
+    #[inline]
+    fn from(value: [u8; 64]) -> Self {
+        Self::Sha512(value)
+    }
+}
+
+/// Internal hasher for computing a digest with the same algorithm as another one.
+#[derive(Debug, Clone)]
+enum DigestHasher {
+    Sha1(Sha1),
+    Sha256(Sha256),
+    Sha512(Sha512),
+}
+
+impl DigestHasher {
+
+    fn new(digest: &Digest) -> Self {
+        match digest {
+            Digest::Sha1(_) => Self::Sha1(Sha1::new()),
+            Digest::Sha256(_) => Self::Sha256(Sha256::new()),
+            Digest::Sha512(_) => Self::Sha512(Sha512::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha1(hasher) => hasher.update(data),
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Sha512(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize(self) -> Digest {
+        match self {
+            Self::Sha1(hasher) => Digest::Sha1(hasher.finalize().into()),
+            Self::Sha256(hasher) => Digest::Sha256(hasher.finalize().into()),
+            Self::Sha512(hasher) => Digest::Sha512(hasher.finalize().into()),
+        }
+    }
+
+}
+
+impl Write for DigestHasher {
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
 }
 
 /// When a download batch has been downloaded, this returned completed batch contains, 
@@ -361,6 +1315,11 @@ impl BatchResult {
         self.errors.len()
     }
 
+    /// Return true if any entry has been cancelled, see [`Batch::set_cancel_token`].
+    pub fn is_cancelled(&self) -> bool {
+        self.iter_errors().any(|e| matches!(e.kind, EntryErrorKind::Cancelled))
+    }
+
     pub fn iter_successes(&self) -> BatchResultSuccessesIter<'_> {
         BatchResultSuccessesIter {
             entries: self.entries.iter(),
@@ -477,10 +1436,18 @@ pub struct EntrySuccess {
 
 #[derive(Debug)]
 struct EntrySuccessInner {
+    /// Index of the source URL the entry has been downloaded from, zero for the main 
+    /// URL and then the fallback URLs.
+    source_index: usize,
+    /// The actual URL the entry has been downloaded from, it can be a mirror URL.
+    source_url: Box<str>,
     /// The final size of the downloaded entry.
-    size: u32,
+    size: u64,
     /// The final SHA-1 of the downloaded entry.
     sha1: [u8; 20],
+    /// The final digest of the downloaded entry, computed with the algorithm of the
+    /// expected digest, only if it's not SHA-1.
+    digest: Option<Digest>,
     /// Optional handle to the opened file, in case `keep_open` option was enabled.
     handle: Option<std::fs::File>,
 }
@@ -497,8 +1464,22 @@ impl EntrySuccess {
         &self.core.file
     }
 
+    /// The index of the source URL this entry has been downloaded from, zero for the 
+    /// main URL and then each fallback URL, see [`Entry::add_fallback_url`].
+    #[inline]
+    pub fn source_index(&self) -> usize {
+        self.inner.source_index
+    }
+
+    /// The actual URL this entry has been downloaded from, this can be the main URL,
+    /// a fallback URL or one of their mirrors, see [`Config::add_mirror`].
+    #[inline]
+    pub fn source_url(&self) -> &str {
+        &self.inner.source_url
+    }
+
     #[inline]
-    pub fn size(&self) -> u32 {
+    pub fn size(&self) -> u64 {
         self.inner.size
     }
 
@@ -507,6 +1488,14 @@ impl EntrySuccess {
         &self.inner.sha1
     }
 
+    /// The digest of the downloaded entry, computed with the algorithm of its expected
+    /// digest, or SHA-1 if there is no expected digest. Note that for cached entries
+    /// that have not been downloaded again, this is always the SHA-1.
+    #[inline]
+    pub fn digest(&self) -> Digest {
+        self.inner.digest.unwrap_or(Digest::Sha1(self.inner.sha1))
+    }
+
     /// If the entry was configured with `keep_open` option then it should return some
     /// file handle.
     #[inline]
@@ -550,6 +1539,52 @@ pub struct EntryError {
     core: EntryCore,
     #[source]
     kind: EntryErrorKind,
+    /// The URL of the last attempt, that produced the error kind.
+    source_url: Box<str>,
+    /// All previous failed attempts, in order, before the last one.
+    prev_attempts: Box<[EntryAttempt]>,
+}
+
+/// Internal error of an entry, before being associated to its core.
+#[derive(Debug)]
+struct EntryErrorInner {
+    kind: EntryErrorKind,
+    source_url: Box<str>,
+    prev_attempts: Vec<EntryAttempt>,
+}
+
+impl EntryErrorInner {
+
+    #[inline]
+    fn new(kind: EntryErrorKind, source_url: &str) -> Self {
+        Self { 
+            kind, 
+            source_url: Box::from(source_url), 
+            prev_attempts: Vec::new(),
+        }
+    }
+
+}
+
+/// A failed attempt at downloading an entry from one of its source URLs.
+#[derive(Debug)]
+pub struct EntryAttempt {
+    url: Box<str>,
+    kind: EntryErrorKind,
+}
+
+impl EntryAttempt {
+
+    #[inline]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    #[inline]
+    pub fn kind(&self) -> &EntryErrorKind {
+        &self.kind
+    }
+
 }
 
 /// An error for a single entry.
@@ -563,9 +1598,26 @@ pub enum EntryErrorKind {
     /// Implies that [`Entry::set_expected_sha1`] is not none.
     #[error("invalid sha1")]
     InvalidSha1,
+    /// Invalid digest of the fully downloaded entry compared to the expected digest,
+    /// when it's not a SHA-1 digest. Implies that [`Entry::set_expected_digest`] is 
+    /// not none.
+    #[error("invalid digest")]
+    InvalidDigest,
     /// Invalid HTTP status code while requesting the entry.
     #[error("invalid status: {0}")]
     InvalidStatus(u16),
+    /// The local file of a `file://` URL, or found with [`Config::set_local_root`], 
+    /// doesn't exist.
+    #[error("source file not found")]
+    SourceFileNotFound,
+    /// The entry needs a network request but the download is offline, see 
+    /// [`Config::set_offline`].
+    #[error("offline")]
+    Offline,
+    /// The download has been cancelled through its [`CancelToken`], the partial file
+    /// has been removed, if any.
+    #[error("cancelled")]
+    Cancelled,
     /// A generic error type for internal and third-party errors that may change depending
     /// on the actual implementation.
     /// 
@@ -590,10 +1642,35 @@ impl EntryErrorKind {
         Self::Internal(Box::new(e))
     }
 
+    /// Return true if this error is related to the source that the entry is being 
+    /// downloaded from, and so another source may be tried.
+    fn is_source_related(&self) -> bool {
+        match self {
+            Self::InvalidSize |
+            Self::InvalidSha1 |
+            Self::InvalidDigest |
+            Self::InvalidStatus(_) |
+            Self::SourceFileNotFound |
+            Self::Offline => true,
+            Self::Cancelled => false,
+            Self::Internal(e) => e.is::<reqwest::Error>(),
+        }
+    }
+
 }
 
 impl EntryError {
 
+    #[inline]
+    fn new(core: EntryCore, inner: EntryErrorInner) -> Self {
+        Self {
+            core,
+            kind: inner.kind,
+            source_url: inner.source_url,
+            prev_attempts: inner.prev_attempts.into_boxed_slice(),
+        }
+    }
+
     #[inline]
     pub fn url(&self) -> &str {
         &self.core.url
@@ -604,11 +1681,27 @@ impl EntryError {
         &self.core.file
     }
 
+    /// The error of the last attempt, see [`Self::attempts`].
     #[inline]
     pub fn kind(&self) -> &EntryErrorKind {
         &self.kind
     }
 
+    /// The URL of the last attempt, this can be the main URL, a fallback URL or one of
+    /// their mirrors.
+    #[inline]
+    pub fn source_url(&self) -> &str {
+        &self.source_url
+    }
+
+    /// Iterator over all the failed attempts of this entry, in order, as `(url, kind)` 
+    /// tuples, the last one being the source URL with the error kind.
+    pub fn attempts(&self) -> impl Iterator<Item = (&str, &EntryErrorKind)> + use<'_> {
+        self.prev_attempts.iter()
+            .map(|attempt| (&*attempt.url, &attempt.kind))
+            .chain(std::iter::once((&*self.source_url, &self.kind)))
+    }
+
 }
 
 /// A handle for watching a batch download progress.
@@ -617,32 +1710,143 @@ pub trait Handler {
     /// 'count' is equal to 'total_count'. This is called anyway at the beginning and at 
     /// the end of the download. Note that the final given 'size' may be greater than
     /// 'total_size' in case of unknown expected size, which 'total_size' is the sum.
-    fn on_progress(&mut self, count: u32, total_count: u32, size: u32, total_size: u32);
+    fn on_progress(&mut self, count: u32, total_count: u32, size: u64, total_size: u64);
+
+    /// Notification of an event concerning a single entry, given its index in the 
+    /// batch, it's always zero when downloading a single entry. Does nothing by default.
+    fn on_entry_event(&mut self, index: usize, event: EntryEvent<'_>) {
+        let _ = (index, event);
+    }
+
+    /// Notification of the current download speed, in bytes per second, and the 
+    /// estimated remaining time if the total size is known. This is called regularly
+    /// while bytes are being received. Does nothing by default.
+    fn on_speed(&mut self, speed: u64, eta: Option<Duration>) {
+        let _ = (speed, eta);
+    }
+
 }
 
 // Mutable implementation.
 impl<H: Handler + ?Sized> Handler for &mut H {
+    
     #[inline]
-    fn on_progress(&mut self, count: u32, total_count: u32, size: u32, total_size: u32) {
+    fn on_progress(&mut self, count: u32, total_count: u32, size: u64, total_size: u64) {
         Handler::on_progress(&mut **self, count, total_count, size, total_size)
     }
+
+    #[inline]
+    fn on_entry_event(&mut self, index: usize, event: EntryEvent<'_>) {
+        Handler::on_entry_event(&mut **self, index, event)
+    }
+
+    #[inline]
+    fn on_speed(&mut self, speed: u64, eta: Option<Duration>) {
+        Handler::on_speed(&mut **self, speed, eta)
+    }
+
 }
 
 impl Handler for () {
-    fn on_progress(&mut self, count: u32, total_count: u32, size: u32, total_size: u32) {
+    fn on_progress(&mut self, count: u32, total_count: u32, size: u64, total_size: u64) {
         let _ = (count, total_count, size, total_size);
     }
 }
 
+/// An event concerning a single entry being downloaded, see [`Handler::on_entry_event`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EntryEvent<'a> {
+    /// The entry download has started, this happens when the entry is actually being 
+    /// requested, after waiting for the concurrency limits.
+    Started { url: &'a str, file: &'a Path, expected_size: Option<u64> },
+    /// Some bytes have been received for the entry, the size is the total number of
+    /// bytes received for this entry so far.
+    Progress { size: u64, expected_size: Option<u64> },
+    /// A transient error happened and the entry will be requested again from the given
+    /// URL after the given delay, the retry number starts at 1.
+    Retried { url: &'a str, retry: u8, delay: Duration },
+    /// The entry has been successfully downloaded with the given final size.
+    Completed { size: u64 },
+    /// The entry download has failed with the given error.
+    Failed { error: &'a EntryErrorKind },
+}
+
+/// A cancellation token that can be cloned and shared across threads, all clones 
+/// share the same state. When cancelled, any download or installation that has been
+/// given this token will be aborted as soon as possible, there is no way to reset a
+/// cancelled token.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<CancelTokenInner>);
+
+#[derive(Debug, Default)]
+struct CancelTokenInner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancelToken {
+
+    /// Create a new token, not yet cancelled.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel this token and all of its clones, waking up all pending downloads.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, atomic::Ordering::Release);
+        self.0.notify.notify_waiters();
+    }
+
+    /// Return true if this token has been cancelled.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(atomic::Ordering::Acquire)
+    }
+
+    /// Wait until this token is cancelled, returning immediately if already cancelled.
+    pub(crate) async fn cancelled(&self) {
+        
+        // Register ourself as a waiter before checking the flag, so that we can't miss
+        // a cancellation happening between the check and the wait.
+        let notified = self.0.notify.notified();
+        let mut notified = std::pin::pin!(notified);
+        notified.as_mut().enable();
+
+        if !self.is_cancelled() {
+            notified.await;
+        }
+
+    }
+
+}
+
+/// Run the given future to completion, unless the given optional token gets cancelled
+/// before, in which case none is returned.
+pub(crate) async fn cancellable<F: Future>(token: Option<&CancelToken>, future: F) -> Option<F::Output> {
+    match token {
+        Some(token) => tokio::select! {
+            biased;
+            _ = token.cancelled() => None,
+            output = future => Some(output),
+        },
+        None => Some(future.await),
+    }
+}
+
 /// Internal split of the download_impl function without reqwest initialization error.
 #[inline]
-async fn download_many(
+async fn download_many<H: Handler + ?Sized>(
     client: Client,
-    handler: &mut dyn Handler,
-    concurrent_count: usize,
+    handler: &mut H,
+    config: &Config,
     entries: Vec<Entry>,
+    cancel_token: Option<CancelToken>,
 ) -> BatchResult {
 
+    let concurrent_count = config.concurrency;
+
     // Make it constant and sharable between all tasks.
     let entries = Arc::new(entries);
 
@@ -667,14 +1871,18 @@ async fn download_many(
     let mut size = 0;
     let total_size = indices.iter()
         .map(|&index| entries[index].expected_size.unwrap_or(0))
-        .sum::<u32>();
+        .sum::<u64>();
 
     // Send a progress update for each 1000 parts of the download.
     let progress_size_interval = total_size / 1000;
-    let mut last_size = 0u32;
+    let mut last_size = 0u64;
 
     handler.on_progress(0, entries.len() as u32, size, total_size);
 
+    // The size received for each entry, and the meter for computing the speed.
+    let mut entries_size = vec![0u64; entries.len()];
+    let mut speed_meter = SpeedMeter::new();
+
     let mut completed = 0;
     let mut futures = JoinSet::new();
 
@@ -685,16 +1893,51 @@ async fn download_many(
 
     let mut results = (0..entries.len()).map(|_| None).collect::<Vec<_>>();
 
+    // If the concurrency per host is limited, each host has its own semaphore.
+    let mut host_semaphores = HashMap::<Box<str>, Arc<Semaphore>>::new();
+
+    // The configuration is shared between all tasks.
+    let config = Arc::new(config.clone());
+
     // If we have theoretically completed all downloads, we still wait for joining all
     // remaining futures in the join set.
     while completed < entries.len() || !futures.is_empty() {
+
+        // If cancelled, the pending entries are never started, and we continue in 
+        // order to wait for the in-flight entries that should abort quickly.
+        if !indices.is_empty() && cancel_token.as_ref().is_some_and(CancelToken::is_cancelled) {
+            for index in indices.drain(..) {
+                handler.on_entry_event(index, EntryEvent::Failed { error: &EntryErrorKind::Cancelled });
+                let prev_res = results[index].replace(Err(EntryErrorInner::new(EntryErrorKind::Cancelled, &entries[index].core.url)));
+                debug_assert!(prev_res.is_none());
+                completed += 1;
+            }
+            handler.on_progress(completed as u32, entries.len() as u32, size, total_size);
+            continue;
+        }
         
         while futures.len() < concurrent_count && !indices.is_empty() {
+
+            let index = indices.pop().unwrap();  // Safe because not empty.
+            
+            // The host is the one of the first URL to be requested.
+            let host_semaphore = config.host_concurrency.and_then(|count| {
+                let url = Url::parse(&config.mirror_urls(&entries[index].core.url)[0]).ok()?;
+                let host = url.host_str()?;
+                let semaphore = host_semaphores.entry(Box::from(host))
+                    .or_insert_with(|| Arc::new(Semaphore::new(count)));
+                Some(Arc::clone(semaphore))
+            });
+
             futures.spawn(download_many_entry(
                 client.clone(), 
+                Arc::clone(&config),
                 Arc::clone(&entries),
-                indices.pop().unwrap(),  // Safe because not empty.
-                progress_tx.clone()));
+                index,
+                progress_tx.clone(),
+                host_semaphore,
+                cancel_token.clone()));
+
         }
 
         let mut force_progress = false;
@@ -704,11 +1947,30 @@ async fn download_many(
                 let (index, res) = res.expect("task should not be cancelled nor panicking");
                 completed += 1;
                 force_progress = true;
+                handler.on_entry_event(index, match &res {
+                    Ok(inner) => EntryEvent::Completed { size: inner.size },
+                    Err(inner) => EntryEvent::Failed { error: &inner.kind },
+                });
                 let prev_res = results[index].replace(res);
                 debug_assert!(prev_res.is_none());
             }
-            Some(progress) = progress_rx.recv() => {
-                size += progress as u32;
+            Some((index, progress)) = progress_rx.recv() => {
+                let entry = &entries[index];
+                handler.on_entry_event(index, match progress {
+                    EntryProgress::Started => EntryEvent::Started { 
+                        url: &entry.core.url, 
+                        file: &entry.core.file, 
+                        expected_size: entry.expected_size,
+                    },
+                    EntryProgress::Received(delta) => {
+                        size += delta;
+                        entries_size[index] += delta;
+                        EntryEvent::Progress { size: entries_size[index], expected_size: entry.expected_size }
+                    }
+                    EntryProgress::Retried { ref url, retry, delay } => {
+                        EntryEvent::Retried { url, retry, delay }
+                    }
+                });
             }
             else => {
                 // Just ignore, because it's invalid state, in case of join_next we 
@@ -724,6 +1986,10 @@ async fn download_many(
             last_size = size;
         }
 
+        if speed_meter.update(size) {
+            handler.on_speed(speed_meter.speed(), speed_meter.eta(size, total_size));
+        }
+
     }
 
     // Ensure that all tasks are aborted, this allows us to take back ownership of the 
@@ -742,7 +2008,7 @@ async fn download_many(
         }
         ret_entries.push(match res {
             Ok(inner) => Ok(EntrySuccess { core: entry.core, inner }),
-            Err(kind) => Err(EntryError { core: entry.core, kind }),
+            Err(inner) => Err(EntryError::new(entry.core, inner)),
         });
     }
 
@@ -757,62 +2023,165 @@ async fn download_many(
 /// function in order to easily catch the result and send it as an event.
 async fn download_many_entry(
     client: Client, 
+    config: Arc<Config>,
     entries: Arc<Vec<Entry>>,
     index: usize,
-    progress_sender: mpsc::Sender<u32>,
-) -> (usize, Result<EntrySuccessInner, EntryErrorKind>) {
+    progress_sender: mpsc::Sender<(usize, EntryProgress)>,
+    host_semaphore: Option<Arc<Semaphore>>,
+    cancel_token: Option<CancelToken>,
+) -> (usize, Result<EntrySuccessInner, EntryErrorInner>) {
 
     let progress_sender = ChannelEntryProgressSender {
         sender: progress_sender,
+        index,
+    };
+
+    // Wait for the host to be available, the permit is held until the entry is done.
+    let _host_permit = match host_semaphore {
+        Some(semaphore) => match cancellable(cancel_token.as_ref(), semaphore.acquire_owned()).await {
+            Some(permit) => Some(permit.expect("host semaphore should not be closed")),
+            None => return (index, Err(EntryErrorInner::new(EntryErrorKind::Cancelled, &entries[index].core.url))),
+        }
+        None => None,
     };
 
-    (index, download_entry(client, &entries[index], progress_sender).await)
+    (index, download_entry(&client, &config, &entries[index], progress_sender, cancel_token.as_ref()).await)
 
 }
 
-async fn download_single(
+async fn download_single<H: Handler + ?Sized>(
     client: Client,
-    handler: &mut dyn Handler,
+    handler: &mut H,
+    config: &Config,
     entry: &Entry,
+    cancel_token: Option<&CancelToken>,
 ) -> Result<EntrySuccess, EntryError> {
 
-    let mut size = 0u32;
+    let mut size = 0u64;
     let total_size = entry.expected_size.unwrap_or(0);
 
     handler.on_progress(0, 1, 0, total_size);
 
-    let progress_sender = DirectEntryProgressSender {
-        handler: &mut *handler,
-        size: &mut size,
-        total_size,
-    };
+    let progress_sender = DirectEntryProgressSender {
+        handler: &mut *handler,
+        entry,
+        size: &mut size,
+        total_size,
+        speed_meter: SpeedMeter::new(),
+    };
+
+    let res = download_entry(&client, config, entry, progress_sender, cancel_token).await;
+
+    handler.on_progress(1, 1, size, total_size);
+    handler.on_entry_event(0, match &res {
+        Ok(inner) => EntryEvent::Completed { size: inner.size },
+        Err(inner) => EntryEvent::Failed { error: &inner.kind },
+    });
+
+    match res {
+        Ok(inner) => Ok(EntrySuccess { core: entry.core.clone(), inner }),
+        Err(inner) => Err(EntryError::new(entry.core.clone(), inner)),
+    }
+
+}
+
+/// Internal function to download a single download entry, returning a result with an
+/// optional handle to the std file, if keep open parameter is enabled on the entry.
+/// If the given token is cancelled, the entry is aborted and its file removed.
+/// 
+/// Each source URL of the entry, with its mirrors, is tried in order, falling back to 
+/// the next one on errors related to the source, every failed attempt is returned.
+async fn download_entry(
+    client: &Client, 
+    config: &Config,
+    entry: &Entry,
+    mut progress_sender: impl EntryProgressSender,
+    cancel_token: Option<&CancelToken>,
+) -> Result<EntrySuccessInner, EntryErrorInner> {
+
+    let mut urls = Vec::new();
+    for (source_index, url) in entry.source_urls().enumerate() {
+        if let Some(local_url) = config.local_file(url).and_then(|file| Url::from_file_path(file).ok()) {
+            urls.push((source_index, String::from(local_url)));
+        }
+        urls.extend(config.mirror_urls(url).into_iter().map(|url| (source_index, url)));
+    }
+
+    let mut urls = urls.into_iter().peekable();
+
+    let mut prev_attempts = Vec::new();
+
+    progress_sender.send(EntryProgress::Started).await;
+
+    loop {
+
+        let (source_index, url) = urls.next().expect("source urls should not be empty");
+        let res = if url.starts_with("file:") {
+            download_entry_file(config, entry, source_index, &url, &mut progress_sender, cancel_token).await
+        } else {
+            download_entry_url(client, config, entry, source_index, &url, &mut progress_sender, cancel_token).await
+        };
+
+        let kind = match res {
+            Ok(inner) => return Ok(inner),
+            Err(kind) => kind,
+        };
 
-    let res = download_entry(client, entry, progress_sender).await;
+        if !kind.is_source_related() || urls.peek().is_none() {
+            return Err(EntryErrorInner {
+                kind,
+                source_url: url.into_boxed_str(),
+                prev_attempts,
+            });
+        }
 
-    handler.on_progress(1, 1, size, total_size);
+        prev_attempts.push(EntryAttempt { 
+            url: url.into_boxed_str(), 
+            kind,
+        });
 
-    match res {
-        Ok(inner) => Ok(EntrySuccess { core: entry.core.clone(), inner }),
-        Err(kind) => Err(EntryError { core: entry.core.clone(), kind }),
     }
 
 }
 
-/// Internal function to download a single download entry, returning a result with an
-/// optional handle to the std file, if keep open parameter is enabled on the entry.
-async fn download_entry(
-    client: Client, 
+/// Internal function to download a single download entry from the given URL.
+/// 
+/// The data is first downloaded to a temporary file next to the entry file, and then
+/// atomically moved in place only once fully checked. On network errors, this 
+/// temporary file is kept as a partial file so that a later download can resume it 
+/// with a range request. Transient errors are retried according to the retry policy.
+async fn download_entry_url(
+    client: &Client, 
+    config: &Config,
     entry: &Entry,
+    source_index: usize,
+    url: &str,
     mut progress_sender: impl EntryProgressSender,
+    cancel_token: Option<&CancelToken>,
 ) -> Result<EntrySuccessInner, EntryErrorKind> {
 
-    let mut req = client.get(&*entry.core.url);
+    let mut req = client.get(url);
+
+    // The retry state of the entry, shared by all requests.
+    let mut retry = EntryRetry {
+        url,
+        policy: entry.retry_policy.as_ref().unwrap_or(&config.retry_policy),
+        max_retry: 0,
+        try_num: 0,
+    };
+    retry.max_retry = entry.max_retry.unwrap_or(retry.policy.max_retry);
     
     // If we are in cache mode, then we derive the file name.
     let cache_file = entry.use_cache.then(|| {
         entry.core.file.to_path_buf().appended(".cache")
     });
 
+    // The partial file and its metadata, used to resume interrupted downloads, and
+    // the temporary file, unique to this download, where the data is written.
+    let part_file = entry.core.file.to_path_buf().appended(".part");
+    let part_meta_file = entry.core.file.to_path_buf().appended(".part.meta");
+    let tmp_file = entry.core.file.temp_sibling();
+
     // If we are in cache mode, try checking the file, if the file is locally valid.
     let mut cache = None;
     if let Some(cache_file) = cache_file.as_deref() {
@@ -820,6 +2189,21 @@ async fn download_entry(
             .map_err(EntryErrorKind::new_io)?;
     }
 
+    // When offline, the cached file is used as-is, if any, no request can be made.
+    if config.offline {
+        let Some((handle, cache_meta)) = cache else {
+            return Err(EntryErrorKind::Offline);
+        };
+        return Ok(EntrySuccessInner { 
+            source_index,
+            source_url: Box::from(url),
+            size: cache_meta.size, 
+            sha1: cache_meta.sha1.0,
+            digest: None,
+            handle: entry.keep_open.then_some(handle),
+        });
+    }
+
     // Then we add corresponding request headers for cache control.
     if let Some((_, cache_meta)) = &cache {
         if let Some(etag) = cache_meta.etag.as_deref() {
@@ -830,81 +2214,146 @@ async fn download_entry(
         }
     }
 
-    // If it's a connection error just use the cached copy.
-    let mut res = match req.send().await {
-        Ok(res) => res,
-        Err(e) if cache.is_some() && (e.is_timeout() || e.is_request() || e.is_connect()) => {
+    // If the file is not cached, check if a previous partial download can be resumed,
+    // the server will send the whole resource if it has changed since. The partial 
+    // file is claimed by moving it to our temporary file, so that concurrent downloads
+    // of the same file never write to the same file.
+    let mut part_size = 0;
+    if cache.is_none() {
+        let part = check_download_part(url, &part_file, &part_meta_file, &tmp_file).await
+            .map_err(EntryErrorKind::new_io)?;
+        if let Some((size, validator)) = part {
+            req = req
+                .header(header::RANGE, format!("bytes={size}-"))
+                .header(header::IF_RANGE, validator);
+            part_size = size;
+        }
+    }
+
+    // If it's a connection error just use the cached copy, we don't retry connection
+    // errors in such case.
+    let mut res = match retry.send(req, cache.is_none(), &mut progress_sender, cancel_token).await {
+        Some(Ok(res)) => res,
+        Some(Err(e)) if cache.is_some() && (e.is_timeout() || e.is_request() || e.is_connect()) => {
             // Using cache in case of network error.
             let (handle, cache_meta) = cache.unwrap();
             return Ok(EntrySuccessInner { 
+                source_index,
+                source_url: Box::from(url),
                 size: cache_meta.size, 
                 sha1: cache_meta.sha1.0,
+                digest: None,
                 handle: entry.keep_open.then_some(handle),
             });
         }
-        Err(e) => {
+        res => {
+            // The claimed partial file is given back, if any, for a later download.
+            if part_size != 0 {
+                let _ = fs::rename(&tmp_file, &part_file).await;
+            }
             // Other unhandled errors are returned and will be present in errored entries.
-            return Err(EntryErrorKind::new_reqwest(e));
+            return Err(match res {
+                Some(Err(e)) => EntryErrorKind::new_reqwest(e),
+                _ => EntryErrorKind::Cancelled,
+            });
         }
     };
 
     // Checking if the status is not OK, if this is a NOT_MODIFIED then we returned the
-    // file as-is, with the handle if keep open is requested.
+    // file as-is, with the handle if keep open is requested. If we requested a range, 
+    // we only accept partial content that starts where our partial file ends.
+    let mut offset = 0;
     if res.status() == StatusCode::NOT_MODIFIED && cache.is_some() {
         let (handle, cache_meta) = cache.unwrap();
         return Ok(EntrySuccessInner { 
+            source_index,
+            source_url: Box::from(url),
             size: cache_meta.size, 
             sha1: cache_meta.sha1.0,
+            digest: None,
             handle: entry.keep_open.then_some(handle),
         });
+    } else if res.status() == StatusCode::PARTIAL_CONTENT && part_size != 0 && content_range_start(&res) == Some(part_size) {
+        offset = part_size;
     } else if res.status() != StatusCode::OK {
+        // The claimed partial file is given back, unless it can't be resumed.
+        if part_size != 0 {
+            if matches!(res.status(), StatusCode::PARTIAL_CONTENT | StatusCode::RANGE_NOT_SATISFIABLE) {
+                let _ = fs::remove_file(&tmp_file).await;
+                let _ = fs::remove_file(&part_meta_file).await;
+            } else {
+                let _ = fs::rename(&tmp_file, &part_file).await;
+            }
+        }
         return Err(EntryErrorKind::InvalidStatus(res.status().as_u16()));
     }
 
     // Close the possible cached file because we'll need to create it just below. 
     drop(cache);
 
+    // The metadata and validator that will be used to resume the download with a 
+    // range request, if none then the download can't be resumed.
+    let part_meta = response_part_meta(url, &res);
+    let validator = part_meta.as_ref()
+        .and_then(|meta| meta.etag.as_ref().or(meta.last_modified.as_ref()))
+        .cloned();
+
     // Create any parent directory so that we can create the file.
     if let Some(parent_dir) = entry.core.file.parent() {
         fs::create_dir_all(parent_dir).await.map_err(EntryErrorKind::new_io)?;
     }
 
-    // Only add read capability if the handle needs to be kept.
+    // Only truncate the temporary file if not resuming.
     let mut file = File::options()
         .write(true)
         .create(true)
-        .truncate(true)
-        .read(entry.keep_open)
-        .open(&*entry.core.file).await
+        .truncate(offset == 0)
+        .read(true)
+        .open(&tmp_file).await
         .map_err(EntryErrorKind::new_io)?;
+
+    // The SHA-1 is always computed, and another hasher is used if the expected digest
+    // is not SHA-1.
+    let mut size = 0u64;
+    let mut sha1 = Sha1::new();
+    let new_digest_hasher = || entry.expected_digest.as_ref()
+        .filter(|digest| digest.sha1().is_none())
+        .map(DigestHasher::new);
+    let mut digest_hasher = new_digest_hasher();
+
+    if offset != 0 {
+        // Hash the already downloaded data, and ensure that we are at the right offset.
+        let mut std_file = file.into_std().await;
+        let mut writer = DigestWriter { sha1: &mut sha1, digest_hasher: digest_hasher.as_mut() };
+        size = io::copy(&mut Read::take(&mut std_file, offset), &mut writer)
+            .map_err(EntryErrorKind::new_io)?;
+        std_file.set_len(size).map_err(EntryErrorKind::new_io)?;
+        std_file.seek(SeekFrom::Start(size)).map_err(EntryErrorKind::new_io)?;
+        file = File::from_std(std_file);
+        progress_sender.send(EntryProgress::Received(size)).await;
+    }
     
     // Now we do all the allowed tries at downloading the file.
-    let mut try_num = 0u8;
-    let (size, sha1) = 'success: loop {
-
-        let mut size = 0usize;
-        let mut sha1 = Sha1::new();
+    let (sha1, digest) = 'success: loop {
 
         // On success we break the 'outer loop, on error we break the inner loop!
         // We specify if this error should be retried.
-        let (retry, mut err) = loop {
+        let (retryable, mut err) = loop {
 
             // Read chunk by chunk, any error break the loop and fallthrough, retrying if
             // this is timeout or decode error.
-            let chunk = match res.chunk().await {
-                Ok(chunk) => chunk,
-                Err(e) => break (e.is_timeout() || e.is_decode(), EntryErrorKind::new_reqwest(e)),
+            let chunk = match cancellable(cancel_token, res.chunk()).await {
+                Some(Ok(chunk)) => chunk,
+                Some(Err(e)) => break (e.is_timeout() || e.is_decode(), EntryErrorKind::new_reqwest(e)),
+                None => break (false, EntryErrorKind::Cancelled),
             };
 
             // If chunk is none, it means that we finished reading, the file is complete
             // and so we check size and sha-1, on mismatch we don't retry!
             let Some(chunk) = chunk else {
 
-                let Ok(size) = u32::try_from(size) else {
-                    break (false, EntryErrorKind::InvalidSize);
-                };
-
-                let sha1 = sha1.finalize();
+                let sha1 = sha1.finalize_reset();
+                let digest = digest_hasher.take().map(DigestHasher::finalize);
 
                 if let Some(expected_size) = entry.expected_size {
                     if expected_size != size {
@@ -912,61 +2361,92 @@ async fn download_entry(
                     }
                 }
 
-                if let Some(expected_sha1) = &entry.expected_sha1 {
-                    if expected_sha1 != sha1.as_slice() {
+                match &entry.expected_digest {
+                    Some(Digest::Sha1(expected_sha1)) if expected_sha1 != sha1.as_slice() => {
                         break (false, EntryErrorKind::InvalidSha1);
                     }
+                    Some(expected_digest) if digest.is_some_and(|digest| digest != *expected_digest) => {
+                        break (false, EntryErrorKind::InvalidDigest);
+                    }
+                    _ => (),
                 }
 
-                break 'success (size, sha1);
+                break 'success (sha1, digest);
 
             };
 
             // Adding the size delta and transmit it to the progress handler.
-            let delta = chunk.len();
+            let delta = chunk.len() as u64;
             size += delta;
 
+            // Wait for the bandwidth limit before reading further, this also slows down
+            // the server through the transport flow control.
+            if let Some(rate_limit) = &config.rate_limit 
+            && cancellable(cancel_token, rate_limit.acquire(delta)).await.is_none() {
+                break (false, EntryErrorKind::Cancelled);
+            }
+
             // We don't want to retry on I/O errors because these are local problem, and
             // no longer network problems...
             match AsyncWriteExt::write_all(&mut file, &chunk).await {
                 Ok(_) => (),
                 Err(e) => break (false, EntryErrorKind::new_io(e)),
             }
-            match Write::write_all(&mut sha1, &chunk) {
-                Ok(_) => (),
-                Err(e) => break (false, EntryErrorKind::new_io(e)),
+            sha1.update(&chunk);
+            if let Some(digest_hasher) = &mut digest_hasher {
+                digest_hasher.update(&chunk);
             }
 
-            progress_sender.send(delta as u32).await;
+            progress_sender.send(EntryProgress::Received(delta)).await;
 
         };
 
-        // We have not reached the max retry, so we try to rewind the file and re-request
-        // the resource, if not possible, we flow below to delete the file and return the
-        // error!
-        if retry && try_num < entry.max_retry {
-            
-            try_num += 1;
+        // We have not reached the max retry, so we try to re-request the resource from
+        // where we stopped, if the server doesn't support it, we rewind the file, if
+        // not possible, we flow below to delete the file and return the error!
+        let mut keep_part = retryable;
+        if retryable && retry.try_num < retry.max_retry {
             
             // Scope the error in this closure...
-            let rewind_res = async || -> Result<(), EntryErrorKind> {
+            let rewind_res = async {
+
+                // Wait before retrying, like any other retry.
+                retry.wait(None, &mut progress_sender, cancel_token).await
+                    .ok_or(EntryErrorKind::Cancelled)?;
                 
-                file.rewind().await.map_err(EntryErrorKind::new_io)?;
-                file.set_len(0).await.map_err(EntryErrorKind::new_io)?;
+                let mut req = client.get(url);
+                if let Some(validator) = validator.as_deref() {
+                    req = req
+                        .header(header::RANGE, format!("bytes={size}-"))
+                        .header(header::IF_RANGE, validator);
+                }
                 
-                res = client.get(&*entry.core.url).send().await.map_err(EntryErrorKind::new_reqwest)?;
-                if res.status() != StatusCode::OK {
+                res = retry.send(req, true, &mut progress_sender, cancel_token).await
+                    .ok_or(EntryErrorKind::Cancelled)?
+                    .map_err(EntryErrorKind::new_reqwest)?;
+
+                if res.status() == StatusCode::PARTIAL_CONTENT && validator.is_some() && content_range_start(&res) == Some(size) {
+                    return Ok(());
+                } else if res.status() != StatusCode::OK {
                     return Err(EntryErrorKind::InvalidStatus(res.status().as_u16()));
                 }
 
+                file.rewind().await.map_err(EntryErrorKind::new_io)?;
+                file.set_len(0).await.map_err(EntryErrorKind::new_io)?;
+                size = 0;
+                sha1 = Sha1::new();
+                digest_hasher = new_digest_hasher();
+                
                 Ok(())
 
-            }().await;
+            }.await;
 
             // If no error, retry, if error just fallthrough to the cleanup code below!
             match rewind_res {
                 Ok(()) => continue,
                 Err(e) => {
+                    // Only keep the partial file on network errors.
+                    keep_part = matches!(e, EntryErrorKind::Internal(_));
                     err = e;
                     // Fallthrough to cleanup code below...
                 }
@@ -980,19 +2460,43 @@ async fn download_entry(
         let _ = file.flush().await;
         drop(file);
         
-        // Remove the file, because we are not guaranteed that it's complete.
-        // Ignore the error in case it fails, we just want to return the original error.
-        let _ = fs::remove_file(&*entry.core.file).await;
-
-        // Also remove the cache file if the file is cached.
-        if let Some(cache_file) = cache_file.as_deref() {
-            let _ = fs::remove_file(cache_file).await;
+        // Keep the temporary file as the partial file if it can be resumed later, if 
+        // not, remove it. Ignore the errors in case it fails, we just want to return 
+        // the original error.
+        match part_meta.as_ref().filter(|_| keep_part) {
+            Some(part_meta) => {
+                if fs::rename(&tmp_file, &part_file).await.is_ok() {
+                    write_download_part_meta(&part_meta_file, part_meta).await;
+                }
+            }
+            None => {
+                let _ = fs::remove_file(&tmp_file).await;
+                if part_size != 0 {
+                    let _ = fs::remove_file(&part_meta_file).await;
+                }
+            }
         }
-        
+
+        // The entry file, and its possible cache file, are left untouched because the
+        // rename below is the only write to it.
         return Err(err);
 
     };
 
+    // The temporary file is complete and checked, atomically move it to the entry 
+    // file, so that the entry file is never seen partially written.
+    file.flush().await.map_err(EntryErrorKind::new_io)?;
+    drop(file);
+
+    if let Err(e) = fs::rename(&tmp_file, &*entry.core.file).await {
+        let _ = fs::remove_file(&tmp_file).await;
+        return Err(EntryErrorKind::new_io(e));
+    }
+
+    if part_size != 0 {
+        let _ = fs::remove_file(&part_meta_file).await;
+    }
+
     // If we have a cache file, write it.
     if let Some(cache_file) = cache_file.as_deref() {
 
@@ -1002,22 +2506,27 @@ async fn download_entry(
         let last_modified = res.headers().get(header::LAST_MODIFIED)
             .and_then(|h| h.to_str().ok().map(str::to_string));
 
-        // Only write the cache file if relevant!
+        // Only write the cache file if relevant! It's also written to a temporary 
+        // file before being moved in place.
         if etag.is_some() || last_modified.is_some() {
 
-            let cache_meta_writer = File::create(cache_file).await.map_err(EntryErrorKind::new_io)?;
-            let cache_meta_writer = BufWriter::new(cache_meta_writer.into_std().await);
+            let cache_tmp_file = cache_file.temp_sibling();
+            let cache_meta_writer = File::create(&cache_tmp_file).await.map_err(EntryErrorKind::new_io)?;
+            let mut cache_meta_writer = BufWriter::new(cache_meta_writer.into_std().await);
 
-            let res = serde_json::to_writer(cache_meta_writer, &serde::CacheMeta {
-                url: entry.core.url.to_string(),
+            let res = serde_json::to_writer(&mut cache_meta_writer, &serde::CacheMeta {
+                url: url.to_string(),
                 size,
                 sha1: crate::serde::HexString(sha1.into()),
                 etag,
                 last_modified,
-            });
+            }).map_err(io::Error::from).and_then(|()| cache_meta_writer.flush());
+
+            drop(cache_meta_writer);
 
             // Silently ignore errors by we remove the file if it happens.
-            if res.is_err() {
+            if res.is_err() || fs::rename(&cache_tmp_file, cache_file).await.is_err() {
+                let _ = fs::remove_file(&cache_tmp_file).await;
                 let _ = fs::remove_file(cache_file).await;
             }
 
@@ -1025,26 +2534,388 @@ async fn download_entry(
 
     }
 
-    file.flush().await.map_err(EntryErrorKind::new_io)?;
-
     // At the end, handle the keep open option!
     let handle;
     if entry.keep_open {
-        let mut file = file.into_std().await;
-        file.rewind().map_err(EntryErrorKind::new_io)?;
-        handle = Some(file);
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .open(&*entry.core.file).await
+            .map_err(EntryErrorKind::new_io)?;
+        handle = Some(file.into_std().await);
     } else {
         handle = None;
     }
 
     Ok(EntrySuccessInner {
+        source_index,
+        source_url: Box::from(url),
+        size,
+        sha1: sha1.into(),
+        digest,
+        handle,
+    })
+
+}
+
+/// Internal function to copy a single entry from the local file of the given `file://`
+/// URL, with the same checks as a download. The file is copied to a temporary file 
+/// next to the entry file and then moved in place, or hard linked if enabled.
+async fn download_entry_file(
+    config: &Config,
+    entry: &Entry,
+    source_index: usize,
+    url: &str,
+    mut progress_sender: impl EntryProgressSender,
+    cancel_token: Option<&CancelToken>,
+) -> Result<EntrySuccessInner, EntryErrorKind> {
+
+    let Some(source_file) = Url::parse(url).ok().and_then(|url| url.to_file_path().ok()) else {
+        return Err(EntryErrorKind::SourceFileNotFound);
+    };
+
+    let mut source = match File::open(&source_file).await {
+        Ok(source) => source,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Err(EntryErrorKind::SourceFileNotFound),
+        Err(e) => return Err(EntryErrorKind::new_io(e)),
+    };
+
+    if let Some(parent_dir) = entry.core.file.parent() {
+        fs::create_dir_all(parent_dir).await.map_err(EntryErrorKind::new_io)?;
+    }
+
+    // When linking, the source file is only read for checking it.
+    let tmp_file = entry.core.file.temp_sibling();
+    let mut file = match config.local_link {
+        true => None,
+        false => Some(File::create(&tmp_file).await.map_err(EntryErrorKind::new_io)?),
+    };
+
+    let mut size = 0u64;
+    let mut sha1 = Sha1::new();
+    let mut digest_hasher = entry.expected_digest.as_ref()
+        .filter(|digest| digest.sha1().is_none())
+        .map(DigestHasher::new);
+
+    let mut buf = vec![0; 64 * 1024];
+    let res = async {
+        
+        loop {
+            
+            let len = cancellable(cancel_token, source.read(&mut buf)).await
+                .ok_or(EntryErrorKind::Cancelled)?
+                .map_err(EntryErrorKind::new_io)?;
+
+            if len == 0 {
+                break;
+            }
+
+            let chunk = &buf[..len];
+            if let Some(file) = &mut file {
+                file.write_all(chunk).await.map_err(EntryErrorKind::new_io)?;
+            }
+            
+            sha1.update(chunk);
+            if let Some(digest_hasher) = &mut digest_hasher {
+                digest_hasher.update(chunk);
+            }
+
+            size += len as u64;
+            progress_sender.send(EntryProgress::Received(len as u64)).await;
+
+        }
+
+        if let Some(file) = &mut file {
+            file.flush().await.map_err(EntryErrorKind::new_io)?;
+        }
+
+        Ok(())
+
+    }.await;
+
+    drop(file);
+
+    let sha1 = sha1.finalize();
+    let digest = digest_hasher.map(DigestHasher::finalize);
+
+    let res = res.and_then(|()| {
+        if entry.expected_size.is_some_and(|expected_size| expected_size != size) {
+            return Err(EntryErrorKind::InvalidSize);
+        }
+        match &entry.expected_digest {
+            Some(Digest::Sha1(expected_sha1)) if expected_sha1 != sha1.as_slice() => Err(EntryErrorKind::InvalidSha1),
+            Some(expected_digest) if digest.is_some_and(|digest| digest != *expected_digest) => Err(EntryErrorKind::InvalidDigest),
+            _ => Ok(()),
+        }
+    });
+
+    // Set when the entry file shares its content with the source file.
+    let mut linked = false;
+    let res = match res {
+        Ok(()) if config.local_link => {
+            let _ = fs::remove_file(&tmp_file).await;
+            match fs::hard_link(&source_file, &tmp_file).await {
+                Ok(()) => {
+                    linked = true;
+                    Ok(())
+                }
+                Err(_) => fs::copy(&source_file, &tmp_file).await.map(drop).map_err(EntryErrorKind::new_io),
+            }
+        }
+        res => res,
+    };
+
+    let res = match res {
+        Ok(()) => fs::rename(&tmp_file, &*entry.core.file).await.map_err(EntryErrorKind::new_io),
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = res {
+        let _ = fs::remove_file(&tmp_file).await;
+        return Err(e);
+    }
+
+    // The possible cache metadata is no longer relevant for this file.
+    if entry.use_cache {
+        let _ = fs::remove_file(entry.core.file.to_path_buf().appended(".cache")).await;
+    }
+
+    // A linked file is opened read-only, writing to the handle would otherwise modify
+    // the local source file.
+    let handle = match entry.keep_open {
+        true => Some(File::options()
+            .read(true)
+            .write(!linked)
+            .open(&*entry.core.file).await
+            .map_err(EntryErrorKind::new_io)?
+            .into_std().await),
+        false => None,
+    };
+
+    Ok(EntrySuccessInner {
+        source_index,
+        source_url: Box::from(url),
         size,
         sha1: sha1.into(),
+        digest,
         handle,
     })
 
 }
 
+/// Internal retry state of an entry being downloaded from an URL.
+struct EntryRetry<'a> {
+    /// The URL being requested.
+    url: &'a str,
+    /// The retry policy used to compute delays.
+    policy: &'a RetryPolicy,
+    /// The maximum number of retries.
+    max_retry: u8,
+    /// The number of retries already done.
+    try_num: u8,
+}
+
+impl EntryRetry<'_> {
+
+    /// Wait before the next try, if any, the delay is computed from the policy if not
+    /// given, returning false if no more try is allowed, or none if cancelled.
+    async fn wait(
+        &mut self, 
+        delay: Option<Duration>, 
+        progress_sender: &mut impl EntryProgressSender,
+        cancel_token: Option<&CancelToken>,
+    ) -> Option<bool> {
+
+        if self.try_num >= self.max_retry {
+            return Some(false);
+        }
+
+        let delay = match delay {
+            Some(delay) => delay.min(self.policy.max_delay),
+            None => self.policy.delay(self.try_num),
+        };
+
+        self.try_num += 1;
+        progress_sender.send(EntryProgress::Retried { 
+            url: Box::from(self.url), 
+            retry: self.try_num, 
+            delay,
+        }).await;
+
+        cancellable(cancel_token, tokio::time::sleep(delay)).await?;
+        Some(true)
+
+    }
+
+    /// Send the given request and retry it while the response has a transient status, 
+    /// or on connection errors if enabled, returning none if cancelled.
+    async fn send(
+        &mut self, 
+        req: reqwest::RequestBuilder, 
+        retry_send_errors: bool,
+        progress_sender: &mut impl EntryProgressSender,
+        cancel_token: Option<&CancelToken>,
+    ) -> Option<reqwest::Result<reqwest::Response>> {
+        loop {
+
+            let req = req.try_clone().expect("request without body should be cloneable");
+            let res = cancellable(cancel_token, req.send()).await?;
+
+            let delay = match &res {
+                Ok(res) if is_transient_status(res.status()) => response_retry_after(res),
+                Err(e) if retry_send_errors && (e.is_connect() || e.is_timeout() || e.is_request()) => None,
+                _ => return Some(res),
+            };
+
+            if !self.wait(delay, progress_sender, cancel_token).await? {
+                return Some(res);
+            }
+
+        }
+    }
+
+}
+
+/// Return true if the given status is a transient error that should be retried.
+fn is_transient_status(status: StatusCode) -> bool {
+    matches!(status, 
+        StatusCode::REQUEST_TIMEOUT |
+        StatusCode::TOO_MANY_REQUESTS |
+        StatusCode::INTERNAL_SERVER_ERROR |
+        StatusCode::BAD_GATEWAY |
+        StatusCode::SERVICE_UNAVAILABLE |
+        StatusCode::GATEWAY_TIMEOUT)
+}
+
+/// Return the delay given by the `Retry-After` header of the response, in seconds or
+/// as an HTTP date.
+fn response_retry_after(res: &reqwest::Response) -> Option<Duration> {
+    
+    let value = res.headers().get(header::RETRY_AFTER)?.to_str().ok()?;
+    if let Ok(secs) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.to_utc() - chrono::Utc::now()).to_std().unwrap_or_default())
+
+}
+
+/// Internal writer used to hash the already downloaded data of a partial file, with 
+/// the SHA-1 and the optional other digest at the same time.
+struct DigestWriter<'a> {
+    sha1: &'a mut Sha1,
+    digest_hasher: Option<&'a mut DigestHasher>,
+}
+
+impl Write for DigestWriter<'_> {
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sha1.update(buf);
+        if let Some(digest_hasher) = &mut self.digest_hasher {
+            digest_hasher.update(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+}
+
+/// Given the partial file of an entry and its metadata, return the size of the 
+/// partial file and the validator to use to resume the download, only if the partial
+/// file is not empty and its metadata is valid for the given URL. If returned, the 
+/// partial file has been claimed by moving it to the given temporary file.
+async fn check_download_part(
+    url: &str, 
+    part_file: &Path, 
+    part_meta_file: &Path, 
+    tmp_file: &Path,
+) -> io::Result<Option<(u64, String)>> {
+
+    let part_meta = match File::open(part_meta_file).await {
+        Ok(file) => serde_json::from_reader::<_, serde::PartMeta>(file.into_std().await).ok(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+
+    let Some(part_meta) = part_meta.filter(|meta| meta.url == url) else {
+        return Ok(None);
+    };
+
+    // Strong entity tags are preferred over the last modified date.
+    let Some(validator) = part_meta.etag.or(part_meta.last_modified) else {
+        return Ok(None);
+    };
+
+    // If the partial file is not found, it may have been claimed by another download.
+    match fs::rename(part_file, tmp_file).await {
+        Ok(()) => (),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    }
+
+    let size = fs::metadata(tmp_file).await?.len();
+    if size == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some((size, validator)))
+
+}
+
+/// Write the partial metadata file of an entry, errors are silently ignored because 
+/// this is only needed to resume downloads.
+async fn write_download_part_meta(part_meta_file: &Path, part_meta: &serde::PartMeta) {
+
+    let Ok(writer) = File::create(part_meta_file).await else {
+        return;
+    };
+
+    let writer = BufWriter::new(writer.into_std().await);
+    if serde_json::to_writer(writer, part_meta).is_err() {
+        let _ = fs::remove_file(part_meta_file).await;
+    }
+
+}
+
+/// Return the partial metadata of the given response, used to resume it later with 
+/// a `If-Range` header, none if the response has no usable validator, weak entity 
+/// tags are not allowed for that.
+fn response_part_meta(url: &str, res: &reqwest::Response) -> Option<serde::PartMeta> {
+
+    let etag = res.headers().get(header::ETAG)
+        .and_then(|h| h.to_str().ok())
+        .filter(|etag| !etag.starts_with("W/"))
+        .map(str::to_string);
+
+    let last_modified = res.headers().get(header::LAST_MODIFIED)
+        .and_then(|h| h.to_str().ok().map(str::to_string));
+
+    if etag.is_none() && last_modified.is_none() {
+        return None;
+    }
+
+    Some(serde::PartMeta {
+        url: url.to_string(),
+        etag,
+        last_modified,
+    })
+
+}
+
+/// Return the start offset of a partial content response, from its `Content-Range`
+/// header, formatted like `bytes <start>-<end>/<size>`.
+fn content_range_start(res: &reqwest::Response) -> Option<u64> {
+    res.headers().get(header::CONTENT_RANGE)?
+        .to_str().ok()?
+        .strip_prefix("bytes ")?
+        .split_once('-')?.0
+        .parse().ok()
+}
+
 /// Given a file and its cache file, return the cache metadata only if the file is 
 /// existing and the file has not been modified (size and SHA-1). 
 /// 
@@ -1073,7 +2944,7 @@ async fn check_download_cache(file: &Path, cache_file: &Path) -> io::Result<Opti
 
     // Start by checking size...
     let actual_size = reader.seek(SeekFrom::End(0)).await?;
-    if cache.size as u64 != actual_size {
+    if cache.size != actual_size {
         return Ok(None);
     }
 
@@ -1103,33 +2974,134 @@ async fn check_download_cache(file: &Path, cache_file: &Path) -> io::Result<Opti
 /// actually used, but this still caused the actual enum type to be different, and the
 /// monomorphization didn't seem to work, increasing the binary size by 40 kB.
 trait EntryProgressSender {
-    async fn send(&mut self, delta: u32);
+    async fn send(&mut self, progress: EntryProgress);
+}
+
+impl<S: EntryProgressSender> EntryProgressSender for &mut S {
+    async fn send(&mut self, progress: EntryProgress) {
+        (**self).send(progress).await
+    }
+}
+
+/// Internal progress of an entry being downloaded, sent to the progress sender.
+enum EntryProgress {
+    /// The entry download has started.
+    Started,
+    /// The given number of bytes has been received.
+    Received(u64),
+    /// The entry will be retried from the given URL after the given delay.
+    Retried { url: Box<str>, retry: u8, delay: Duration },
 }
 
 /// Implementation of the progress sender for the `download_many` function with channel.
 struct ChannelEntryProgressSender {
-    sender: mpsc::Sender<u32>,
+    sender: mpsc::Sender<(usize, EntryProgress)>,
+    index: usize,
 }
 
 impl EntryProgressSender for ChannelEntryProgressSender {
-    async fn send(&mut self, delta: u32) {
-        self.sender.send(delta).await.unwrap();
+    async fn send(&mut self, progress: EntryProgress) {
+        self.sender.send((self.index, progress)).await.unwrap();
     }
 }
 
 /// A progress sender specialized when downloading a single progress, we can therefore
 /// directly send any progress directly to the handler!
-struct DirectEntryProgressSender<'a> {
-    handler: &'a mut dyn Handler,
-    size: &'a mut u32,
-    total_size: u32,
+struct DirectEntryProgressSender<'a, H: Handler + ?Sized> {
+    handler: &'a mut H,
+    entry: &'a Entry,
+    size: &'a mut u64,
+    total_size: u64,
+    speed_meter: SpeedMeter,
+}
+
+impl<H: Handler + ?Sized> EntryProgressSender for DirectEntryProgressSender<'_, H> {
+    async fn send(&mut self, progress: EntryProgress) {
+        match progress {
+            EntryProgress::Started => {
+                self.handler.on_entry_event(0, EntryEvent::Started { 
+                    url: &self.entry.core.url, 
+                    file: &self.entry.core.file, 
+                    expected_size: self.entry.expected_size,
+                });
+            }
+            EntryProgress::Received(delta) => {
+                *self.size += delta;
+                self.handler.on_progress(0, 1, *self.size, self.total_size);
+                self.handler.on_entry_event(0, EntryEvent::Progress { 
+                    size: *self.size, 
+                    expected_size: self.entry.expected_size,
+                });
+                if self.speed_meter.update(*self.size) {
+                    self.handler.on_speed(self.speed_meter.speed(), self.speed_meter.eta(*self.size, self.total_size));
+                }
+            }
+            EntryProgress::Retried { url, retry, delay } => {
+                self.handler.on_entry_event(0, EntryEvent::Retried { url: &url, retry, delay });
+            }
+        }
+    }
+}
+
+/// Internal meter of the download speed, smoothed with an exponential moving average
+/// that is updated at regular intervals.
+struct SpeedMeter {
+    /// The last time the speed has been updated.
+    last_instant: Instant,
+    /// The size at the last update.
+    last_size: u64,
+    /// The current smoothed speed, in bytes per second, none before the first update.
+    speed: Option<f64>,
 }
 
-impl EntryProgressSender for DirectEntryProgressSender<'_> {
-    async fn send(&mut self, delta: u32) {
-        *self.size += delta;
-        self.handler.on_progress(0, 1, *self.size, self.total_size);
+impl SpeedMeter {
+
+    /// The minimum interval between two updates of the speed.
+    const INTERVAL: Duration = Duration::from_millis(500);
+    /// The weight of the last measured speed in the moving average.
+    const SMOOTHING: f64 = 0.3;
+
+    fn new() -> Self {
+        Self {
+            last_instant: Instant::now(),
+            last_size: 0,
+            speed: None,
+        }
+    }
+
+    /// Update the meter with the current received size, returning true if the speed
+    /// has been updated.
+    fn update(&mut self, size: u64) -> bool {
+        
+        let elapsed = self.last_instant.elapsed();
+        if elapsed < Self::INTERVAL {
+            return false;
+        }
+
+        let instant_speed = size.saturating_sub(self.last_size) as f64 / elapsed.as_secs_f64();
+        self.speed = Some(match self.speed {
+            Some(speed) => speed + (instant_speed - speed) * Self::SMOOTHING,
+            None => instant_speed,
+        });
+
+        self.last_instant = Instant::now();
+        self.last_size = size;
+        true
+
+    }
+
+    /// The current speed in bytes per second.
+    fn speed(&self) -> u64 {
+        self.speed.unwrap_or(0.0) as u64
+    }
+
+    /// The estimated remaining time to reach the given total size, if known.
+    fn eta(&self, size: u64, total_size: u64) -> Option<Duration> {
+        let speed = self.speed.filter(|&speed| speed > 0.0)?;
+        let remaining = total_size.checked_sub(size).filter(|&remaining| remaining != 0)?;
+        Some(Duration::from_secs_f64(remaining as f64 / speed))
     }
+
 }
 
 /// Internal module for serde of cache metadata file.
@@ -1142,7 +3114,7 @@ mod serde {
         /// The full URL of the cached resource, just for information.
         pub url: String,
         /// Size of the cached file, used to verify its validity.
-        pub size: u32,
+        pub size: u64,
         /// SHA-1 hash of the cached file, used to verify its validity. 
         pub sha1: HexString<20>,
         /// The ETag if present.
@@ -1151,4 +3123,15 @@ mod serde {
         pub last_modified: Option<String>,
     }
 
+    #[derive(Debug, serde::Deserialize, serde::Serialize)]
+    pub struct PartMeta {
+        /// The full URL of the partially downloaded resource, it should match in order
+        /// to resume the download.
+        pub url: String,
+        /// The strong ETag if present.
+        pub etag: Option<String>,
+        /// Last modified data if present.
+        pub last_modified: Option<String>,
+    }
+
 }
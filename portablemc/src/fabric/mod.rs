@@ -93,13 +93,67 @@ impl Installer {
     }
 
     /// Install the currently configured Fabric loader with the given handler.
+    /// 
+    /// The dedicated server is installed instead of the client if the side of the 
+    /// underlying base installer is [`base::InstallSide::Server`], in such case the
+    /// server profile of the loader is used on top of the vanilla server and the root
+    /// version has a `-server` suffix.
+    /// 
+    /// See [`base::Installer::install`] about the internal Tokio runtime.
     #[inline]
     pub fn install(&mut self, mut handler: impl Handler) -> Result<Game> {
         self.install_dyn(&mut handler)
     }
 
+    /// Async version of [`Self::install`], to be awaited from an existing Tokio 
+    /// runtime.
+    pub async fn install_async(&mut self, mut handler: impl Handler + Send) -> Result<Game> {
+        self.install_impl(&mut handler as &mut (dyn Handler + Send)).await
+    }
+
+    /// Resolve the currently configured Fabric loader like it would be installed, but
+    /// return a plan of everything that would be downloaded, see 
+    /// [`base::Installer::plan`]. Missing version metadata are still fetched.
+    #[inline]
+    pub fn plan(&mut self, mut handler: impl Handler) -> Result<base::Plan> {
+        self.plan_dyn(&mut handler)
+    }
+
+    /// Async version of [`Self::plan`], to be awaited from an existing Tokio runtime.
+    pub async fn plan_async(&mut self, mut handler: impl Handler + Send) -> Result<base::Plan> {
+        self.plan_impl(&mut handler as &mut (dyn Handler + Send)).await
+    }
+
     #[inline(never)]
     fn install_dyn(&mut self, handler: &mut dyn Handler) -> Result<Game> {
+        crate::tokio::sync(self.install_impl(handler))
+    }
+
+    #[inline(never)]
+    fn plan_dyn(&mut self, handler: &mut dyn Handler) -> Result<base::Plan> {
+        crate::tokio::sync(self.plan_impl(handler))
+    }
+
+    async fn install_impl<H: Handler + ?Sized>(&mut self, handler: &mut H) -> Result<Game> {
+        let (game_version, loader_version, root_version) = self.resolve_versions().await?;
+        let mut handler = self.internal_handler(handler, &game_version, &loader_version, &root_version);
+        let res = self.mojang.install_impl(&mut handler).await;
+        handler.error?;
+        Ok(res?)
+    }
+
+    async fn plan_impl<H: Handler + ?Sized>(&mut self, handler: &mut H) -> Result<base::Plan> {
+        let (game_version, loader_version, root_version) = self.resolve_versions().await?;
+        let mut handler = self.internal_handler(handler, &game_version, &loader_version, &root_version);
+        let res = self.mojang.plan_impl(&mut handler).await;
+        handler.error?;
+        Ok(res?)
+    }
+
+    /// Internal function to resolve the game and loader versions to install, and 
+    /// set the root version of the mojang installer, the game, loader and root versions
+    /// are returned.
+    async fn resolve_versions(&mut self) -> Result<(String, String, String)> {
 
         let Self {
             ref mut mojang,
@@ -108,14 +162,15 @@ impl Installer {
             ref loader_version,
         } = *self;
 
-        let api = Api::new(loader);
+        let mut api = Api::new(loader);
+        api.set_download_config(mojang.base().download_config().clone());
 
         let game_version = match game_version {
             GameVersion::Stable |
             GameVersion::Unstable => {
 
                 let stable = matches!(game_version, GameVersion::Stable);
-                let versions = api.request_game_versions()?;
+                let versions = api.request_game_versions_async().await?;
 
                 match versions.find_latest(stable) {
                     Some(v) => v.name().to_string(),
@@ -134,7 +189,7 @@ impl Installer {
             LoaderVersion::Unstable => {
                 
                 let stable = matches!(loader_version, LoaderVersion::Stable);
-                let versions = api.request_loader_versions(Some(&game_version))?;
+                let versions = api.request_loader_versions_async(Some(&game_version)).await?;
                 
                 match versions.find_latest(stable) {
                     Some(v) => v.name().to_string(),
@@ -149,34 +204,44 @@ impl Installer {
         };
 
         // Set the root version for underlying Mojang installer, equal to the name that
-        // we'll give to the version.
+        // we'll give to the version. The server has its own version because the loader
+        // has a different profile for it.
+        let side = mojang.base().side();
         let prefix = loader.default_prefix();
-        let root_version = format!("{prefix}-{game_version}-{loader_version}");
+        let root_version = match side {
+            base::InstallSide::Client => format!("{prefix}-{game_version}-{loader_version}"),
+            base::InstallSide::Server => format!("{prefix}-{game_version}-{loader_version}-server"),
+        };
         mojang.set_version(root_version.clone());
         
         // NOTE: We don't need to fetch exclude that version because the handler below
         // already take care of that! 'mojang.add_fetch_exclude(...)'
 
-        // Scoping the temporary internal handler.
-        let game = {
-
-            let mut handler = InternalHandler {
-                inner: &mut *handler,
-                error: Ok(()),
-                api,
-                root_version: &root_version,
-                game_version: &game_version,
-                loader_version: &loader_version,
-            };
-    
-            // Same as above, we are giving a &mut dyn ref to avoid huge monomorphization.
-            let res = mojang.install(&mut handler);
-            handler.error?;
-            res?
+        Ok((game_version, loader_version, root_version))
 
-        };
-        
-        Ok(game)
+    }
+
+    /// Internal function to construct the internal handler given to the mojang 
+    /// installer, for the given resolved versions.
+    fn internal_handler<'a, H: Handler + ?Sized>(&self, 
+        handler: &'a mut H,
+        game_version: &'a str,
+        loader_version: &'a str,
+        root_version: &'a str,
+    ) -> InternalHandler<'a, H> {
+
+        let mut api = Api::new(self.loader);
+        api.set_download_config(self.mojang.base().download_config().clone());
+
+        InternalHandler {
+            inner: handler,
+            error: Ok(()),
+            api,
+            side: self.mojang.base().side(),
+            root_version,
+            game_version,
+            loader_version,
+        }
 
     }
 
@@ -362,7 +427,10 @@ pub struct Api {
     /// - `/versions/loader/<game_version>`
     /// - `/versions/loader/<game_version>/<loader_loader>` (returning status 400 or 404)
     /// - `/versions/loader/<game_version>/<loader_loader>/profile/json` (returning status 400 or 404)
+    /// - `/versions/loader/<game_version>/<loader_loader>/server/json` (returning status 400 or 404)
     base_url: &'static str,
+    /// The download configuration used for requests.
+    download_config: download::Config,
 }
 
 impl Api {
@@ -375,70 +443,127 @@ impl Api {
                 Loader::Quilt => "https://meta.quiltmc.org/v3",
                 Loader::LegacyFabric => "https://meta.legacyfabric.net/v2",
                 Loader::Babric => "https://meta.babric.glass-launcher.net/v2",
-            }
+            },
+            download_config: download::Config::default(),
         }
     }
 
+    /// The download configuration used for requests to this API.
+    #[inline]
+    pub fn download_config(&self) -> &download::Config {
+        &self.download_config
+    }
+
+    /// See [`Self::download_config`].
+    #[inline]
+    pub fn set_download_config(&mut self, config: download::Config) -> &mut Self {
+        self.download_config = config;
+        self
+    }
+
     /// Request supported game versions.
+    /// 
+    /// See [`download::Single::download`] about the internal Tokio runtime.
     pub fn request_game_versions(&self) -> Result<ApiGameVersions<'_>> {
-        self.raw_request_game_versions().map(|versions| ApiGameVersions {
+        crate::tokio::sync(self.request_game_versions_async())
+    }
+
+    /// Async version of [`Self::request_game_versions`].
+    pub async fn request_game_versions_async(&self) -> Result<ApiGameVersions<'_>> {
+        self.raw_request_game_versions().await.map(|versions| ApiGameVersions {
             _api: self,
             versions,
         })
     }
 
-    fn raw_request_game_versions(&self) -> Result<Vec<serde::Game>> {
-        crate::tokio::sync(async move {
-            crate::http::client()?
-                .get(format!("{}/versions/game", self.base_url))
+    /// Send a GET request to the given URL of the API, each of its mirror URLs is tried
+    /// in order and the first successful response is returned, or the last error.
+    async fn raw_get(&self, url: &str) -> reqwest::Result<reqwest::Response> {
+
+        let client = self.download_config.http_client()?;
+        let mut last_err = None;
+
+        for url in self.download_config.mirror_urls(url) {
+            let res = client.get(url)
                 .header(reqwest::header::ACCEPT, "application/json")
-                .send().await?
-                .error_for_status()?
+                .send().await
+                .and_then(reqwest::Response::error_for_status);
+            match res {
+                Ok(res) => return Ok(res),
+                Err(e) => last_err = Some(e),
+            }
+        }
+
+        Err(last_err.expect("mirror urls should not be empty"))
+
+    }
+
+    /// Return an offline error for the given URL if the download configuration is 
+    /// offline, this must be checked before any request to the API.
+    fn check_offline(&self, url: &str) -> Result<()> {
+        if self.download_config.offline() {
+            Err(Error::from(base::Error::Offline { 
+                resources: vec![base::MissingResource { url: url.to_string(), file: None }],
+            }))
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn raw_request_game_versions(&self) -> Result<Vec<serde::Game>> {
+        let url = format!("{}/versions/game", self.base_url);
+        self.check_offline(&url)?;
+        async move {
+            self.raw_get(&url).await?
                 .json::<Vec<serde::Game>>().await
-        }).map_err(|e| {
+        }.await.map_err(|e| {
             Error::from(base::Error::new_reqwest(e, "request all game versions"))
         })
     }
 
     /// Request supported loader versions.
+    /// 
+    /// See [`download::Single::download`] about the internal Tokio runtime.
     pub fn request_loader_versions(&self, game_version: Option<&str>) -> Result<ApiLoaderVersions<'_>> {
+        crate::tokio::sync(self.request_loader_versions_async(game_version))
+    }
+
+    /// Async version of [`Self::request_loader_versions`].
+    pub async fn request_loader_versions_async(&self, game_version: Option<&str>) -> Result<ApiLoaderVersions<'_>> {
         if let Some(game_version) = game_version {
-            self.raw_request_game_loader_versions(game_version).map(|versions| ApiLoaderVersions {
+            self.raw_request_game_loader_versions(game_version).await.map(|versions| ApiLoaderVersions {
                 _api: self,
                 versions: versions.into_iter().map(|v| v.loader).collect(),
             })
         } else {
-            self.raw_request_loader_versions().map(|versions| ApiLoaderVersions {
+            self.raw_request_loader_versions().await.map(|versions| ApiLoaderVersions {
                 _api: self,
                 versions,
             })
         }
     }
 
-    fn raw_request_loader_versions(&self) -> Result<Vec<serde::Loader>> {
-        crate::tokio::sync(async move {
-            crate::http::client()?
-                .get(format!("{}/versions/loader", self.base_url))
-                .header(reqwest::header::ACCEPT, "application/json")
-                .send().await?
-                .error_for_status()?
+    async fn raw_request_loader_versions(&self) -> Result<Vec<serde::Loader>> {
+        let url = format!("{}/versions/loader", self.base_url);
+        self.check_offline(&url)?;
+        async move {
+            self.raw_get(&url).await?
                 .json::<Vec<serde::Loader>>().await
-        }).map_err(|e| {
+        }.await.map_err(|e| {
             Error::from(base::Error::new_reqwest(e, "request all loader versions"))
         })
     }
 
     /// Request supported loader versions for the given game version.
-    fn raw_request_game_loader_versions(&self, game_version: &str) -> Result<Vec<serde::GameLoader>> {
+    async fn raw_request_game_loader_versions(&self, game_version: &str) -> Result<Vec<serde::GameLoader>> {
         
-        let ret = crate::tokio::sync(async move {
-            crate::http::client()?
-                .get(format!("{}/versions/loader/{game_version}", self.base_url))
-                .header(reqwest::header::ACCEPT, "application/json")
-                .send().await?
-                .error_for_status()?
+        let url = format!("{}/versions/loader/{game_version}", self.base_url);
+        self.check_offline(&url)?;
+
+        let ret = async move {
+            self.raw_get(&url).await?
                 .json::<Vec<serde::GameLoader>>().await
-        });
+        }.await;
 
         if let Err(e) = &ret && let Some(StatusCode::NOT_FOUND | StatusCode::BAD_REQUEST) = e.status() {
             return Ok(Vec::new());
@@ -451,17 +576,16 @@ impl Api {
     }
 
     /// Return true if the given game version has any loader versions supported.
-    fn raw_request_has_game_loader_versions(&self, game_version: &str) -> Result<bool> {
+    async fn raw_request_has_game_loader_versions(&self, game_version: &str) -> Result<bool> {
         
-        let ret = crate::tokio::sync(async move {
-            crate::http::client()?
-                .get(format!("{}/versions/loader/{game_version}", self.base_url))
-                .header(reqwest::header::ACCEPT, "application/json")
-                .send().await?
-                .error_for_status()?
+        let url = format!("{}/versions/loader/{game_version}", self.base_url);
+        self.check_offline(&url)?;
+
+        let ret = async move {
+            self.raw_get(&url).await?
                 .bytes().await
                 .map(|bytes| &*bytes != b"[]") // This avoids parsing JSON
-        });
+        }.await;
 
         if let Err(e) = &ret && let Some(StatusCode::NOT_FOUND | StatusCode::BAD_REQUEST) = e.status() {
             return Ok(false);
@@ -473,17 +597,22 @@ impl Api {
 
     }
 
-    /// Request the prebuilt version metadata for the given game and loader versions.
-    fn raw_request_game_loader_version_metadata(&self, game_version: &str, loader_version: &str) -> Result<Option<base::serde::VersionMetadata>> {
+    /// Request the prebuilt version metadata for the given game and loader versions, 
+    /// the client or server profile is requested depending on the given side.
+    async fn raw_request_game_loader_version_metadata(&self, game_version: &str, loader_version: &str, side: base::InstallSide) -> Result<Option<base::serde::VersionMetadata>> {
         
-        let ret = crate::tokio::sync(async move {
-            crate::http::client()?
-                .get(format!("{}/versions/loader/{game_version}/{loader_version}/profile/json", self.base_url))
-                .header(reqwest::header::ACCEPT, "application/json")
-                .send().await?
-                .error_for_status()?
+        let profile = match side {
+            base::InstallSide::Client => "profile",
+            base::InstallSide::Server => "server",
+        };
+
+        let url = format!("{}/versions/loader/{game_version}/{loader_version}/{profile}/json", self.base_url);
+        self.check_offline(&url)?;
+
+        let ret = async move {
+            self.raw_get(&url).await?
                 .json::<base::serde::VersionMetadata>().await
-        });
+        }.await;
 
         if let Err(e) = &ret && let Some(StatusCode::NOT_FOUND | StatusCode::BAD_REQUEST) = e.status() {
             return Ok(None);
@@ -582,21 +711,36 @@ impl<'d> ApiLoaderVersion<'d> {
 // ========================== //
 
 /// Internal handler given to the mojang installer.
-struct InternalHandler<'a> {
+struct InternalHandler<'a, H: ?Sized> {
     /// Inner handler.
-    inner: &'a mut dyn Handler,
+    inner: &'a mut H,
     /// If there is an error in the handler.
     error: Result<()>,
     /// The real version is, as defined 
     api: Api,
+    /// The side of the profile to request.
+    side: base::InstallSide,
     root_version: &'a str,
     game_version: &'a str,
     loader_version: &'a str,
 }
 
-impl moj::Handler for InternalHandler<'_> {
+impl<H: Handler + ?Sized> moj::Handler for InternalHandler<'_, H> {
+    
+    fn on_event(&mut self, event: moj::Event) {
+        self.inner.on_event(Event::Mojang(event));
+    }
+
+}
+
+impl<H: Handler + ?Sized> moj::AsyncHandler for InternalHandler<'_, H> {
+
+    #[inline]
+    fn as_dyn(&mut self) -> &mut dyn moj::Handler {
+        self
+    }
 
-    fn on_event(&mut self, mut event: moj::Event) {
+    async fn on_event_async(&mut self, mut event: moj::Event<'_>) {
 
         let ret = match event {
             moj::Event::Base(base::Event::NeedVersion { 
@@ -604,7 +748,7 @@ impl moj::Handler for InternalHandler<'_> {
                 file, 
                 ref mut retry, 
             }) => {
-                match self.inner_need_version(version, file) {
+                match self.inner_need_version(version, file).await {
                     Ok(true) => {
                         **retry = true;
                         Ok(())
@@ -627,9 +771,9 @@ impl moj::Handler for InternalHandler<'_> {
 
 }
 
-impl InternalHandler<'_> {
+impl<H: Handler + ?Sized> InternalHandler<'_, H> {
 
-    fn inner_need_version(&mut self, version: &str, file: &Path) -> Result<bool> {
+    async fn inner_need_version(&mut self, version: &str, file: &Path) -> Result<bool> {
 
         if version != self.root_version {
             return Ok(false);
@@ -645,10 +789,10 @@ impl InternalHandler<'_> {
         // version if he will. But now that we need to request the prebuilt
         // version metadata, in case of error we'll try to understand what's the
         // issue: unknown game version or unknown loader version?
-        let mut metadata = match self.api.raw_request_game_loader_version_metadata(self.game_version, self.loader_version)? {
+        let mut metadata = match self.api.raw_request_game_loader_version_metadata(self.game_version, self.loader_version, self.side).await? {
             Some(metadata) => metadata,
             None => {
-                if self.api.raw_request_has_game_loader_versions(self.game_version)? {
+                if self.api.raw_request_has_game_loader_versions(self.game_version).await? {
                     return Err(Error::LoaderVersionNotFound { 
                         game_version: self.game_version.to_string(),
                         loader_version: self.loader_version.to_string(),
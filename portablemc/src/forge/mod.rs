@@ -3,13 +3,14 @@
 
 mod serde;
 
-use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek};
-use std::process::{Command, Output};
+use std::io::{self, BufReader, BufWriter, Read, Seek};
+use std::process::{Command, Output, Stdio};
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use std::iter::FusedIterator;
 use std::fmt::Write;
 use std::{env, fs};
+use std::time::Duration;
 use std::fs::File;
 
 use crate::moj::{self, FetchExclude, HandlerInto as _};
@@ -88,62 +89,80 @@ impl Installer {
     }
 
     /// Install the currently configured Forge/NeoForge loader with the given handler.
+    /// 
+    /// The dedicated server is installed instead of the client if the side of the 
+    /// underlying base installer is [`base::InstallSide::Server`], in such case the
+    /// root version has a `-server` suffix.
+    /// 
+    /// See [`base::Installer::install`] about the internal Tokio runtime.
     #[inline]
     pub fn install(&mut self, mut handler: impl Handler) -> Result<Game> {
         self.install_dyn(&mut handler)
     }
 
+    /// Async version of [`Self::install`], to be awaited from an existing Tokio 
+    /// runtime. The outputs of installer processors are read on the blocking threads
+    /// of the runtime.
+    pub async fn install_async(&mut self, mut handler: impl Handler + Send) -> Result<Game> {
+        self.install_impl(&mut handler as &mut (dyn Handler + Send)).await
+    }
+
+    /// Resolve the currently configured Forge/NeoForge loader like it would be 
+    /// installed, but return a plan of everything that would be downloaded, see 
+    /// [`base::Installer::plan`]. Missing version metadata are still fetched.
+    /// 
+    /// The loader must already be installed, because the libraries it needs are only
+    /// known once its installer has been run, if not then [`Error::NotInstalled`] is
+    /// returned with the reason for installing it. Unlike the installation, the 
+    /// libraries generated by the loader installer are not checked on the client side.
+    #[inline]
+    pub fn plan(&mut self, mut handler: impl Handler) -> Result<base::Plan> {
+        self.plan_dyn(&mut handler)
+    }
+
+    /// Async version of [`Self::plan`], to be awaited from an existing Tokio runtime.
+    pub async fn plan_async(&mut self, mut handler: impl Handler + Send) -> Result<base::Plan> {
+        self.plan_impl(&mut handler as &mut (dyn Handler + Send)).await
+    }
+
     #[inline(never)]
     fn install_dyn(&mut self, handler: &mut dyn Handler) -> Result<Game> {
+        crate::tokio::sync(self.install_impl(handler))
+    }
 
-        let Self {
-            ref mut mojang,
-            loader,
-            ref version,
-        } = *self;
+    #[inline(never)]
+    fn plan_dyn(&mut self, handler: &mut dyn Handler) -> Result<base::Plan> {
+        crate::tokio::sync(self.plan_impl(handler))
+    }
 
-        // Request the repository if needed!
-        let version = match version {
-            Version::Name(name) => name.clone(),
-            Version::Stable(game_version) |
-            Version::Unstable(game_version) => {
-                let stable = matches!(version, Version::Stable(_));
-                match Repo::request(loader)?.find_latest(&game_version, stable) {
-                    Some(v) => v.name().to_string(),
-                    None => return Err(Error::LatestVersionNotFound { 
-                        game_version: game_version.clone(), 
-                        stable,
-                    }),
-                }
+    async fn plan_impl<H: Handler + ?Sized>(&mut self, handler: &mut H) -> Result<base::Plan> {
+
+        let (_config, root_version, side) = self.resolve_config().await?;
+
+        match self.mojang.plan_impl(&mut (&mut *handler).into_mojang()).await {
+            Ok(plan) => Ok(plan),
+            Err(e) => match error_install_reason(&e, &root_version, side) {
+                Some(reason) => Err(Error::NotInstalled { reason }),
+                None => Err(Error::Mojang(e)),
             }
-        };
+        }
 
-        let config = match loader {
-            Loader::Forge => InstallConfig::new_forge(&version),
-            Loader::NeoForge => InstallConfig::new_neoforge(&version),
-        };
-        
-        // Shortcut because the version name is invalid and there will be no installer or
-        // that installer is not supported.
-        let Some(config) = config else {
-            return Err(Error::InstallerNotFound { version });
-        };
+    }
 
-        // Construct the root version id.
-        let prefix = config.default_prefix;
-        let root_version = format!("{prefix}-{version}");
+    async fn install_impl<H: Handler + ?Sized>(&mut self, handler: &mut H) -> Result<Game> {
 
-        // Adding it to fetch exclude, we don't want to try to fetch it from Mojang's 
-        // manifest: it's pointless and it avoids trying to fetch the manifest.
-        mojang.add_fetch_exclude(FetchExclude::Exact(root_version.clone()));
+        let (config, root_version, side) = self.resolve_config().await?;
+        let mojang = &mut self.mojang;
 
         // The goal is to run the installer a first time, check potential errors to 
         // know if the error is related to the loader, or not.
-        mojang.set_version(root_version.clone());
-        let reason = match mojang.install((&mut *handler).into_mojang()) {
+        let reason = match mojang.install_impl(&mut (&mut *handler).into_mojang()).await {
             Ok(game) => {
 
-                let Some(check_libraries) = config.check_libraries else {
+                // All server libraries are listed in its version metadata, so missing 
+                // ones are already detected by the base installer.
+                let Some(check_libraries) = config.check_libraries
+                    .filter(|_| side == serde::InstallSide::Client) else {
                     return Ok(game);
                 };
 
@@ -262,26 +281,84 @@ impl Installer {
                 }
 
             }
-            Err(moj::Error::Base(base::Error::VersionNotFound { version })) 
-            if version == root_version => {
-                InstallReason::MissingVersionMetadata
-            }
-            Err(moj::Error::Base(base::Error::LibraryNotFound { name: gav })) 
-            if gav.group() == "net.minecraftforge" && gav.artifact() == "forge" => {
-                InstallReason::MissingCoreLibrary
+            Err(e) => match error_install_reason(&e, &root_version, side) {
+                Some(reason) => reason,
+                None => return Err(Error::Mojang(e)),
             }
-            Err(e) => return Err(Error::Mojang(e))
         };
 
-        try_install(&mut *handler, &mut *mojang, &config, &root_version, serde::InstallSide::Client, reason)?;
+        try_install(&mut *handler, &mut *mojang, &config, &root_version, side, reason).await?;
 
         // Retrying launch!
         mojang.set_version(root_version);
-        let game = mojang.install((&mut *handler).into_mojang())?;
+        let game = mojang.install_impl(&mut (&mut *handler).into_mojang()).await?;
         Ok(game)
 
     }
 
+    /// Internal function to resolve the loader version to install and its install 
+    /// configuration, the root version is set on the mojang installer and returned 
+    /// with the side to install.
+    async fn resolve_config(&mut self) -> Result<(InstallConfig, String, serde::InstallSide)> {
+
+        let Self {
+            ref mut mojang,
+            loader,
+            ref version,
+        } = *self;
+
+        // Request the repository if needed!
+        let version = match version {
+            Version::Name(name) => name.clone(),
+            Version::Stable(game_version) |
+            Version::Unstable(game_version) => {
+                let stable = matches!(version, Version::Stable(_));
+                let repo = Repo::request_impl(loader, mojang.base().download_config(), mojang.base().cancel_token()).await?;
+                match repo.find_latest(game_version, stable) {
+                    Some(v) => v.name().to_string(),
+                    None => return Err(Error::LatestVersionNotFound { 
+                        game_version: game_version.clone(), 
+                        stable,
+                    }),
+                }
+            }
+        };
+
+        let config = match loader {
+            Loader::Forge => InstallConfig::new_forge(&version),
+            Loader::NeoForge => InstallConfig::new_neoforge(&version),
+        };
+        
+        // Shortcut because the version name is invalid and there will be no installer or
+        // that installer is not supported.
+        let Some(config) = config else {
+            return Err(Error::InstallerNotFound { version });
+        };
+
+        // The side to install follows the one of the base installer.
+        let side = match mojang.base().side() {
+            base::InstallSide::Client => serde::InstallSide::Client,
+            base::InstallSide::Server => serde::InstallSide::Server,
+        };
+
+        // Construct the root version id, the server has its own version because its
+        // libraries and arguments are completely different from the client ones.
+        let prefix = config.default_prefix;
+        let root_version = match side {
+            serde::InstallSide::Client => format!("{prefix}-{version}"),
+            serde::InstallSide::Server => format!("{prefix}-{version}-server"),
+        };
+
+        // Adding it to fetch exclude, we don't want to try to fetch it from Mojang's 
+        // manifest: it's pointless and it avoids trying to fetch the manifest.
+        mojang.add_fetch_exclude(FetchExclude::Exact(root_version.clone()));
+
+        mojang.set_version(root_version.clone());
+
+        Ok((config, root_version, side))
+
+    }
+
 }
 
 /// Events happening when installing.
@@ -336,15 +413,11 @@ impl Handler for () {
 #[allow(unused)]
 pub(crate) trait HandlerInto: Handler + Sized {
     
+    /// NOTE: The adapter type is not opaque so that the future of the async installer
+    /// can be known to be [`Send`] when the handler is.
     #[inline]
-    fn into_mojang(self) -> impl moj::Handler {
-        pub(crate) struct Adapter<H: Handler>(pub H);
-        impl<H: Handler> moj::Handler for Adapter<H> {
-            fn on_event(&mut self, event: moj::Event) {
-                self.0.on_event(Event::Mojang(event));
-            }
-        }
-        Adapter(self)
+    fn into_mojang(self) -> MojangAdapter<Self> {
+        MojangAdapter(self)
     }
     
     #[inline]
@@ -361,6 +434,23 @@ pub(crate) trait HandlerInto: Handler + Sized {
 
 impl<H: Handler> HandlerInto for H {}
 
+/// Internal adapter for using a handler as a mojang handler, see 
+/// [`HandlerInto::into_mojang`].
+pub(crate) struct MojangAdapter<H: Handler>(pub H);
+
+impl<H: Handler> moj::Handler for MojangAdapter<H> {
+    fn on_event(&mut self, event: moj::Event) {
+        self.0.on_event(Event::Mojang(event));
+    }
+}
+
+impl<H: Handler> moj::AsyncHandler for MojangAdapter<H> {
+    #[inline]
+    fn as_dyn(&mut self) -> &mut dyn moj::Handler {
+        self
+    }
+}
+
 /// The Forge installer could not proceed to the installation of a version.
 #[derive(thiserror::Error, Debug)]
 #[non_exhaustive]
@@ -425,7 +515,17 @@ pub enum Error {
         name: Gav,
         file: Box<Path>,
         expected_sha1: Box<[u8; 20]>,
-    }
+    },
+    /// When installing the server, the installer has no arguments file and no launcher
+    /// JAR with a main class, so there is no known way of launching the server.
+    #[error("installer server launcher not found")]
+    InstallerServerLauncherNotFound {  },
+    /// When planning, the loader is not installed, or not completely, so the plan can't
+    /// be known without running the loader installer, the reason is linked.
+    #[error("not installed: {reason:?}")]
+    NotInstalled {
+        reason: InstallReason,
+    },
 }
 
 impl<T: Into<moj::Error>> From<T> for Error {
@@ -453,6 +553,9 @@ pub enum InstallReason {
     MissingPatchedClient,
     /// The universal client is missing.
     MissingUniversalClient,
+    /// A library of the server is missing, these are either extracted from the 
+    /// installer or generated by its processors. Reinstalling.
+    MissingServerLibrary,
 }
 
 /// Represent the different kind of loaders to install or fetch for versions.
@@ -498,21 +601,52 @@ pub struct Repo {
 impl Repo {
 
     /// Request the repository for a given loader.
+    /// 
+    /// See [`download::Single::download`] about the internal Tokio runtime.
     pub fn request(loader: Loader) -> Result<Self> {
+        crate::tokio::sync(Self::request_async(loader))
+    }
+
+    /// Async version of [`Self::request`].
+    pub async fn request_async(loader: Loader) -> Result<Self> {
+        Self::request_impl(loader, &download::Config::default(), None).await
+    }
+
+    /// Request the repository for a given loader, with the given download 
+    /// configuration, for example to use mirrors.
+    /// 
+    /// See [`download::Single::download`] about the internal Tokio runtime.
+    pub fn request_with_config(loader: Loader, config: &download::Config) -> Result<Self> {
+        crate::tokio::sync(Self::request_with_config_async(loader, config))
+    }
+
+    /// Async version of [`Self::request_with_config`].
+    pub async fn request_with_config_async(loader: Loader, config: &download::Config) -> Result<Self> {
+        Self::request_impl(loader, config, None).await
+    }
+
+    /// Internal request function with the download configuration of the installer.
+    pub(crate) async fn request_impl(
+        loader: Loader, 
+        config: &download::Config,
+        cancel_token: Option<&download::CancelToken>,
+    ) -> Result<Self> {
         match loader {
-            Loader::Forge => Self::request_forge(),
-            Loader::NeoForge => Self::request_neoforge(),
+            Loader::Forge => Self::request_forge(config, cancel_token).await,
+            Loader::NeoForge => Self::request_neoforge(config, cancel_token).await,
         }
     }
 
     /// Request the online Forge repository.
-    fn request_forge() -> Result<Self> {
+    async fn request_forge(config: &download::Config, cancel_token: Option<&download::CancelToken>) -> Result<Self> {
 
         // This entry doesn't really support caching, but we use this so we can access
         // the resource while being offline.
         let mut main_entry = download::single_cached("https://maven.minecraftforge.net/net/minecraftforge/forge/maven-metadata.xml")
             .set_keep_open()
-            .download(())?;
+            .set_config(config.clone())
+            .set_cancel_token(cancel_token.cloned())
+            .download_async(()).await?;
 
         let main_xml = main_entry.read_handle_to_string().unwrap()
             .map_err(|e| base::Error::new_io_file(e, main_entry.file()))?;
@@ -527,14 +661,16 @@ impl Repo {
     }
 
     /// Request the online NeoForge repository.
-    fn request_neoforge() -> Result<Self> {
+    async fn request_neoforge(config: &download::Config, cancel_token: Option<&download::CancelToken>) -> Result<Self> {
 
         // See comment above about caching.
         let mut batch = download::Batch::new();
+        batch.set_config(config.clone());
+        batch.set_cancel_token(cancel_token.cloned());
         batch.push_cached("https://maven.neoforged.net/releases/net/neoforged/neoforge/maven-metadata.xml").set_keep_open();
         batch.push_cached("https://maven.neoforged.net/releases/net/neoforged/forge/maven-metadata.xml").set_keep_open();
         
-        let mut result = batch.download(())
+        let mut result = batch.download_async(()).await
             .map_err(|e| base::Error::new_reqwest(e, "request neoforge repo"))?
             .into_result()?;
         
@@ -865,8 +1001,28 @@ impl InstallConfigCheckLibraries {
 }
 
 /// Try installing the mod loader.
-fn try_install(
-    handler: &mut dyn Handler,
+/// Return the reason for (re)installing the loader if the given error, returned by the
+/// mojang installer for the root version, is caused by the loader not being installed.
+fn error_install_reason(error: &moj::Error, root_version: &str, side: serde::InstallSide) -> Option<InstallReason> {
+    match error {
+        moj::Error::Base(base::Error::VersionNotFound { version }) 
+        if version == root_version => {
+            Some(InstallReason::MissingVersionMetadata)
+        }
+        moj::Error::Base(base::Error::LibraryNotFound { name: gav }) 
+        if gav.group() == "net.minecraftforge" && gav.artifact() == "forge" => {
+            Some(InstallReason::MissingCoreLibrary)
+        }
+        moj::Error::Base(base::Error::LibraryNotFound { .. })
+        if side == serde::InstallSide::Server => {
+            Some(InstallReason::MissingServerLibrary)
+        }
+        _ => None
+    }
+}
+
+async fn try_install<H: Handler + ?Sized>(
+    handler: &mut H,
     mojang: &mut moj::Installer,
     config: &InstallConfig,
     root_version: &str,
@@ -875,6 +1031,8 @@ fn try_install(
 ) -> Result<()> {
 
     let tmp_dir = env::temp_dir().joined(root_version);
+    let download_config = mojang.base().download_config().clone();
+    let cancel_token = mojang.base().cancel_token().cloned();
     handler.on_event(Event::Installing { tmp_dir: &tmp_dir, reason });
 
     // The first thing we do is fetching the installer, so it ends early if there is 
@@ -893,7 +1051,9 @@ fn try_install(
     // Download and check result in case installer is just not found.
     let entry = download::single(installer_url, tmp_dir.join("installer.jar"))
         .set_keep_open()
-        .download((&mut *handler).into_download());
+        .set_config(download_config.clone())
+        .set_cancel_token(cancel_token.clone())
+        .download_impl(&mut (&mut *handler).into_download()).await;
 
     let mut entry = match entry {
         Ok(entry) => entry,
@@ -922,7 +1082,7 @@ fn try_install(
     // be already canonicalized.
     handler.on_event(Event::InstallingGame);
     mojang.set_version(config.game_version.clone());
-    let jvm_file = match mojang.install((&mut *handler).into_mojang()) {
+    let jvm_file = match mojang.install_impl(&mut (&mut *handler).into_mojang()).await {
         Err(e) => return Err(Error::Mojang(e)),
         Ok(game) => game.jvm_file,
     };
@@ -949,10 +1109,17 @@ fn try_install(
     };
 
     // The installer directly installs libraries to these directories.
-    // We canonicalize the libs path here, this avoids doing it after each join.
-    let libraries_dir = base::canonicalize_file(mojang.base().libraries_dir())?;
+    // We canonicalize the libs path here, this avoids doing it after each join, the
+    // directory may not exist yet when installing a server that has no library.
+    let libraries_dir = mojang.base().libraries_dir();
+    fs::create_dir_all(libraries_dir)
+        .map_err(|e| base::Error::new_io_file(e, libraries_dir))?;
+    let libraries_dir = base::canonicalize_file(libraries_dir)?;
     let game_version_dir = mojang.base().versions_dir().join(&config.game_version);
-    let game_client_file = game_version_dir.join_with_extension(&config.game_version, "jar");
+    let mut game_file = match side {
+        serde::InstallSide::Client => game_version_dir.join_with_extension(&config.game_version, "jar"),
+        serde::InstallSide::Server => game_version_dir.join_with_extension(format!("{}-server", config.game_version), "jar"),
+    };
     let root_version_dir = mojang.base().versions_dir().join(&root_version);
     let metadata_file = root_version_dir.join_with_extension(&root_version, "json");
     let mut metadata;
@@ -1011,11 +1178,11 @@ fn try_install(
                 libraries.insert(&lib.name, lib_file.clone());
                 
                 if !lib_dl.download.url.is_empty() {
-                    let check_lib_sha1 = lib_dl.download.sha1.as_deref().filter(|_| mojang.base().strict_libraries_check());
-                    if !base::check_file(&lib_file, lib_dl.download.size, check_lib_sha1)? {
+                    let check_lib_digest = lib_dl.download.digest().filter(|_| mojang.base().strict_libraries_check());
+                    if !base::check_file(&lib_file, lib_dl.download.size, check_lib_digest.as_ref())? {
                         batch.push(lib_dl.download.url.to_string(), lib_file)
                             .set_expected_size(lib_dl.download.size)
-                            .set_expected_sha1(lib_dl.download.sha1.as_deref().copied());
+                            .set_expected_digest(lib_dl.download.digest());
                     }
                 } else {
                     extract_installer_maven_artifact(installer_file, &mut installer_zip, &lib.name, &lib_file)?;
@@ -1025,7 +1192,9 @@ fn try_install(
 
             // Download all libraries just before running post processors.
             if !batch.is_empty() {
-                batch.download((&mut *handler).into_download())
+                batch.set_config(download_config.clone());
+                batch.set_cancel_token(cancel_token.clone());
+                batch.download_impl(&mut (&mut *handler).into_download()).await
                     .map_err(|e| base::Error::new_reqwest(e, "download forge libraries"))?
                     .into_result()?;
             }
@@ -1061,17 +1230,52 @@ fn try_install(
 
             // Builtin entries.
             data.insert("SIDE".to_string(), InstallDataTypedEntry::Literal(side.as_str().to_string()));
-            data.insert("MINECRAFT_JAR".to_string(), InstallDataTypedEntry::File(game_client_file));
             data.insert("MINECRAFT_VERSION".to_string(), InstallDataTypedEntry::Literal(config.game_version.to_string()));
-            // Currently no support for ROOT because it's apparently used only for server...
-            // data.insert("ROOT".to_string(), InstallDataTypedEntry::File(mojang.standard().));
             data.insert("INSTALLER".to_string(), InstallDataTypedEntry::File(installer_file.to_path_buf()));
             data.insert("LIBRARY_DIR".to_string(), InstallDataTypedEntry::File(libraries_dir.to_path_buf()));
 
+            if side == serde::InstallSide::Server {
+
+                // The root is the directory where the server is run.
+                let server_dir = mojang.base().server_dir();
+                fs::create_dir_all(server_dir)
+                    .map_err(|e| base::Error::new_io_file(e, server_dir))?;
+                
+                let server_dir = base::canonicalize_file(server_dir)?;
+                data.insert("ROOT".to_string(), InstallDataTypedEntry::File(server_dir));
+
+                // Some installers expect the vanilla server JAR to be at a given path,
+                // so we copy it there, the copy is then used by processors.
+                if let Some(server_jar_path) = &profile.server_jar_path
+                && let Some(server_jar_file) = format_processor_arg(server_jar_path, &libraries_dir, &data) {
+                    
+                    let server_jar_file = PathBuf::from(server_jar_file);
+                    if let Some(parent_dir) = server_jar_file.parent() {
+                        fs::create_dir_all(parent_dir)
+                            .map_err(|e| base::Error::new_io_file(e, parent_dir))?;
+                    }
+
+                    fs::copy(&game_file, &server_jar_file)
+                        .map_err(|e| base::Error::new_io(e, format!("copy: {}, to: {}", 
+                            game_file.display(), 
+                            server_jar_file.display())))?;
+                    
+                    game_file = server_jar_file;
+
+                }
+
+            }
+
+            data.insert("MINECRAFT_JAR".to_string(), InstallDataTypedEntry::File(game_file));
+
             // Now we process each post-processor in order, each processor will refer to
             // one of the library installed earlier.
             for processor in &profile.processors {
 
+                if cancel_token.as_ref().is_some_and(download::CancelToken::is_cancelled) {
+                    return Err(base::Error::Cancelled {  }.into());
+                }
+
                 if let Some(processor_sides) = &processor.sides {
                     if !processor_sides.iter().copied().any(|processor_side| processor_side == side) {
                         continue
@@ -1084,7 +1288,8 @@ fn try_install(
                     });
                 };
 
-                let Some(main_class) = find_jar_main_class(&jar_file)? else {
+                let Some(main_class) = read_jar_manifest(jar_file)?
+                    .and_then(|mut manifest| manifest.remove("Main-Class")) else {
                     return Err(Error::InstallerProcessorMainClassNotFound {
                         name: processor.jar.clone(),
                     });
@@ -1129,9 +1334,13 @@ fn try_install(
                     }
                 }
 
-                let output = command.output()
+                let output = run_processor(command, cancel_token.as_ref()).await
                     .map_err(|e| base::Error::new_io(e, format!("spawn: {}", jvm_file.display())))?;
 
+                let Some(output) = output else {
+                    return Err(base::Error::Cancelled {  }.into());
+                };
+
                 if !output.status.success() {
                     return Err(Error::InstallerProcessorFailed {
                         name: processor.jar.clone(),
@@ -1146,7 +1355,7 @@ fn try_install(
                         let Some(sha1) = format_processor_arg(&sha1, &libraries_dir, &data) else { continue };
                         let Some(sha1) = crate::serde::parse_hex_bytes::<20>(&sha1) else { continue };
                         let file = Path::new(&file);
-                        if !base::check_file(file, None, Some(&sha1))? {
+                        if !base::check_file(file, None, Some(&download::Digest::Sha1(sha1)))? {
                             return Err(Error::InstallerProcessorCorrupted {
                                 name: processor.jar.clone(),
                                 file: file.to_path_buf().into_boxed_path(),
@@ -1158,6 +1367,27 @@ fn try_install(
                 
             }
 
+            if side == serde::InstallSide::Server {
+
+                // Libraries of the install profile are known to the server metadata,
+                // so their download information can be reused.
+                let profile_libraries = profile.libraries.iter()
+                    .map(|lib| base::serde::VersionLibrary {
+                        name: lib.name.clone(),
+                        downloads: base::serde::VersionLibraryDownloads {
+                            artifact: Some(lib.downloads.artifact.clone()),
+                            classifiers: HashMap::new(),
+                        },
+                        natives: None,
+                        rules: None,
+                        url: None,
+                    });
+
+                make_server_metadata(installer_file, &mut installer_zip, &libraries_dir, 
+                    &mut metadata, profile.path.as_ref(), profile_libraries)?;
+
+            }
+
         }
         InstallProfileKind::Legacy(profile) => {
             
@@ -1183,6 +1413,12 @@ fn try_install(
             let jar_entry = &profile.install.file_path[..];
             extract_installer_file(installer_file, &mut installer_zip, &jar_entry, &jar_file)?;
 
+            // The universal JAR is also the server launcher.
+            if side == serde::InstallSide::Server {
+                make_server_metadata(installer_file, &mut installer_zip, &libraries_dir, 
+                    &mut metadata, Some(&profile.install.path), std::iter::empty())?;
+            }
+
         }
     }
 
@@ -1212,6 +1448,59 @@ enum InstallDataTypedEntry {
     File(PathBuf),
 }
 
+/// Run the given processor command and wait for its output, the child process is 
+/// killed if the given token is cancelled, in such case none is returned.
+async fn run_processor(
+    mut command: Command, 
+    cancel_token: Option<&download::CancelToken>,
+) -> io::Result<Option<Output>> {
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // The outputs are read on the blocking threads, until the child process exits,
+    // we can't wait for both outputs and the exit status without blocking.
+    let read_output = |mut reader: Option<Box<dyn Read + Send>>| {
+        tokio::task::spawn_blocking(move || {
+            let mut buf = Vec::new();
+            if let Some(reader) = &mut reader {
+                reader.read_to_end(&mut buf)?;
+            }
+            io::Result::Ok(buf)
+        })
+    };
+
+    let stdout = read_output(child.stdout.take().map(|r| Box::new(r) as _));
+    let stderr = read_output(child.stderr.take().map(|r| Box::new(r) as _));
+
+    // The child process is polled because we can't wait for it asynchronously.
+    let status = loop {
+        
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        let sleep = tokio::time::sleep(Duration::from_millis(50));
+        if download::cancellable(cancel_token, sleep).await.is_none() {
+            // Ignore errors because the child may have exited just before.
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+
+    };
+
+    Ok(Some(Output {
+        status,
+        stdout: stdout.await.expect("processor output task should not be cancelled nor panicking")?,
+        stderr: stderr.await.expect("processor output task should not be cancelled nor panicking")?,
+    }))
+
+}
+
 /// Format a processor argument, NOTE THAT it is directly implemented, especially from
 /// `net.minecraftforge.installer.json.Util.replaceToken` class inside the installer.
 fn format_processor_arg(
@@ -1330,8 +1619,8 @@ fn extract_installer_file<R: Read + Seek>(
 
 }
 
-/// From a JAR file path, open it and try to find the main class path from the manifest.
-fn find_jar_main_class(jar_file: &Path) -> Result<Option<String>> {
+/// From a JAR file path, open it and try to read the main attributes of its manifest.
+fn read_jar_manifest(jar_file: &Path) -> Result<Option<HashMap<String, String>>> {
 
     let jar_reader = File::open(jar_file)
         .map_err(|e| base::Error::new_io_file(e, jar_file))
@@ -1340,31 +1629,261 @@ fn find_jar_main_class(jar_file: &Path) -> Result<Option<String>> {
     let mut jar_zip = ZipArchive::new(jar_reader)
         .map_err(|e| base::Error::new_zip_file(e, jar_file))?;
 
-    let Ok(mut manifest_reader) = jar_zip.by_name("META-INF/MANIFEST.MF")
-        .map(BufReader::new) else {
-            // The manifest was not found, is should NEVER happen, we ignore this.
-            return Ok(None);
-        };
+    let Ok(mut manifest_reader) = jar_zip.by_name("META-INF/MANIFEST.MF") else {
+        // The manifest was not found, is should NEVER happen, we ignore this.
+        return Ok(None);
+    };
+
+    let mut manifest = String::new();
+    if manifest_reader.read_to_string(&mut manifest).is_err() {
+        return Ok(None);
+    }
+
+    Ok(Some(parse_jar_manifest(&manifest)))
     
-    const MAIN_CLASS_KEY: &str = "Main-Class: ";
-
-    let mut line = String::new();
-    while manifest_reader.read_line(&mut line).unwrap_or(0) != 0 {
-        if line.starts_with(MAIN_CLASS_KEY) {
-            if let Some(last_non_whitespace) = line.rfind(|c: char| !c.is_whitespace()) {
-                line.truncate(last_non_whitespace + 1);
-                line.drain(0..MAIN_CLASS_KEY.len());
-                return Ok(Some(line))
-            } else {
-                // The main class is empty?
-                return Ok(None);
+}
+
+/// Parse the main section of a JAR manifest, values may be continued on the next lines
+/// when these lines start with a single space.
+fn parse_jar_manifest(manifest: &str) -> HashMap<String, String> {
+
+    let mut attrs = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+
+    for line in manifest.lines() {
+        
+        if let Some(continuation) = line.strip_prefix(' ') {
+            if let Some((_, value)) = &mut current {
+                value.push_str(continuation);
             }
+            continue;
+        }
+
+        if let Some((key, value)) = current.take() {
+            attrs.insert(key, value);
         }
-        line.clear();
+
+        // An empty line ends the main section.
+        if line.is_empty() {
+            break;
+        }
+
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            current = Some((key.to_string(), value.to_string()));
+        }
+
     }
 
-    Ok(None)
+    if let Some((key, value)) = current {
+        attrs.insert(key, value);
+    }
+
+    attrs
+
+}
+
+/// Rewrite the given version metadata, that has been installed for the client, into 
+/// the metadata for launching the dedicated server. Modern installers have an 
+/// arguments file containing the arguments for launching the server, if not present,
+/// the launcher JAR is used with its main class and class path.
+fn make_server_metadata<R: Read + Seek>(
+    installer_file: &Path,
+    installer_zip: &mut ZipArchive<R>,
+    libraries_dir: &Path,
+    metadata: &mut base::serde::VersionMetadata,
+    launcher_name: Option<&Gav>,
+    extra_libraries: impl Iterator<Item = base::serde::VersionLibrary>,
+) -> Result<()> {
+
+    #[cfg(windows)]
+    const ARGS_ENTRY: &str = "data/win_args.txt";
+    #[cfg(not(windows))]
+    const ARGS_ENTRY: &str = "data/unix_args.txt";
+
+    #[cfg(windows)]
+    const CLASS_PATH_SEPARATOR: char = ';';
+    #[cfg(not(windows))]
+    const CLASS_PATH_SEPARATOR: char = ':';
+
+    // Libraries from the client metadata are only kept to reuse their download info.
+    let mut known_libraries = metadata.libraries.drain(..)
+        .chain(extra_libraries)
+        .map(|lib| (lib.name.clone(), lib))
+        .collect::<HashMap<_, _>>();
+
+    let mut lib_files = Vec::new();
+    let main_class;
     
+    if let Ok(mut reader) = installer_zip.by_name(ARGS_ENTRY) {
+
+        let mut args = String::new();
+        reader.read_to_string(&mut args)
+            .map_err(|e| base::Error::new_io(e, format!("extract: {}, from: {}", 
+                ARGS_ENTRY, 
+                installer_file.display())))?;
+
+        let Some((raw_jvm_args, args_main_class, raw_game_args)) = split_server_args(&args) else {
+            return Err(Error::InstallerServerLauncherNotFound {  });
+        };
+
+        let mut rewrite_args = |args: &[&str]| args.iter()
+            .map(|arg| base::serde::VersionArgument::Raw(rewrite_server_arg(arg, CLASS_PATH_SEPARATOR, &mut lib_files)))
+            .collect::<Vec<_>>();
+
+        let jvm_args = rewrite_args(&raw_jvm_args);
+        let game_args = rewrite_args(&raw_game_args);
+
+        main_class = args_main_class.to_string();
+        metadata.arguments = Some(base::serde::VersionArguments {
+            game: game_args,
+            jvm: jvm_args,
+        });
+
+    } else {
+
+        let Some(launcher_name) = launcher_name else {
+            return Err(Error::InstallerServerLauncherNotFound {  });
+        };
+
+        let Some(mut manifest) = read_jar_manifest(&libraries_dir.join(launcher_name.file()))? else {
+            return Err(Error::InstallerServerLauncherNotFound {  });
+        };
+
+        let Some(manifest_main_class) = manifest.remove("Main-Class") else {
+            return Err(Error::InstallerServerLauncherNotFound {  });
+        };
+
+        lib_files.push(launcher_name.file().to_string_lossy().replace('\\', "/"));
+
+        // The class path is relative to the server directory, only libraries are kept,
+        // the vanilla server JAR is added to the class path by the base installer.
+        if let Some(class_path) = manifest.get("Class-Path") {
+            for file in class_path.split_whitespace() {
+                if let Some(file) = file.strip_prefix("libraries/") {
+                    lib_files.push(file.to_string());
+                }
+            }
+        }
+
+        // No arguments, so the class path is given by the base installer.
+        main_class = manifest_main_class;
+        metadata.arguments = None;
+
+    }
+
+    for file in &lib_files {
+        let Some(gav) = gav_from_file(file) else { continue };
+        let lib = match known_libraries.remove(&gav) {
+            Some(lib) => lib,
+            // Unknown library, likely generated by processors, must be present.
+            None => base::serde::VersionLibrary {
+                name: gav,
+                downloads: Default::default(),
+                natives: None,
+                rules: None,
+                url: None,
+            },
+        };
+        metadata.libraries.push(lib);
+    }
+
+    metadata.main_class = Some(main_class);
+    metadata.legacy_arguments = None;
+
+    Ok(())
+
+}
+
+/// Split the arguments of the installer's arguments file into the JVM arguments, the
+/// main class and the game arguments. The main class is the first argument that is 
+/// neither an option nor the value of an option, returning none if there is none.
+fn split_server_args(args: &str) -> Option<(Vec<&str>, &str, Vec<&str>)> {
+
+    /// JVM options that take their value as the next argument.
+    const VALUE_OPTIONS: [&str; 9] = [
+        "-p", "--module-path", 
+        "-cp", "-classpath", "--class-path", 
+        "--add-modules", "--add-opens", "--add-exports", "--add-reads",
+    ];
+
+    let mut jvm_args = Vec::new();
+    let mut it = args.split_whitespace();
+
+    let main_class = loop {
+        let arg = it.next()?;
+        jvm_args.push(arg);
+        if VALUE_OPTIONS.contains(&arg) {
+            jvm_args.push(it.next()?);
+        } else if !arg.starts_with('-') {
+            jvm_args.pop();
+            break arg;
+        }
+    };
+
+    Some((jvm_args, main_class, it.collect()))
+
+}
+
+/// Rewrite a server argument from the installer's arguments file, all paths relative 
+/// to the libraries directory are replaced by the library directory placeholder, and 
+/// are pushed to the given vector of library files.
+fn rewrite_server_arg(arg: &str, separator: char, lib_files: &mut Vec<String>) -> String {
+
+    // Arguments such as '-Dkey=value' can have paths in their value.
+    let (key, value) = match arg.split_once('=') {
+        Some((key, value)) => (&arg[..key.len() + 1], value),
+        None => ("", arg),
+    };
+
+    let mut rewritten = false;
+    let parts = value.split(separator)
+        .map(|part| {
+            if part == "libraries" {
+                rewritten = true;
+                "${library_directory}".to_string()
+            } else if let Some(file) = part.strip_prefix("libraries/") {
+                rewritten = true;
+                lib_files.push(file.to_string());
+                format!("${{library_directory}}/{file}")
+            } else {
+                part.to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if !rewritten {
+        return arg.to_string();
+    }
+
+    format!("{key}{}", parts.join("${classpath_separator}"))
+
+}
+
+/// Find the library name from its file path, relative to the libraries directory.
+fn gav_from_file(file: &str) -> Option<Gav> {
+
+    let mut parts = file.rsplit('/');
+    let file_name = parts.next()?;
+    let version = parts.next()?;
+    let artifact = parts.next()?;
+    let group = parts.rev().collect::<Vec<_>>().join(".");
+    if group.is_empty() {
+        return None;
+    }
+
+    let rest = file_name.strip_prefix(artifact)?
+        .strip_prefix('-')?
+        .strip_prefix(version)?;
+
+    let (classifier, extension) = rest.rsplit_once('.')?;
+    let classifier = match classifier {
+        "" => None,
+        classifier => Some(classifier.strip_prefix('-')?),
+    };
+
+    Gav::new(&group, artifact, version, classifier, Some(extension))
+
 }
 
 /// Generic version parsing with dot separator and default value to zero.
@@ -1411,4 +1930,52 @@ mod test {
 
     }
 
+    #[test]
+    fn parse_manifest() {
+
+        let manifest = parse_jar_manifest("Manifest-Version: 1.0\r\nMain-Class: net.minecraftforge.fml.relauncher.ServerLaunchWrapper\r\nClass-Path: libraries/foo/bar/1.0/bar-1.0.jar libraries/foo/baz/2.0/b\r\n az-2.0.jar minecraft_server.1.12.2.jar\r\n\r\nName: net/minecraftforge/\r\nSpecification-Title: Forge\r\n");
+        assert_eq!(manifest.get("Main-Class").map(String::as_str), Some("net.minecraftforge.fml.relauncher.ServerLaunchWrapper"));
+        assert_eq!(manifest.get("Class-Path").map(String::as_str), Some("libraries/foo/bar/1.0/bar-1.0.jar libraries/foo/baz/2.0/baz-2.0.jar minecraft_server.1.12.2.jar"));
+        assert_eq!(manifest.get("Specification-Title"), None);
+
+    }
+
+    #[test]
+    fn server_args() {
+
+        let args = "-Djava.net.preferIPv6Addresses=system -p libraries/a/b/1/b-1.jar --add-modules ALL-MODULE-PATH \
+            --add-opens java.base/java.util.jar=cpw.mods.securejarhandler \
+            --add-exports=java.base/sun.security.util=ALL-UNNAMED \
+            net.neoforged.serverlauncher.Main --launchTarget forgeserver --fml.neoForgeVersion 21.0.1\n";
+        let (jvm_args, main_class, game_args) = split_server_args(args).unwrap();
+        assert_eq!(jvm_args, [
+            "-Djava.net.preferIPv6Addresses=system", 
+            "-p", "libraries/a/b/1/b-1.jar", 
+            "--add-modules", "ALL-MODULE-PATH",
+            "--add-opens", "java.base/java.util.jar=cpw.mods.securejarhandler",
+            "--add-exports=java.base/sun.security.util=ALL-UNNAMED",
+        ]);
+        assert_eq!(main_class, "net.neoforged.serverlauncher.Main");
+        assert_eq!(game_args, ["--launchTarget", "forgeserver", "--fml.neoForgeVersion", "21.0.1"]);
+
+        assert_eq!(split_server_args("-cp a.jar:b.jar Main"), Some((vec!["-cp", "a.jar:b.jar"], "Main", vec![])));
+        assert_eq!(split_server_args("-Dfoo=bar -cp a.jar"), None);
+        assert_eq!(split_server_args("-Dfoo=bar -cp"), None);
+
+        let mut lib_files = Vec::new();
+        assert_eq!(rewrite_server_arg("--add-modules", ':', &mut lib_files), "--add-modules");
+        assert_eq!(rewrite_server_arg("-DlibraryDirectory=libraries", ':', &mut lib_files), "-DlibraryDirectory=${library_directory}");
+        assert_eq!(rewrite_server_arg("-DignoreList=a,b:c", ':', &mut lib_files), "-DignoreList=a,b:c");
+        assert_eq!(rewrite_server_arg("libraries/a/b/1/b-1.jar:libraries/c/d/2/d-2-extra.jar", ':', &mut lib_files), 
+            "${library_directory}/a/b/1/b-1.jar${classpath_separator}${library_directory}/c/d/2/d-2-extra.jar");
+        assert_eq!(lib_files, ["a/b/1/b-1.jar", "c/d/2/d-2-extra.jar"]);
+
+        assert_eq!(gav_from_file("a/b/1/b-1.jar"), Gav::new("a", "b", "1", None, None));
+        assert_eq!(gav_from_file("a/b/c/d/2/d-2-extra.jar"), Gav::new("a.b.c", "d", "2", Some("extra"), None));
+        assert_eq!(gav_from_file("a/b/1/b-1.zip"), Gav::new("a", "b", "1", None, Some("zip")));
+        assert_eq!(gav_from_file("a/b/1/c-1.jar"), None);
+        assert_eq!(gav_from_file("b/1/b-1.jar"), None);
+
+    }
+
 }
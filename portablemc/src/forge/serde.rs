@@ -30,6 +30,10 @@ pub struct ModernInstallProfile {
     /// Constant data used for replacement in post-processor arguments.
     #[serde(deserialize_with = "crate::serde::deserialize_or_empty_seq")]
     pub data: HashMap<String, InstallDataEntry>,
+    /// For some loader versions, the path where the vanilla server JAR should be placed
+    /// when installing the server, it can contain data tokens.
+    #[serde(default, rename = "serverJarPath")]
+    pub server_jar_path: Option<String>,
 }
 
 #[derive(serde::Deserialize, Debug, Clone)]
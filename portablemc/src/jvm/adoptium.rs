@@ -0,0 +1,209 @@
+//! JVM provider for Eclipse Temurin builds, through the Adoptium API.
+
+use std::future::Future;
+use std::io::BufReader;
+use std::pin::Pin;
+use std::env;
+
+use crate::base::{Error, Result};
+use crate::download;
+
+use super::{Archive, ArchiveFormat, Provider};
+
+
+/// The default base URL of the Adoptium API.
+const DEFAULT_BASE_URL: &str = "https://api.adoptium.net/v3";
+
+/// A JVM provider that installs Eclipse Temurin builds from the [Adoptium API]. A JRE
+/// is preferred, and a JDK is used if no JRE is available for the host platform, this
+/// provider supports musl-based Linux distributions and most architectures, including
+/// those without Mojang-provided JVMs.
+///
+/// [Adoptium API]: https://api.adoptium.net/
+#[derive(Debug, Clone)]
+pub struct AdoptiumProvider {
+    /// Base URL of the API, not ending with a '/'. This API must support the
+    /// `/assets/latest/<major_version>/hotspot` endpoint of the official API.
+    base_url: String,
+}
+
+impl Default for AdoptiumProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AdoptiumProvider {
+
+    /// Create a new provider using the official Adoptium API.
+    pub fn new() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+
+    /// The base URL of the API, defaults to the official Adoptium API.
+    #[inline]
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Change the base URL of the API, this can be used to point to a compatible API,
+    /// any trailing '/' is removed.
+    #[inline]
+    pub fn set_base_url(&mut self, base_url: impl Into<String>) -> &mut Self {
+        let mut base_url = base_url.into();
+        while base_url.ends_with('/') {
+            base_url.pop();
+        }
+        self.base_url = base_url;
+        self
+    }
+
+    async fn find_archive_impl(&self, 
+        major_version: u32, 
+        config: &download::Config,
+        cancel_token: Option<&download::CancelToken>,
+    ) -> Result<Option<Archive>> {
+
+        let (Some(os), Some(arch)) = (adoptium_os(), adoptium_arch()) else {
+            return Ok(None);
+        };
+
+        for image_type in ["jre", "jdk"] {
+
+            let url = format!("{}/assets/latest/{major_version}/hotspot?architecture={arch}&image_type={image_type}&os={os}&vendor=eclipse",
+                self.base_url);
+
+            let Some(assets) = request_assets(&url, config, cancel_token).await? else {
+                continue;
+            };
+
+            // Take the first asset with a supported archive format.
+            for asset in assets {
+
+                let package = asset.binary.package;
+                let Some(format) = ArchiveFormat::from_file_name(&package.name) else {
+                    continue;
+                };
+
+                return Ok(Some(Archive {
+                    name: format!("{}-{image_type}", asset.release_name),
+                    version: asset.version.openjdk_version,
+                    url: package.link,
+                    size: Some(package.size),
+                    digest: package.checksum.map(|checksum| download::Digest::Sha256(checksum.0)),
+                    format,
+                }));
+
+            }
+
+        }
+
+        Ok(None)
+
+    }
+
+}
+
+impl Provider for AdoptiumProvider {
+
+    fn name(&self) -> &str {
+        "adoptium"
+    }
+
+    fn find_archive<'a>(&'a self,
+        major_version: u32,
+        config: &'a download::Config,
+        cancel_token: Option<&'a download::CancelToken>,
+    ) -> Pin<Box<dyn Future<Output = Result<Option<Archive>>> + Send + 'a>> {
+        Box::pin(self.find_archive_impl(major_version, config, cancel_token))
+    }
+
+}
+
+/// Request the assets at the given URL, the response is cached so that it can also be
+/// used offline, returning none if the API has no assets (status 404).
+async fn request_assets(url: &str, 
+    config: &download::Config, 
+    cancel_token: Option<&download::CancelToken>,
+) -> Result<Option<Vec<serde::Asset>>> {
+
+    let res = download::single_cached(url)
+        .set_keep_open()
+        .set_config(config.clone())
+        .set_cancel_token(cancel_token.cloned())
+        .download_async(()).await;
+
+    let mut entry = match res {
+        Ok(entry) => entry,
+        Err(e) if matches!(e.kind(), download::EntryErrorKind::InvalidStatus(404)) => return Ok(None),
+        Err(e) => return Err(Error::from(e)),
+    };
+
+    let reader = BufReader::new(entry.take_handle().unwrap());
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    serde_path_to_error::deserialize::<_, Vec<serde::Asset>>(&mut deserializer)
+        .map_err(|e| Error::new_json_file(e, entry.file()))
+        .map(Some)
+
+}
+
+/// Return the Adoptium name of the host OS, if supported.
+fn adoptium_os() -> Option<&'static str> {
+    Some(match env::consts::OS {
+        "linux" if cfg!(target_env = "musl") => "alpine-linux",
+        "linux" => "linux",
+        "macos" => "mac",
+        "windows" => "windows",
+        "aix" => "aix",
+        "solaris" => "solaris",
+        _ => return None,
+    })
+}
+
+/// Return the Adoptium name of the host architecture, if supported.
+fn adoptium_arch() -> Option<&'static str> {
+    Some(match env::consts::ARCH {
+        "x86_64" => "x64",
+        "x86" => "x86",
+        "aarch64" => "aarch64",
+        "arm" => "arm",
+        "powerpc64" if cfg!(target_endian = "little") => "ppc64le",
+        "powerpc64" => "ppc64",
+        "riscv64" => "riscv64",
+        "s390x" => "s390x",
+        _ => return None,
+    })
+}
+
+mod serde {
+
+    use crate::serde::HexString;
+
+    #[derive(Debug, serde::Deserialize)]
+    pub struct Asset {
+        pub binary: Binary,
+        pub release_name: String,
+        pub version: Version,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    pub struct Binary {
+        pub package: Package,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    pub struct Package {
+        pub link: String,
+        pub name: String,
+        pub size: u64,
+        pub checksum: Option<HexString<32>>,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    pub struct Version {
+        pub openjdk_version: String,
+    }
+
+}
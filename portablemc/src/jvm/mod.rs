@@ -0,0 +1,944 @@
+//! Java Virtual Machine discovery and inspection, used to find the JVMs installed on the
+//! system and to probe their version, vendor and architecture. This module also defines
+//! the [`Provider`] trait for sources of JVMs that can be installed, such as the 
+//! [`AdoptiumProvider`].
+
+mod adoptium;
+
+pub use adoptium::AdoptiumProvider;
+
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::future::Future;
+use std::{env, fmt, thread};
+use std::pin::Pin;
+
+use indexmap::IndexMap;
+
+use crate::path::{PathExt, PathBufExt};
+use crate::download;
+use crate::base;
+
+
+/// Timeout for probing JVMs, after which any JVM still running is killed.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Interval between two checks of the probed JVMs processes.
+const PROBE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Information about a JVM, as returned by probing its executable, see [`probe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JvmInfo {
+    /// The 'java' or 'javaw' executable file that has been probed.
+    pub file: PathBuf,
+    /// The full version string, such as `17.0.2` or `1.8.0_111`.
+    pub version: String,
+    /// The major version parsed from the full version, such as `17` or `8`.
+    pub major_version: u32,
+    /// The vendor of this JVM, such as `Eclipse Adoptium`, if known.
+    pub vendor: Option<String>,
+    /// The architecture of this JVM, if known, normalized to the naming used by Rust
+    /// (see [`std::env::consts::ARCH`]), such as `x86_64` or `aarch64`.
+    pub arch: Option<String>,
+}
+
+impl JvmInfo {
+
+    /// Compute the compatibility score of this JVM with the given required major
+    /// version, see [`compatibility`].
+    #[inline]
+    pub fn compatibility(&self, required_major_version: u32) -> Option<u32> {
+        compatibility(required_major_version, self.major_version)
+    }
+
+    /// Return true if the architecture of this JVM is the same as the host one, or if
+    /// it is unknown. Note that a JVM with a different architecture might be able to
+    /// run through emulation, but it's not compatible with the game's natives.
+    #[inline]
+    pub fn is_host_arch(&self) -> bool {
+        self.arch.as_deref().is_none_or(|arch| arch == env::consts::ARCH)
+    }
+
+}
+
+/// Return the name of the JVM executable file for the current OS, this is `javaw.exe`
+/// on Windows in order to not open a console window, and `java` otherwise.
+#[inline]
+pub fn exec_name() -> &'static str {
+    if cfg!(windows) { "javaw.exe" } else { "java" }
+}
+
+/// Find every JVM executable installed on the system in standard locations, depending
+/// on the OS, and in the given additional search directories. Each search directory
+/// can either be a JVM home directory, or a directory containing JVM home directories.
+/// 
+/// The returned files are unique and exist, but they are not probed so they might not
+/// be valid JVMs, see [`probe`] for that. Files that are resolving to the same file,
+/// through symlinks, are only returned once.
+pub fn find_candidates(search_dirs: &[PathBuf]) -> Vec<PathBuf> {
+
+    let mut candidates = Candidates::default();
+
+    // Check every JVM available in PATH.
+    if let Some(path) = env::var_os("PATH") {
+        for path in env::split_paths(&path) {
+            candidates.add_file(path.joined(candidates.exec_name));
+        }
+    }
+
+    if let Some(java_home) = env::var_os("JAVA_HOME") {
+        candidates.add_home(Path::new(&java_home));
+    }
+
+    for dir in search_dirs {
+        candidates.add_home(dir);
+        candidates.add_homes_in(dir);
+    }
+
+    // Well-known directories where JVM version managers and tools install JVMs.
+    #[cfg(unix)]
+    if let Some(home_dir) = dirs::home_dir() {
+
+        let env_dir = |name: &str, default: &str| env::var_os(name)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home_dir.join(default));
+
+        // SDKMAN, its 'current' version is a symlink to another one.
+        candidates.add_homes_in(&env_dir("SDKMAN_DIR", ".sdkman").joined("candidates/java"));
+        // asdf, using its Java plugin.
+        candidates.add_homes_in(&env_dir("ASDF_DATA_DIR", ".asdf").joined("installs/java"));
+        // jenv, with its versions being symlinks to other JVMs.
+        candidates.add_homes_in(&home_dir.join(".jenv/versions"));
+        // IntelliJ IDEA's downloaded JDKs.
+        candidates.add_homes_in(&home_dir.join(".jdks"));
+        // Gradle toolchains that have been auto-provisioned.
+        candidates.add_homes_in(&env_dir("GRADLE_USER_HOME", ".gradle").joined("jdks"));
+
+        #[cfg(target_os = "linux")]
+        candidates.add_flatpak_dir(&home_dir.join(".local/share/flatpak"));
+
+    }
+
+    #[cfg(target_os = "linux")] {
+
+        // On Linux distributions the different JVMs are in '/usr/lib/jvm/', manually
+        // installed JVMs are also commonly extracted in '/opt/'.
+        candidates.add_homes_in(Path::new("/usr/lib/jvm"));
+        candidates.add_homes_in(Path::new("/opt"));
+
+        // OpenJDK Flatpak SDK extensions, installed system-wide, and from inside the
+        // sandbox they are mounted in '/usr/lib/sdk/openjdk<major>/jvm/'.
+        candidates.add_flatpak_dir(Path::new("/var/lib/flatpak"));
+        for dir in read_dir_paths(Path::new("/usr/lib/sdk")) {
+            if file_name_starts_with(&dir, "openjdk") {
+                candidates.add_homes_in(&dir.joined("jvm"));
+            }
+        }
+
+        // Nix profiles of the system and the user, JVM packages have their home in
+        // 'lib/openjdk/'. The Nix store itself is not scanned because it contains all
+        // packages ever built on the system, JVMs only present there are not installed.
+        let mut nix_profiles = vec![
+            PathBuf::from("/run/current-system/sw"),
+            PathBuf::from("/nix/var/nix/profiles/default"),
+        ];
+        if let Some(user) = env::var_os("USER") {
+            nix_profiles.push(Path::new("/etc/profiles/per-user").join(user));
+        }
+        if let Some(home_dir) = dirs::home_dir() {
+            nix_profiles.push(home_dir.join(".nix-profile"));
+            nix_profiles.push(home_dir.join(".local/state/nix/profile"));
+        }
+        for profile in nix_profiles {
+            candidates.add_home(&profile.joined("lib/openjdk"));
+        }
+
+    }
+
+    // On windows we can search in registry.
+    #[cfg(windows)] {
+
+        const REG_PATHS: [&str; 4] = [
+            "SOFTWARE\\JavaSoft\\Java Development Kit",
+            "SOFTWARE\\JavaSoft\\Java Runtime Environment",
+            "SOFTWARE\\JavaSoft\\JDK",
+            "SOFTWARE\\JavaSoft\\JRE",
+        ];
+
+        // Here we silently ignore any error.
+        for path in REG_PATHS {
+            let Ok(key) = windows_registry::LOCAL_MACHINE.open(path) else { continue };
+            let Ok(keys) = key.keys() else { continue };
+            for sub_key in keys {
+                let Ok(sub_key) = key.open(&sub_key) else { continue };
+                let Ok(java_home) = sub_key.get_string("JavaHome") else { continue };
+                candidates.add_home(Path::new(&java_home));
+            }
+        }
+
+    }
+
+    candidates.files.into_values().collect()
+
+}
+
+/// Probe all the given JVM executables, returning their information in the same order,
+/// or none if the file is not a valid JVM, or if it didn't answer in time.
+///
+/// The `release` file of each JVM home directory is read first, this file is shipped
+/// with most JVMs and gives their version, vendor and architecture. If this file is
+/// missing or incomplete, the JVM is spawned with `-XshowSettings:properties -version`
+/// arguments, in parallel, and its information are read from the standard error output.
+pub fn probe<P: AsRef<Path>>(files: &[P]) -> Vec<Option<JvmInfo>> {
+    let mut infos = vec![None; files.len()];
+    probe_missing(files, &mut infos);
+    infos
+}
+
+/// Same as [`probe`], but the information of valid JVMs are cached in the given file,
+/// keyed by the canonical path of the executable and its modification time, so the
+/// same JVM found through different symlinks is cached once, and unchanged JVMs 
+/// are not probed again. Any error while reading or writing the cache file is silently
+/// ignored, because the cache is only an optimization.
+pub fn probe_cached<P: AsRef<Path>>(files: &[P], cache_file: &Path) -> Vec<Option<JvmInfo>> {
+
+    let mut cache = File::open(cache_file).ok()
+        .and_then(|reader| serde_json::from_reader::<_, serde::Cache>(BufReader::new(reader)).ok())
+        .map(|cache| cache.jvms)
+        .unwrap_or_default();
+
+    // Find the cache key and modification time of each file, the file is probed again
+    // if any of them is missing.
+    let stamps = files.iter()
+        .map(|file| {
+            let key = fs::canonicalize(file).ok()?.into_os_string().into_string().ok()?;
+            let mtime = fs::metadata(file).ok()
+                .and_then(|metadata| metadata.modified().ok())
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_nanos() as u64)?;
+            Some((key, mtime))
+        })
+        .collect::<Vec<_>>();
+
+    let mut infos = files.iter()
+        .zip(&stamps)
+        .map(|(file, stamp)| {
+            let (key, mtime) = stamp.as_ref()?;
+            let entry = cache.get(key).filter(|entry| entry.mtime == *mtime)?;
+            Some(JvmInfo {
+                file: file.as_ref().to_path_buf(),
+                major_version: parse_major_version(&entry.version)?,
+                version: entry.version.clone(),
+                vendor: entry.vendor.clone(),
+                arch: entry.arch.clone(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let cached = infos.iter().map(Option::is_some).collect::<Vec<_>>();
+    probe_missing(files, &mut infos);
+
+    let mut dirty = false;
+    for ((info, stamp), cached) in infos.iter().zip(&stamps).zip(cached) {
+        if let (Some(info), Some((key, mtime)), false) = (info, stamp, cached) {
+            cache.insert(key.to_string(), serde::CacheEntry {
+                mtime: *mtime,
+                version: info.version.clone(),
+                vendor: info.vendor.clone(),
+                arch: info.arch.clone(),
+            });
+            dirty = true;
+        }
+    }
+
+    if dirty {
+        let _ = save_cache(cache_file, &cache);
+    }
+
+    infos
+
+}
+
+/// Probe each JVM for which the information is missing, in-place.
+fn probe_missing<P: AsRef<Path>>(files: &[P], infos: &mut [Option<JvmInfo>]) {
+
+    for (file, info) in files.iter().zip(infos.iter_mut()) {
+        if info.is_none() {
+            *info = read_release(file.as_ref());
+        }
+    }
+
+    // Every child is associated to a thread that reads its standard error, this is
+    // needed because the output may be larger than the pipe's buffer, which would
+    // block the child.
+    let mut children = files.iter()
+        .zip(infos.iter())
+        .map(|(file, info)| {
+
+            if info.is_some() {
+                return None;
+            }
+
+            // These environment variables are picked up by the JVM which prints them
+            // and may fail if they are invalid, we don't want that.
+            let mut child = Command::new(file.as_ref())
+                .arg("-XshowSettings:properties")
+                .arg("-version")
+                .env_remove("JAVA_TOOL_OPTIONS")
+                .env_remove("JDK_JAVA_OPTIONS")
+                .env_remove("_JAVA_OPTIONS")
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .spawn()
+                .ok()?;
+
+            let mut stderr = child.stderr.take()?;
+            let reader = thread::spawn(move || {
+                let mut output = Vec::new();
+                stderr.read_to_end(&mut output).ok().map(|_| output)
+            });
+
+            Some((child, reader))
+
+        })
+        .collect::<Vec<_>>();
+
+    let start = Instant::now();
+
+    loop {
+
+        let mut remaining = 0usize;
+
+        for (index, child_opt) in children.iter_mut().enumerate() {
+
+            let Some((child, _)) = child_opt else { continue };
+            let Ok(status) = child.try_wait() else {
+                // If an error happens we just forget the child: don't check it again.
+                let _ = child.kill();
+                *child_opt = None;
+                continue;
+            };
+
+            // If child has terminated, we take child to not check it again.
+            let Some(status) = status else {
+                remaining += 1;
+                continue
+            };
+
+            let (_, reader) = child_opt.take().unwrap();
+
+            // Not a success, just forget this child.
+            if !status.success() {
+                continue;
+            }
+
+            // The child has terminated so its stderr pipe has been closed.
+            let Ok(Some(output)) = reader.join() else { continue };
+            let Ok(output) = String::from_utf8(output) else {
+                continue; // Ignore if stderr is not UTF-8.
+            };
+
+            infos[index] = parse_output(files[index].as_ref(), &output);
+
+        }
+
+        if remaining == 0 {
+            break;
+        } else if start.elapsed() >= PROBE_TIMEOUT {
+            // Kill any child that is still running, their reader thread will terminate
+            // once the pipe is closed.
+            for (child, _) in children.iter_mut().flatten() {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            break;
+        }
+
+        thread::sleep(PROBE_INTERVAL);
+
+    }
+
+}
+
+/// Save the probe cache to the given file, atomically.
+fn save_cache(cache_file: &Path, cache: &HashMap<String, serde::CacheEntry>) -> io::Result<()> {
+
+    if let Some(parent) = cache_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let tmp_file = cache_file.temp_sibling();
+    let mut writer = BufWriter::new(File::create(&tmp_file)?);
+    serde_json::to_writer(&mut writer, &serde::CacheRef { jvms: cache })?;
+    writer.flush()?;
+    drop(writer);
+
+    fs::rename(&tmp_file, cache_file)
+
+}
+
+/// Find every JVM installed on the system and probe them, only returning the valid ones,
+/// this is a shortcut for [`find_candidates`] and then [`probe`].
+pub fn find_all(search_dirs: &[PathBuf]) -> Vec<JvmInfo> {
+    probe(&find_candidates(search_dirs)).into_iter().flatten().collect()
+}
+
+/// Internal set of candidate JVM executables, deduplicated by their canonical path.
+struct Candidates {
+    exec_name: &'static str,
+    /// Mapping of canonical files to their file, as found.
+    files: IndexMap<PathBuf, PathBuf>,
+}
+
+impl Default for Candidates {
+    fn default() -> Self {
+        Self {
+            exec_name: exec_name(),
+            files: IndexMap::new(),
+        }
+    }
+}
+
+impl Candidates {
+
+    /// Add the given executable file, if it exists.
+    fn add_file(&mut self, file: PathBuf) {
+        if file.is_file() {
+            let canon_file = dunce::canonicalize(&file).unwrap_or_else(|_| file.clone());
+            self.files.entry(canon_file).or_insert(file);
+        }
+    }
+
+    /// Add the executable of the given JVM home directory, if it exists.
+    fn add_home(&mut self, home_dir: &Path) {
+        self.add_file(home_dir.join("bin").joined(self.exec_name));
+        // On macOS the JVM bundle structure is a bit different.
+        if cfg!(target_os = "macos") {
+            self.add_file(home_dir.join("Contents/Home/bin").joined(self.exec_name));
+        }
+    }
+
+    /// Add the executables of every JVM home directory in the given directory.
+    fn add_homes_in(&mut self, dir: &Path) {
+        for home_dir in read_dir_paths(dir) {
+            self.add_home(&home_dir);
+        }
+    }
+
+    /// Add the executables of every OpenJDK SDK extensions in the given Flatpak 
+    /// installation directory, such extensions are found at the following path:
+    /// `runtime/org.freedesktop.Sdk.Extension.openjdk<major>/<arch>/<branch>/active/files/jvm/`.
+    #[cfg(target_os = "linux")]
+    fn add_flatpak_dir(&mut self, dir: &Path) {
+        for ext_dir in read_dir_paths(&dir.join("runtime")) {
+            if !file_name_starts_with(&ext_dir, "org.freedesktop.Sdk.Extension.openjdk") {
+                continue;
+            }
+            for arch_dir in read_dir_paths(&ext_dir) {
+                for branch_dir in read_dir_paths(&arch_dir) {
+                    self.add_homes_in(&branch_dir.joined("active/files/jvm"));
+                }
+            }
+        }
+    }
+
+}
+
+/// Iterate over all the paths in the given directory, silently ignoring any error.
+fn read_dir_paths(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    fs::read_dir(dir).into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+}
+
+/// Return true if the file name of the given path starts with the given prefix.
+#[cfg(target_os = "linux")]
+fn file_name_starts_with(path: &Path, prefix: &str) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(prefix))
+}
+
+/// Read the `release` file of the home directory of the given JVM executable, the
+/// executable is canonicalized first because it's commonly a symlink to the real one.
+fn read_release(file: &Path) -> Option<JvmInfo> {
+    let canon_file = dunce::canonicalize(file).ok()?;
+    let home_dir = canon_file.parent()?.parent()?;
+    let content = fs::read_to_string(home_dir.join("release")).ok()?;
+    parse_release(file, &content)
+}
+
+/// Parse the content of a JVM `release` file, made of `KEY="value"` lines, the version 
+/// and the architecture are required for the information to be complete.
+fn parse_release(file: &Path, content: &str) -> Option<JvmInfo> {
+
+    let mut version = None;
+    let mut vendor = None;
+    let mut arch = None;
+
+    for line in content.lines() {
+        
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let value = value.trim();
+        let value = value.strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value);
+
+        match key.trim() {
+            "JAVA_VERSION" => version = Some(value),
+            "IMPLEMENTOR" => vendor = Some(value),
+            "OS_ARCH" => arch = Some(value),
+            _ => {}
+        }
+
+    }
+
+    let version = version.filter(|s| !s.is_empty())?;
+    let arch = arch.filter(|s| !s.is_empty())?;
+
+    Some(JvmInfo {
+        file: file.to_path_buf(),
+        version: version.to_string(),
+        major_version: parse_major_version(version)?,
+        vendor: vendor.filter(|s| !s.is_empty()).map(str::to_string),
+        arch: Some(normalize_arch(arch)),
+    })
+
+}
+
+/// Parse the output of `java -XshowSettings:properties -version`, the properties are
+/// printed first, then the version, the version can be read from both but we prefer the
+/// property, falling back to the quoted version.
+fn parse_output(file: &Path, output: &str) -> Option<JvmInfo> {
+
+    let mut version = None;
+    let mut vendor = None;
+    let mut arch = None;
+    let mut quoted_version = None;
+
+    for line in output.lines() {
+
+        // Properties are indented, multi-values properties continue on next lines
+        // with deeper indentation and no equal sign, we ignore them.
+        if let Some((key, value)) = line.split_once(" = ") {
+            match key.trim() {
+                "java.version" => version = Some(value.trim()),
+                "java.vendor" => vendor = Some(value.trim()),
+                "os.arch" => arch = Some(value.trim()),
+                _ => {}
+            }
+        } else if quoted_version.is_none() {
+            // The standard doc says that -version outputs version on stderr. This
+            // argument -version is also practical because the version is given between
+            // double quotes.
+            quoted_version = line.split_once('"')
+                .and_then(|(_, line)| line.split_once('"'))
+                .map(|(version, _)| version);
+        }
+
+    }
+
+    let version = version.or(quoted_version)?;
+    let major_version = parse_major_version(version)?;
+
+    Some(JvmInfo {
+        file: file.to_path_buf(),
+        version: version.to_string(),
+        major_version,
+        vendor: vendor.filter(|s| !s.is_empty()).map(str::to_string),
+        arch: arch.filter(|s| !s.is_empty()).map(normalize_arch),
+    })
+
+}
+
+/// Normalize the given JVM architecture name to the naming used by Rust, see 
+/// [`std::env::consts::ARCH`], JVMs are using names such as `amd64` or `i386`.
+fn normalize_arch(arch: &str) -> String {
+    match arch {
+        "amd64" | "x86_64" | "x64" => "x86_64",
+        "x86" | "i386" | "i486" | "i586" | "i686" => "x86",
+        "aarch64" | "arm64" => "aarch64",
+        "arm" | "aarch32" => "arm",
+        "ppc64" | "ppc64le" => "powerpc64",
+        "sparcv9" => "sparc64",
+        "loongarch64" | "loong64" => "loongarch64",
+        other => other,
+    }.to_string()
+}
+
+/// Parse the major version of the given JVM version string, supporting the legacy
+/// versions such as `1.8.0_111` or `8u51`, for which the major version is `8`.
+pub fn parse_major_version(version: &str) -> Option<u32> {
+
+    // Special case for parsing versions such as '8u51'.
+    if !version.contains('.') {
+        if let Some((major, _patch)) = version.split_once('u') {
+            return major.parse::<u32>().ok();
+        }
+    }
+
+    let mut comp = version.split('.');
+    let mut major = comp.next()?.parse::<u32>().ok()?;
+    if major == 1 {
+        major = comp.next()?.parse::<u32>().ok()?;
+    }
+    Some(major)
+
+}
+
+/// This function compute the compatibility between a given JVM major version and the
+/// required one, returning None if the versions are fully incompatible. If versions are
+/// compatible then a score is returned, the less this score is, the higher if the
+/// compatibility, a score of zero means that the versions are fully compatible.
+pub fn compatibility(required_major_version: u32, major_version: u32) -> Option<u32> {
+    if required_major_version <= 8 {
+        // Because of huge breakings in the internal APIs between Java 8 and 9 (and onward),
+        // we require strict equality for Java 8 and before.
+        (required_major_version == major_version).then_some(0)
+    } else {
+        // After Java 8, we allow any greater JVM version to run, the score is computed
+        // to privilege versions that are close to another, thus reducing potential
+        // breakings between version, even if it shouldn't happen.
+        if major_version >= required_major_version {
+            Some(major_version - required_major_version)
+        } else {
+            None
+        }
+    }
+}
+
+/// A source of JVMs that can be installed by the installer when using the 
+/// [`JvmPolicy::Provider`](crate::base::JvmPolicy::Provider) policy. The provider only
+/// finds the archive to download, the installer then downloads it and extracts it in 
+/// a sub-directory of the JVM directory.
+pub trait Provider: fmt::Debug + Send + Sync {
+
+    /// The name of this provider, such as `adoptium`, it's used as the name of the
+    /// sub-directory of the JVM directory where its JVMs are extracted, so it must be
+    /// a valid file name.
+    fn name(&self) -> &str;
+
+    /// Find the archive of a JVM for the host OS and architecture that is compatible with
+    /// the given required major version, returning none if this provider has no such
+    /// JVM. The given download configuration and cancellation token should be used 
+    /// for any request.
+    fn find_archive<'a>(&'a self, 
+        major_version: u32, 
+        config: &'a download::Config,
+        cancel_token: Option<&'a download::CancelToken>,
+    ) -> Pin<Box<dyn Future<Output = base::Result<Option<Archive>>> + Send + 'a>>;
+
+}
+
+/// The archive of a JVM to install, as returned by a [`Provider`].
+#[derive(Debug, Clone)]
+pub struct Archive {
+    /// A name that uniquely identifies this JVM build among the ones of its provider, 
+    /// it's used as the name of the directory where the archive is extracted, so it
+    /// must be a valid file name.
+    pub name: String,
+    /// The full version string of this JVM, such as `21.0.5+11`.
+    pub version: String,
+    /// The URL of the archive to download.
+    pub url: String,
+    /// The expected size of the archive, if known.
+    pub size: Option<u64>,
+    /// The expected digest of the archive, if known.
+    pub digest: Option<download::Digest>,
+    /// The format of the archive.
+    pub format: ArchiveFormat,
+}
+
+/// The format of a JVM archive. The archive is expected to contain the JVM home 
+/// directory, either directly or as its single top-level directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArchiveFormat {
+    /// A gzip-compressed TAR archive, `.tar.gz`, usually used on Linux and macOS.
+    TarGz,
+    /// A ZIP archive, `.zip`, usually used on Windows.
+    Zip,
+}
+
+impl ArchiveFormat {
+
+    /// Guess the format of an archive from its file name, returning none if the file
+    /// name has no known extension.
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+
+    /// Return the file extension of this format, including the leading dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::TarGz => ".tar.gz",
+            Self::Zip => ".zip",
+        }
+    }
+
+}
+
+mod serde {
+
+    use std::collections::HashMap;
+
+    #[derive(Debug, serde::Deserialize)]
+    pub struct Cache {
+        pub jvms: HashMap<String, CacheEntry>,
+    }
+
+    #[derive(Debug, serde::Serialize)]
+    pub struct CacheRef<'a> {
+        pub jvms: &'a HashMap<String, CacheEntry>,
+    }
+
+    /// The cached information of a JVM, only valid if the modification time of the 
+    /// executable is unchanged.
+    #[derive(Debug, serde::Deserialize, serde::Serialize)]
+    pub struct CacheEntry {
+        /// Modification time of the executable, in nanoseconds since the UNIX epoch.
+        pub mtime: u64,
+        pub version: String,
+        pub vendor: Option<String>,
+        pub arch: Option<String>,
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn parse_major_version() {
+
+        use super::parse_major_version;
+
+        assert_eq!(parse_major_version("7u80"), Some(7));
+        assert_eq!(parse_major_version("8u51"), Some(8));
+        assert_eq!(parse_major_version("17"), Some(17));
+        assert_eq!(parse_major_version("17.0"), Some(17));
+        assert_eq!(parse_major_version("17.0.2"), Some(17));
+        assert_eq!(parse_major_version("1.8.0_111"), Some(8));
+        assert_eq!(parse_major_version("10.0.2"), Some(10));
+
+        // Corner cases
+        assert_eq!(parse_major_version("10.foo"), Some(10));
+        assert_eq!(parse_major_version("1.foo"), None);
+        assert_eq!(parse_major_version("foou51"), None);
+        assert_eq!(parse_major_version("8ufoo"), Some(8));
+
+    }
+
+    #[test]
+    fn compatibility() {
+
+        use super::compatibility;
+
+        assert_eq!(compatibility(7, 7), Some(0));
+        assert_eq!(compatibility(8, 8), Some(0));
+        assert_eq!(compatibility(8, 7), None);
+
+        assert_eq!(compatibility(9, 8), None);
+        assert_eq!(compatibility(9, 9), Some(0));
+        assert_eq!(compatibility(9, 11), Some(2));
+        assert_eq!(compatibility(9, 17), Some(8));
+        assert_eq!(compatibility(17, 17), Some(0));
+        assert_eq!(compatibility(17, 11), None);
+
+    }
+
+    #[test]
+    fn archive_format() {
+
+        use super::ArchiveFormat;
+
+        assert_eq!(ArchiveFormat::from_file_name("OpenJDK21U-jre_x64_linux_hotspot_21.0.5_11.tar.gz"), Some(ArchiveFormat::TarGz));
+        assert_eq!(ArchiveFormat::from_file_name("jre.tgz"), Some(ArchiveFormat::TarGz));
+        assert_eq!(ArchiveFormat::from_file_name("OpenJDK21U-jre_x64_windows_hotspot_21.0.5_11.zip"), Some(ArchiveFormat::Zip));
+        assert_eq!(ArchiveFormat::from_file_name("OpenJDK21U-jre_x64_windows_hotspot_21.0.5_11.msi"), None);
+        assert_eq!(ArchiveFormat::from_file_name("jre.tar.gz.json"), None);
+
+    }
+
+    #[test]
+    fn find_candidates() {
+
+        use std::fs;
+        use super::{find_candidates, exec_name};
+
+        let dir = tempfile::tempdir().unwrap();
+        let homes_dir = dir.path().join("homes");
+
+        // A search directory containing two homes, and one that is not a JVM.
+        for name in ["jdk-17", "jdk-21"] {
+            fs::create_dir_all(homes_dir.join(name).join("bin")).unwrap();
+            fs::write(homes_dir.join(name).join("bin").join(exec_name()), "").unwrap();
+        }
+        fs::create_dir_all(homes_dir.join("not-a-jdk")).unwrap();
+
+        // A search directory being a home directly.
+        let home_dir = dir.path().join("jdk-8");
+        fs::create_dir_all(home_dir.join("bin")).unwrap();
+        fs::write(home_dir.join("bin").join(exec_name()), "").unwrap();
+
+        // A symlink to an already found home, like SDKMAN's 'current' one.
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(homes_dir.join("jdk-21"), homes_dir.join("current")).unwrap();
+
+        let candidates = find_candidates(&[homes_dir.clone(), home_dir.clone()]);
+        let found = |file: &std::path::Path| candidates.iter()
+            .filter(|candidate| dunce::canonicalize(candidate).unwrap() == dunce::canonicalize(file).unwrap())
+            .count();
+
+        assert_eq!(found(&homes_dir.join("jdk-17/bin").join(exec_name())), 1);
+        assert_eq!(found(&homes_dir.join("jdk-21/bin").join(exec_name())), 1);
+        assert_eq!(found(&home_dir.join("bin").join(exec_name())), 1);
+        assert!(!candidates.iter().any(|candidate| candidate.starts_with(homes_dir.join("not-a-jdk"))));
+
+    }
+
+    #[test]
+    fn parse_release() {
+
+        use std::path::Path;
+        use super::{parse_release, JvmInfo};
+
+        let content = "\
+IMPLEMENTOR=\"Eclipse Adoptium\"
+IMPLEMENTOR_VERSION=\"Temurin-21.0.1+12\"
+JAVA_VERSION=\"21.0.1\"
+JAVA_VERSION_DATE=\"2023-10-17\"
+OS_ARCH=\"aarch64\"
+OS_NAME=\"Linux\"
+";
+
+        assert_eq!(parse_release(Path::new("java"), content), Some(JvmInfo {
+            file: "java".into(),
+            version: "21.0.1".to_string(),
+            major_version: 21,
+            vendor: Some("Eclipse Adoptium".to_string()),
+            arch: Some("aarch64".to_string()),
+        }));
+
+        // Legacy versions without implementor.
+        let content = "JAVA_VERSION=\"1.8.0_111\"\nOS_ARCH=\"amd64\"\n";
+        assert_eq!(parse_release(Path::new("java"), content), Some(JvmInfo {
+            file: "java".into(),
+            version: "1.8.0_111".to_string(),
+            major_version: 8,
+            vendor: None,
+            arch: Some("x86_64".to_string()),
+        }));
+
+        // Architectures of the JVM are normalized to the Rust naming.
+        for (jvm_arch, arch) in [("ppc64", "powerpc64"), ("aarch32", "arm"), ("sparcv9", "sparc64"), ("loongarch64", "loongarch64")] {
+            let content = format!("JAVA_VERSION=\"17\"\nOS_ARCH=\"{jvm_arch}\"\n");
+            assert_eq!(parse_release(Path::new("java"), &content).unwrap().arch.as_deref(), Some(arch));
+        }
+
+        // Architecture is required, so the JVM is spawned instead.
+        assert_eq!(parse_release(Path::new("java"), "JAVA_VERSION=\"17\"\n"), None);
+
+    }
+
+    #[test]
+    fn probe_cached() {
+
+        use std::fs::{self, File};
+        use std::time::{Duration, SystemTime};
+        use super::{probe_cached, exec_name};
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache_file = dir.path().join("jvm_cache.json");
+        let home_dir = dir.path().join("jdk");
+        let file = home_dir.join("bin").join(exec_name());
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, "").unwrap();
+        fs::write(home_dir.join("release"), "JAVA_VERSION=\"17.0.2\"\nOS_ARCH=\"riscv64\"\n").unwrap();
+
+        let info = probe_cached(&[&file], &cache_file).pop().flatten().unwrap();
+        assert_eq!(info.version, "17.0.2");
+        assert_eq!(info.arch.as_deref(), Some("riscv64"));
+        assert_eq!(info.is_host_arch(), std::env::consts::ARCH == "riscv64");
+        assert!(cache_file.is_file());
+
+        // The executable is unchanged, so the cached information are used.
+        fs::write(home_dir.join("release"), "JAVA_VERSION=\"21.0.1\"\nOS_ARCH=\"riscv64\"\n").unwrap();
+        let info = probe_cached(&[&file], &cache_file).pop().flatten().unwrap();
+        assert_eq!(info.version, "17.0.2");
+
+        // The executable is modified, so it's probed again.
+        let mtime = SystemTime::now() + Duration::from_secs(10);
+        File::options().write(true).open(&file).unwrap().set_modified(mtime).unwrap();
+        let info = probe_cached(&[&file], &cache_file).pop().flatten().unwrap();
+        assert_eq!(info.version, "21.0.1");
+
+        // The same executable through a symlink shares the cache entry of its target.
+        #[cfg(unix)] {
+            let link_dir = dir.path().join("jdk-link");
+            std::os::unix::fs::symlink(&home_dir, &link_dir).unwrap();
+            fs::write(home_dir.join("release"), "JAVA_VERSION=\"17.0.2\"\nOS_ARCH=\"riscv64\"\n").unwrap();
+            let link_file = link_dir.join("bin").join(exec_name());
+            let info = probe_cached(&[&link_file], &cache_file).pop().flatten().unwrap();
+            assert_eq!(info.version, "21.0.1");
+            assert_eq!(info.file, link_file);
+        }
+
+    }
+
+    #[test]
+    fn parse_output() {
+
+        use std::path::Path;
+        use super::{parse_output, JvmInfo};
+
+        let output = "\
+Property settings:
+    file.separator = /
+    java.class.path =
+    java.library.path = /usr/java/packages/lib
+        /usr/lib64
+        /lib64
+    java.vendor = Eclipse Adoptium
+    java.version = 17.0.2
+    os.arch = amd64
+
+openjdk version \"17.0.2\" 2022-01-18
+OpenJDK Runtime Environment Temurin-17.0.2+8 (build 17.0.2+8)
+OpenJDK 64-Bit Server VM Temurin-17.0.2+8 (build 17.0.2+8, mixed mode, sharing)
+";
+
+        assert_eq!(parse_output(Path::new("java"), output), Some(JvmInfo {
+            file: "java".into(),
+            version: "17.0.2".to_string(),
+            major_version: 17,
+            vendor: Some("Eclipse Adoptium".to_string()),
+            arch: Some("x86_64".to_string()),
+        }));
+
+        // Only the version is known.
+        let output = "java version \"1.8.0_111\"\nJava(TM) SE Runtime Environment (build 1.8.0_111-b14)\n";
+        assert_eq!(parse_output(Path::new("java"), output), Some(JvmInfo {
+            file: "java".into(),
+            version: "1.8.0_111".to_string(),
+            major_version: 8,
+            vendor: None,
+            arch: None,
+        }));
+
+        assert_eq!(parse_output(Path::new("java"), "Unrecognized option\n"), None);
+
+    }
+
+}
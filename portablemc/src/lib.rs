@@ -13,6 +13,8 @@ pub mod msa;
 
 pub mod download;
 
+pub mod jvm;
+
 pub mod base;
 pub mod moj;
 pub mod fabric;
@@ -460,27 +460,112 @@ impl Installer {
     /// 
     /// If the given version is not found in the manifest then it's silently ignored and
     /// the version metadata must already exists.
+    /// 
+    /// See [`base::Installer::install`] about the internal Tokio runtime.
     #[inline]
     pub fn install(&mut self, mut handler: impl Handler) -> Result<Game> {
         self.install_dyn(&mut handler)
     }
 
+    /// Async version of [`Self::install`], to be awaited from an existing Tokio 
+    /// runtime.
+    pub async fn install_async(&mut self, mut handler: impl Handler + Send) -> Result<Game> {
+        self.install_impl(&mut handler as &mut (dyn Handler + Send)).await
+    }
+
+    /// Resolve the given Mojang version like it would be installed, but return a plan
+    /// of everything that would be downloaded, see [`base::Installer::plan`]. Missing
+    /// version metadata are still fetched.
+    #[inline]
+    pub fn plan(&mut self, mut handler: impl Handler) -> Result<base::Plan> {
+        self.plan_dyn(&mut handler)
+    }
+
+    /// Async version of [`Self::plan`], to be awaited from an existing Tokio runtime.
+    pub async fn plan_async(&mut self, mut handler: impl Handler + Send) -> Result<base::Plan> {
+        self.plan_impl(&mut handler as &mut (dyn Handler + Send)).await
+    }
+
     #[inline(never)]
     fn install_dyn(&mut self, handler: &mut dyn Handler) -> Result<Game> {
+        crate::tokio::sync(self.install_impl(handler))
+    }
+
+    #[inline(never)]
+    fn plan_dyn(&mut self, handler: &mut dyn Handler) -> Result<base::Plan> {
+        crate::tokio::sync(self.plan_impl(handler))
+    }
+
+    /// Internal plan function, see [`base::Installer::install_impl`].
+    pub(crate) async fn plan_impl<H: AsyncHandler + ?Sized>(&mut self, handler: &mut H) -> Result<base::Plan> {
+
+        let manifest = self.resolve_version(&mut *handler).await?;
+        let mut leaf_version = String::new();
+
+        let &mut Self {
+            ref mut base,
+            ref inner,
+        } = self;
+
+        let mut handler = InternalHandler {
+            inner: &mut *handler,
+            installer: inner,
+            download_config: base.download_config().clone(),
+            cancel_token: base.cancel_token().cloned(),
+            error: Ok(()),
+            manifest,
+            leaf_version: &mut leaf_version,
+        };
+
+        let res = base.plan_impl(&mut handler).await;
+        handler.error?;
+        Ok(res?)
+
+    }
+
+    /// Internal install function, see [`base::Installer::install_impl`].
+    pub(crate) async fn install_impl<H: AsyncHandler + ?Sized>(&mut self, handler: &mut H) -> Result<Game> {
         
         // Apply default offline auth, derived from hostname.
         if self.inner.auth_uuid.is_nil() || self.inner.auth_username.is_empty() {
             self.set_auth_offline_hostname();
         }
 
+        let manifest = self.resolve_version(&mut *handler).await?;
+        
+        // Let the handler find the "leaf" version.
+        let mut leaf_version = String::new();
+
         let &mut Self {
             ref mut base,
             ref inner,
         } = self;
 
+        let mut internal_handler = InternalHandler {
+            inner: &mut *handler,
+            installer: inner,
+            download_config: base.download_config().clone(),
+            cancel_token: base.cancel_token().cloned(),
+            error: Ok(()),
+            manifest,
+            leaf_version: &mut leaf_version,
+        };
+
+        let res = base.install_impl(&mut internal_handler).await;
+        internal_handler.error?;
+        let game = res?;
+
+        Ok(self.finalize_game(handler.as_dyn(), &leaf_version, game))
+
+    }
+
+    /// Internal function to resolve the version to install and set it to the base 
+    /// installer, the manifest is returned if it has been requested for that.
+    async fn resolve_version<H: AsyncHandler + ?Sized>(&mut self, handler: &mut H) -> Result<Option<Manifest>> {
+
         let manifest = match self.inner.version {
             Version::Release | 
-            Version::Snapshot => Some(Manifest::request((&mut *handler).into_download())?),
+            Version::Snapshot => Some(Manifest::request_impl(&mut (&mut *handler).into_download(), self.base.download_config(), self.base.cancel_token()).await?),
             _ => None
         };
 
@@ -490,28 +575,23 @@ impl Installer {
             Version::Name(name) => name.as_str(),
         };
 
-        base.set_version(version);
-        
-        // Let the handler find the "leaf" version.
-        let mut leaf_version = String::new();
+        self.base.set_version(version);
+        Ok(manifest)
 
-        // Scoping the temporary internal handler.
-        let mut game = {
+    }
 
-            let mut handler = InternalHandler {
-                inner: &mut *handler,
-                installer: &inner,
-                error: Ok(()),
-                manifest,
-                leaf_version: &mut leaf_version,
-            };
-    
-            // Same as above, we are giving a &mut dyn ref to avoid huge monomorphization.
-            let res = base.install(&mut handler);
-            handler.error?;
-            res?
+    /// Internal function to apply all arguments and fixes to the installed game.
+    fn finalize_game(&self, handler: &mut dyn Handler, leaf_version: &str, mut game: Game) -> Game {
 
-        };
+        let Self {
+            base,
+            inner,
+        } = self;
+
+        // Authentication, Quick Play, resolution and legacy fixes are all client-only.
+        if base.side() == base::InstallSide::Server {
+            return game;
+        }
 
         // Apply auth parameters.
         game.replace_args(|arg| {
@@ -644,7 +724,7 @@ impl Installer {
             game.game_args.push("--disableChat".to_string());
         }
 
-        Ok(game)
+        game
 
     }
 
@@ -725,6 +805,8 @@ pub(crate) trait HandlerInto: Handler + Sized {
 
 impl<H: Handler> HandlerInto for H {}
 
+base::def_async_handler!();
+
 /// The Mojang installer could not proceed to the installation of a version.
 #[derive(thiserror::Error, Debug)]
 #[non_exhaustive]
@@ -814,15 +896,48 @@ pub struct Manifest {
 impl Manifest {
 
     /// Request the Mojang versions' manifest.
+    /// 
+    /// See [`download::Single::download`] about the internal Tokio runtime.
     pub fn request(mut handler: impl download::Handler) -> Result<Self> {
-        return Self::request_dyn(&mut handler)
+        Self::request_with_config_dyn(&mut handler, &download::Config::default())
     }
 
-    fn request_dyn(handler: &mut dyn download::Handler) -> Result<Self> {
+    /// Async version of [`Self::request`], to be awaited from an existing Tokio 
+    /// runtime.
+    pub async fn request_async(mut handler: impl download::Handler + Send) -> Result<Self> {
+        Self::request_impl(&mut handler as &mut (dyn download::Handler + Send), &download::Config::default(), None).await
+    }
+
+    /// Request the Mojang versions' manifest with the given download configuration, 
+    /// for example to use mirrors.
+    /// 
+    /// See [`download::Single::download`] about the internal Tokio runtime.
+    pub fn request_with_config(mut handler: impl download::Handler, config: &download::Config) -> Result<Self> {
+        Self::request_with_config_dyn(&mut handler, config)
+    }
+
+    /// Async version of [`Self::request_with_config`], to be awaited from an existing
+    /// Tokio runtime.
+    pub async fn request_with_config_async(mut handler: impl download::Handler + Send, config: &download::Config) -> Result<Self> {
+        Self::request_impl(&mut handler as &mut (dyn download::Handler + Send), config, None).await
+    }
+
+    #[inline(never)]
+    fn request_with_config_dyn(handler: &mut dyn download::Handler, config: &download::Config) -> Result<Self> {
+        crate::tokio::sync(Self::request_impl(handler, config, None))
+    }
+
+    pub(crate) async fn request_impl<H: download::Handler + ?Sized>(
+        handler: &mut H, 
+        config: &download::Config,
+        cancel_token: Option<&download::CancelToken>,
+    ) -> Result<Self> {
 
         let mut entry = download::single_cached(VERSION_MANIFEST_URL)
             .set_keep_open()
-            .download(handler)?;
+            .set_config(config.clone())
+            .set_cancel_token(cancel_token.cloned())
+            .download_impl(handler).await?;
 
         let reader = BufReader::new(entry.take_handle().unwrap());
         let mut deserializer = serde_json::Deserializer::from_reader(reader);
@@ -907,7 +1022,7 @@ impl<'a> ManifestVersion<'a> {
     }
 
     /// Return the expected size of this version metadata, if any.
-    pub fn size(&self) -> Option<u32> {
+    pub fn size(&self) -> Option<u64> {
         self.0.download.size
     }
 
@@ -923,11 +1038,15 @@ impl<'a> ManifestVersion<'a> {
 // ========================== //
 
 /// Internal handler given to the base installer.
-struct InternalHandler<'a> {
+struct InternalHandler<'a, H: ?Sized> {
     /// Inner handler.
-    inner: &'a mut dyn Handler,
+    inner: &'a mut H,
     /// Back-reference to the installer to know its configuration.
     installer: &'a InstallerInner,
+    /// The base installer's download configuration, used when fetching versions.
+    download_config: download::Config,
+    /// The base installer's cancellation token, used when fetching versions.
+    cancel_token: Option<download::CancelToken>,
     /// If there is an error in the handler.
     error: Result<()>,
     /// If fetching is enabled, then this contains the manifest to use.
@@ -936,7 +1055,7 @@ struct InternalHandler<'a> {
     leaf_version: &'a mut String,
 }
 
-impl<'a> base::Handler for InternalHandler<'a> {
+impl<H: AsyncHandler + ?Sized> base::Handler for InternalHandler<'_, H> {
     
     fn on_event(&mut self, mut event: base::Event) {
         
@@ -947,15 +1066,42 @@ impl<'a> base::Handler for InternalHandler<'a> {
             base::Event::LoadedHierarchy {
                 hierarchy,
             } => self.loaded_hierarchy(hierarchy),
+            base::Event::FilterLibraries { 
+                ref mut libraries,
+            } => self.filter_libraries(*libraries),
+            _ => Ok(())
+        };
+        
+        if let Err(e) = ret {
+            self.error = Err(e);
+            return;
+        }
+
+        self.inner.on_event(Event::Base(event));
+
+    }
+
+}
+
+impl<H: AsyncHandler + ?Sized> base::AsyncHandler for InternalHandler<'_, H> {
+
+    #[inline]
+    fn as_dyn(&mut self) -> &mut dyn base::Handler {
+        self
+    }
+
+    async fn on_event_async(&mut self, mut event: base::Event<'_>) {
+
+        let ret = match event {
             base::Event::LoadVersion { 
                 version, 
                 file,
-            } => self.load_version(version, file),
+            } => self.load_version(version, file).await,
             base::Event::NeedVersion { 
                 version, 
                 file, 
                 ref mut retry,
-            } => match self.need_version(version, file) {
+            } => match self.need_version(version, file).await {
                 Ok(true) => {
                     **retry = true;
                     Ok(())
@@ -963,10 +1109,7 @@ impl<'a> base::Handler for InternalHandler<'a> {
                 Ok(false) => Ok(()),
                 Err(e) => Err(e),
             }
-            base::Event::FilterLibraries { 
-                ref mut libraries,
-            } => self.filter_libraries(*libraries),
-            _ => Ok(())
+            _ => return base::Handler::on_event(self, event),
         };
         
         if let Err(e) = ret {
@@ -974,13 +1117,13 @@ impl<'a> base::Handler for InternalHandler<'a> {
             return;
         }
 
-        self.inner.on_event(Event::Base(event));
+        self.inner.on_event_async(Event::Base(event)).await;
 
     }
 
 }
 
-impl InternalHandler<'_> {
+impl<H: AsyncHandler + ?Sized> InternalHandler<'_, H> {
 
     fn filter_features(&mut self, features: &mut HashSet<String>) -> Result<()> {
         
@@ -1010,25 +1153,32 @@ impl InternalHandler<'_> {
         Ok(())
     }
 
-    fn load_version(&mut self, version: &str, file: &Path) -> Result<()> {
+    /// Return true if the given version is excluded from fetching.
+    fn is_fetch_excluded(&self, version: &str) -> bool {
+        self.installer.fetch_excludes.iter().any(|pattern| match pattern {
+            FetchExclude::All => true,
+            FetchExclude::Exact(name) => name == version,
+            FetchExclude::Regex(regex) => regex.is_match(version),
+        })
+    }
 
-        // If any pattern matches, return Ok.
-        for pattern in &self.installer.fetch_excludes {
-            match pattern {
-                FetchExclude::All => 
-                    return Ok(()),
-                FetchExclude::Exact(name) if name == version => 
-                    return Ok(()),
-                FetchExclude::Regex(regex) if regex.is_match(version) => 
-                    return Ok(()),
-                _ => (),
-            }
+    async fn load_version(&mut self, version: &str, file: &Path) -> Result<()> {
+
+        if self.is_fetch_excluded(version) {
+            return Ok(());
         }
 
-        // Only ensure that the manifest is loaded after checking fetch exclude.
+        // Only ensure that the manifest is loaded after checking fetch exclude. When
+        // offline and the manifest is not cached, the local version is trusted as-is.
         let manifest = match self.manifest {
             Some(ref manifest) => manifest,
-            None => self.manifest.insert(Manifest::request((&mut *self.inner).into_download())?)
+            None => {
+                match Manifest::request_impl(&mut (&mut *self.inner).into_download(), &self.download_config, self.cancel_token.as_ref()).await {
+                    Ok(manifest) => self.manifest.insert(manifest),
+                    Err(Error::Base(base::Error::Offline { .. })) => return Ok(()),
+                    Err(e) => return Err(e),
+                }
+            }
         };
 
         // Unwrap because we checked the manifest in the condition.
@@ -1036,7 +1186,8 @@ impl InternalHandler<'_> {
             return Ok(());
         };
 
-        if !check_file_advanced(file, version.size(), version.sha1(), true)? {
+        let digest = version.sha1().copied().map(download::Digest::Sha1);
+        if !check_file_advanced(file, version.size(), digest.as_ref(), true)? {
             
             fs::remove_file(file)
                 .map_err(|e| base::Error::new_io_file(e, file))?;
@@ -1049,10 +1200,14 @@ impl InternalHandler<'_> {
 
     }
 
-    fn need_version(&mut self, version: &str, file: &Path) -> Result<bool> {
+    async fn need_version(&mut self, version: &str, file: &Path) -> Result<bool> {
 
-        let Some(manifest) = self.manifest.as_ref() else {
-            return Ok(false);
+        // If the manifest has not been loaded while not excluded, this is because we
+        // are offline, so we request it again in order to return the offline error.
+        let manifest = match self.manifest {
+            Some(ref manifest) => manifest,
+            None if self.is_fetch_excluded(version) => return Ok(false),
+            None => self.manifest.insert(Manifest::request_impl(&mut (&mut *self.inner).into_download(), &self.download_config, self.cancel_token.as_ref()).await?),
         };
         
         let Some(version) = manifest.find_by_name(version) else {
@@ -1064,7 +1219,9 @@ impl InternalHandler<'_> {
         download::single(version.url(), file)
             .set_expected_size(version.size())
             .set_expected_sha1(version.sha1().copied())
-            .download((&mut *self.inner).into_download())?;
+            .set_config(self.download_config.clone())
+            .set_cancel_token(self.cancel_token.clone())
+            .download_impl(&mut (&mut *self.inner).into_download()).await?;
 
         self.inner.on_event(Event::FetchedVersion { version: version.name() });
 
@@ -1099,7 +1256,7 @@ impl InternalHandler<'_> {
             libraries[pos].download = Some(LibraryDownload {
                 url: format!("{LIBRARIES_URL}{}", libraries[pos].name.url()),
                 size: Some(87497),
-                sha1: Some([0xd6, 0xe6, 0x77, 0x19, 0x9a, 0xa6, 0xb1, 0x9c, 0x4a, 0x9a, 0x2e, 0x72, 0x50, 0x34, 0x14, 0x9e, 0xb3, 0xe7, 0x46, 0xf8]),
+                digest: Some(download::Digest::Sha1([0xd6, 0xe6, 0x77, 0x19, 0x9a, 0xa6, 0xb1, 0x9c, 0x4a, 0x9a, 0x2e, 0x72, 0x50, 0x34, 0x14, 0x9e, 0xb3, 0xe7, 0x46, 0xf8])),
             });
 
             self.inner.on_event(Event::FixedBrokenAuthlib);
@@ -1174,7 +1331,7 @@ impl InternalHandler<'_> {
         for lib in libraries {
             if let ("org.lwjgl", "jar") = (lib.name.group(), lib.name.extension()) {
                 let url = format!("https://repo1.maven.org/maven2/{}", lib.name.url());
-                lib.download = Some(LibraryDownload { url, size: None, sha1: None });
+                lib.download = Some(LibraryDownload { url, size: None, digest: None });
             }
         }
 
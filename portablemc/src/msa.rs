@@ -14,6 +14,8 @@ use uuid::Uuid;
 
 use jsonwebtoken::{DecodingKey, TokenData, Validation};
 
+use crate::download;
+
 
 /// Microsoft Account authenticator.
 /// 
@@ -23,6 +25,7 @@ use jsonwebtoken::{DecodingKey, TokenData, Validation};
 pub struct Auth {
     app_id: Arc<str>,
     language_code: Option<String>,
+    download_config: download::Config,
 }
 
 impl Auth {
@@ -32,6 +35,7 @@ impl Auth {
         Self {
             app_id: Arc::from(app_id),
             language_code: None,
+            download_config: download::Config::default(),
         }
     }
 
@@ -54,48 +58,66 @@ impl Auth {
         self
     }
 
+    /// The download configuration used for configuring the HTTP client of all 
+    /// authentication requests, it's given to the accounts that are authenticated.
+    #[inline]
+    pub fn download_config(&self) -> &download::Config {
+        &self.download_config
+    }
+
+    /// See [`Self::download_config`].
+    #[inline]
+    pub fn set_download_config(&mut self, config: download::Config) -> &mut Self {
+        self.download_config = config;
+        self
+    }
+
     /// Request a device code and if successful, returns the device code auth flow that
     /// contains the user code and the verification URI for that, this flow should be
     /// waited in order to get access to a minecraft authenticator that will ultimately
     /// produce the desired username, UUID and its auth token(s).
     /// 
     /// You can opt-in to also request the account's primary email via OpenID MSA scope.
+    /// 
+    /// This is the blocking version of [`Self::request_device_code_async`].
     pub fn request_device_code(&self) -> Result<DeviceCodeFlow, AuthError> {
+        crate::tokio::sync(self.request_device_code_async())
+    }
 
-        crate::tokio::sync(async move {
-
-            // We request the 'XboxLive.signin' and 'offline_access' scopes that are
-            // mandatory for the Minecraft authentication.
-            // We could also request email with "openid email" scopes.
-            let req = MsDeviceAuthRequest {
-                client_id: &self.app_id,
-                scope: "XboxLive.signin offline_access",
-                mkt: self.language_code.as_deref(),
-            };
+    /// Async version of [`Self::request_device_code`].
+    pub async fn request_device_code_async(&self) -> Result<DeviceCodeFlow, AuthError> {
 
-            let client = crate::http::builder().build()
-                .map_err(AuthError::new_reqwest)?;
+        // We request the 'XboxLive.signin' and 'offline_access' scopes that are
+        // mandatory for the Minecraft authentication.
+        // We could also request email with "openid email" scopes.
+        let req = MsDeviceAuthRequest {
+            client_id: &self.app_id,
+            scope: "XboxLive.signin offline_access",
+            mkt: self.language_code.as_deref(),
+        };
 
-            let res = client
-                .post("https://login.microsoftonline.com/consumers/oauth2/v2.0/devicecode")
-                .form(&req)
-                .send().await
-                .map_err(AuthError::new_reqwest)?;
+        let client = self.download_config.http_client()
+            .map_err(AuthError::new_reqwest)?;
 
-            if res.status() != StatusCode::OK {
-                return Err(AuthError::InvalidStatus(res.status().as_u16()));
-            }
+        let res = client
+            .post("https://login.microsoftonline.com/consumers/oauth2/v2.0/devicecode")
+            .form(&req)
+            .send().await
+            .map_err(AuthError::new_reqwest)?;
 
-            let res = res
-                .json::<MsDeviceAuthSuccess>().await
-                .map_err(AuthError::new_reqwest)?;
+        if res.status() != StatusCode::OK {
+            return Err(AuthError::InvalidStatus(res.status().as_u16()));
+        }
 
-            Ok(DeviceCodeFlow {
-                client,
-                app_id: Arc::clone(&self.app_id),
-                res,
-            })
+        let res = res
+            .json::<MsDeviceAuthSuccess>().await
+            .map_err(AuthError::new_reqwest)?;
 
+        Ok(DeviceCodeFlow {
+            client,
+            download_config: self.download_config.clone(),
+            app_id: Arc::clone(&self.app_id),
+            res,
         })
 
     }
@@ -106,6 +128,7 @@ impl Auth {
 #[derive(Debug, Clone)]
 pub struct DeviceCodeFlow {
     client: Client,
+    download_config: download::Config,
     app_id: Arc<str>,
     res: MsDeviceAuthSuccess,
 }
@@ -137,47 +160,51 @@ impl DeviceCodeFlow {
     /// possible.
     /// 
     /// After a successful answer, this flow object should not be used again!
+    /// 
+    /// This is the blocking version of [`Self::wait_async`].
     pub fn wait(&self) -> Result<Account, AuthError> {
+        crate::tokio::sync(self.wait_async())
+    }
 
-        crate::tokio::sync(async move {
+    /// Async version of [`Self::wait`].
+    pub async fn wait_async(&self) -> Result<Account, AuthError> {
 
-            let req = MsTokenRequest::DeviceCode {
-                client_id: &self.app_id,
-                device_code: &self.res.device_code,
-            };
-            
-            let interval = Duration::from_secs(self.res.interval as u64);
+        let req = MsTokenRequest::DeviceCode {
+            client_id: &self.app_id,
+            device_code: &self.res.device_code,
+        };
+        
+        let interval = Duration::from_secs(self.res.interval as u64);
 
-            loop {
+        loop {
 
-                tokio::time::sleep(interval).await;
-                match request_ms_token(&self.client, &req, "XboxLive.signin").await? {
-                    Ok(res) => {
+            tokio::time::sleep(interval).await;
+            match request_ms_token(&self.client, &req, "XboxLive.signin").await? {
+                Ok(res) => {
 
-                        let mut account = request_minecraft_account(&self.client, &res.access_token).await?;
-                        account.app_id = self.app_id.to_string();
-                        account.refresh_token = res.refresh_token;
+                    let mut account = request_minecraft_account(&self.client, &res.access_token).await?;
+                    account.app_id = self.app_id.to_string();
+                    account.refresh_token = res.refresh_token;
+                    account.download_config = self.download_config.clone();
 
-                        break Ok(account);
+                    break Ok(account);
 
-                    }
-                    Err(res) => {
-                        match res.error.as_str() {
-                            "authorization_pending" => 
-                                continue,
-                            "authorization_declined" => 
-                                break Err(AuthError::Declined),
-                            "expired_token" => 
-                                break Err(AuthError::TimedOut),
-                            "bad_verification_code" | _ => 
-                                break Err(AuthError::Unknown(res.error_description)),
-                        }
+                }
+                Err(res) => {
+                    match res.error.as_str() {
+                        "authorization_pending" => 
+                            continue,
+                        "authorization_declined" => 
+                            break Err(AuthError::Declined),
+                        "expired_token" => 
+                            break Err(AuthError::TimedOut),
+                        "bad_verification_code" | _ => 
+                            break Err(AuthError::Unknown(res.error_description)),
                     }
                 }
-
             }
 
-        })
+        }
 
     }
 
@@ -192,6 +219,7 @@ pub struct Account {
     uuid: Uuid,
     username: String,
     xuid: String,
+    download_config: download::Config,
 }
 
 impl Account {
@@ -226,18 +254,40 @@ impl Account {
         &self.xuid
     }
 
+    /// The download configuration used for the requests of this account, it's
+    /// inherited from the authenticator, or the default one when loaded from a
+    /// database. It's not stored in the database.
+    #[inline]
+    pub fn download_config(&self) -> &download::Config {
+        &self.download_config
+    }
+
+    /// See [`Self::download_config`].
+    #[inline]
+    pub fn set_download_config(&mut self, config: download::Config) -> &mut Self {
+        self.download_config = config;
+        self
+    }
+
     /// Make a request of this account's profile, this function take self by mutable 
     /// reference because it may update the username if it has been modified since last
     /// request. If this function returns an error, it may be necessary to refresh the
     /// account.
     /// 
     /// It's not required to run that on newly authenticated or refreshed accounts.
+    /// 
+    /// This is the blocking version of [`Self::request_profile_async`].
     pub fn request_profile(&mut self) -> Result<(), AuthError> {
+        crate::tokio::sync(self.request_profile_async())
+    }
+
+    /// Async version of [`Self::request_profile`].
+    pub async fn request_profile_async(&mut self) -> Result<(), AuthError> {
         
-        let client = crate::http::builder().build()
+        let client = self.download_config.http_client()
             .map_err(AuthError::new_reqwest)?;
 
-        let profile = crate::tokio::sync(request_minecraft_profile(&client, &self.access_token))?;
+        let profile = request_minecraft_profile(&client, &self.access_token).await?;
         self.username = profile.name;
         Ok(())
 
@@ -245,37 +295,40 @@ impl Account {
 
     /// Request a token refresh of this account, this will use the internal refresh token,
     /// this will also update the username, uuid and access token.
+    /// 
+    /// This is the blocking version of [`Self::request_refresh_async`].
     pub fn request_refresh(&mut self) -> Result<(), AuthError> {
+        crate::tokio::sync(self.request_refresh_async())
+    }
 
-        crate::tokio::sync(async move {
+    /// Async version of [`Self::request_refresh`].
+    pub async fn request_refresh_async(&mut self) -> Result<(), AuthError> {
 
-            let client = crate::http::builder().build()
-                .map_err(AuthError::new_reqwest)?;
+        let client = self.download_config.http_client()
+            .map_err(AuthError::new_reqwest)?;
 
-            let req = MsTokenRequest::RefreshToken { 
-                client_id: &self.app_id, 
-                scope: Some("XboxLive.signin offline_access"), 
-                refresh_token: &self.refresh_token, 
-                client_secret: None,
-            };
-            
-            let res = match request_ms_token(&client, &req, "XboxLive.signin").await? {
-                Ok(res) => res,
-                Err(res) => {
-                    return Err(AuthError::Unknown(res.error_description));
-                }
-            };
+        let req = MsTokenRequest::RefreshToken { 
+            client_id: &self.app_id, 
+            scope: Some("XboxLive.signin offline_access"), 
+            refresh_token: &self.refresh_token, 
+            client_secret: None,
+        };
+        
+        let res = match request_ms_token(&client, &req, "XboxLive.signin").await? {
+            Ok(res) => res,
+            Err(res) => {
+                return Err(AuthError::Unknown(res.error_description));
+            }
+        };
 
-            let account = request_minecraft_account(&client, &res.access_token).await?;
-            self.refresh_token = res.refresh_token;
-            self.access_token = account.access_token;
-            self.uuid = account.uuid;
-            self.username = account.username;
+        let account = request_minecraft_account(&client, &res.access_token).await?;
+        self.refresh_token = res.refresh_token;
+        self.access_token = account.access_token;
+        self.uuid = account.uuid;
+        self.username = account.username;
+
+        Ok(())
 
-            Ok(())
-            
-        })
-        
     }
 
 }
@@ -350,6 +403,7 @@ async fn request_minecraft_account(
         uuid: profile_res.id,
         username: profile_res.name,
         xuid: mc_res_token.claims.xuid,
+        download_config: download::Config::default(),
     })
 
 }
@@ -901,6 +955,7 @@ impl From<DatabaseDataAccount> for Account {
             uuid: value.uuid,
             username: value.username,
             xuid: value.xuid,
+            download_config: download::Config::default(),
         }
     }
 }
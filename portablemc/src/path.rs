@@ -1,7 +1,9 @@
 //! Various uncategorized utilities.
 
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::path::{Component, Path, PathBuf};
 use std::ffi::OsStr;
+use std::process;
 
 
 /// Extension to the standard [`Path`].
@@ -16,6 +18,10 @@ pub trait PathExt {
     /// Returns true if this path is both only relative and safe to join to a root dir.
     fn is_relative_and_safe(&self) -> bool;
 
+    /// Return a temporary sibling path of this file, unique to this process and call, 
+    /// used to write a file before atomically renaming it in place.
+    fn temp_sibling(&self) -> PathBuf;
+
 }
 
 impl PathExt for Path {
@@ -34,6 +40,12 @@ impl PathExt for Path {
         self.components().all(|c| matches!(c, Component::CurDir | Component::Normal(_)))
     }
 
+    fn temp_sibling(&self) -> PathBuf {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        self.append(format!(".{}-{count}.tmp", process::id()))
+    }
+
 }
 
 
@@ -81,6 +93,8 @@ mod tests {
         assert_eq!(path.join_with_extension("bar", "json"), PathBuf::from(format!("foo{SEP}bar.json")));
         assert_eq!(path.append(SEP).appended("bar.json"), PathBuf::from(format!("foo{SEP}bar.json")));
         assert_eq!(path.join("bar").joined("baz"), PathBuf::from(format!("foo{SEP}bar{SEP}baz")));
+        assert_ne!(path.temp_sibling(), path.temp_sibling());
+        assert_eq!(path.temp_sibling().parent(), path.parent());
         
     }
 
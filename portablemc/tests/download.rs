@@ -1,7 +1,9 @@
 use std::io::{self, Read, Write};
+use std::sync::{mpsc, Mutex};
 use std::fs::{self, File};
+use std::time::{Duration, Instant};
 
-use portablemc::download::{self, Batch, Entry, EntryErrorKind};
+use portablemc::download::{self, Batch, Entry, EntryErrorKind, EntryEvent, RateLimit, RetryPolicy};
 
 use tempfile::TempDir;
 
@@ -344,8 +346,8 @@ fn cache() {
     }
     
     // Now we just want to check that expected size is checked, if we returned unexpected
-    // content then it should return an error and delete the file and its cache 
-    // information.
+    // content then it should return an error and leave the previous file and its cache
+    // information untouched.
     {
         let mock = server.mock("GET", "/cached")
             .match_header("If-None-Match", "0123456789")
@@ -361,9 +363,769 @@ fn cache() {
             .set_expected_size(Some(12))
             .download(())
             .unwrap_err();
+        assert_eq!(fs::read_to_string(file).unwrap(), "Hello world!");
+        assert!(cache_file.is_file());
+        mock.assert();
+    }
+
+}
+
+#[test]
+fn download_async() {
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    
+    let mut batch = TestBatch::new();
+    let entry = batch.push("success");
+    entry.0
+        .with_status(200)
+        .with_body("Hello world!")
+        .create();
+    entry.1
+        .set_expected_size(Some(12));
+
+    // The download future should be sendable to the runtime's threads.
+    let mut inner = std::mem::replace(&mut batch.inner, Batch::new());
+    let batch_result = rt.block_on(rt.spawn(async move {
+        inner.download_async(()).await
+    })).unwrap().unwrap();
+
+    let result = batch_result.entry(0).unwrap();
+    assert!(result.file().is_file());
+    assert_eq!(result.size(), 12);
+
+    // Single download should also work.
+    let mut server = Server::new();
+    let mock = server.mock("GET", "/single")
+        .with_status(200)
+        .with_body("Hello world!")
+        .create();
+
+    let url = format!("{}/single", server.url());
+    let file = batch.dir.path().join("single");
+    let result = rt.block_on(rt.spawn(async move {
+        download::single(url, file)
+            .download_async(()).await
+    })).unwrap().unwrap();
+
+    assert!(result.file().is_file());
+    mock.assert();
+
+}
+
+#[test]
+fn cancel() {
+
+    let token = download::CancelToken::new();
+    let mut batch = TestBatch::new();
+    batch.inner.set_cancel_token(Some(token.clone()));
+
+    // This entry sends a first chunk and then stalls, so it's still in-flight when
+    // the token gets cancelled.
+    let entry = batch.push("stalled");
+    entry.0
+        .with_status(200)
+        .with_chunked_body(|w| {
+            w.write_all(b"Hello")?;
+            w.flush()?;
+            std::thread::sleep(std::time::Duration::from_secs(2));
+            w.write_all(b" world!")
+        })
+        .create();
+
+    let file = batch.dir.path().join("stalled");
+
+    let cancel_thread = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        token.cancel();
+    });
+
+    let batch_result = batch.inner.download(()).unwrap();
+    cancel_thread.join().unwrap();
+
+    assert!(batch_result.is_cancelled());
+    assert!(matches!(batch_result.entry(0).unwrap_err().kind(), EntryErrorKind::Cancelled));
+    assert!(!file.exists());
+
+    // Downloading with an already cancelled token should not start any entry.
+    let token = download::CancelToken::new();
+    token.cancel();
+
+    let mut batch = TestBatch::new();
+    batch.inner.set_cancel_token(Some(token.clone()));
+    let (mock, _) = batch.push("never");
+    let mock = mock.expect(0).create();
+
+    let batch_result = batch.inner.download(()).unwrap();
+    assert!(matches!(batch_result.entry(0).unwrap_err().kind(), EntryErrorKind::Cancelled));
+    mock.assert();
+
+    let err = download::single(format!("{}/never", batch.server.url()), batch.dir.path().join("never"))
+        .set_cancel_token(Some(token))
+        .download(())
+        .unwrap_err();
+    assert!(matches!(err.kind(), EntryErrorKind::Cancelled));
+    mock.assert();
+
+}
+
+#[test]
+fn config() {
+
+    let mut config = download::Config::new();
+    config
+        .set_concurrency(2)
+        .set_host_concurrency(Some(1))
+        .set_read_timeout(Some(std::time::Duration::from_secs(10)))
+        .set_user_agent("custom/1.0");
+
+    let mut batch = TestBatch::new();
+    batch.inner.set_config(config.clone());
+
+    let mut mocks = Vec::new();
+    for i in 0..4 {
+        let (mock, _) = batch.push(&format!("entry{i}"));
+        mocks.push(mock
+            .match_header("user-agent", "custom/1.0")
+            .with_status(200)
+            .with_body("Hello world!")
+            .create());
+    }
+
+    let batch_result = batch.inner.download(()).unwrap();
+    assert!(!batch_result.has_errors());
+    for mock in &mocks {
+        mock.assert();
+    }
+
+    // A user-supplied client takes precedence over other client settings.
+    let client = reqwest::Client::builder()
+        .user_agent("supplied/1.0")
+        .build()
+        .unwrap();
+    config.set_client(Some(client));
+
+    let mock = batch.server.mock("GET", "/supplied")
+        .match_header("user-agent", "supplied/1.0")
+        .with_status(200)
+        .with_body("Hello world!")
+        .create();
+
+    download::single(format!("{}/supplied", batch.server.url()), batch.dir.path().join("supplied"))
+        .set_config(config)
+        .download(())
+        .unwrap();
+    mock.assert();
+
+}
+
+#[test]
+fn resume() {
+
+    let mut server = Server::new();
+    let url = format!("{}/resumed", server.url());
+    let dir = tempfile::Builder::new()
+        .prefix("")
+        .suffix(".download")
+        .tempdir_in(env!("CARGO_TARGET_TMPDIR"))
+        .unwrap();
+
+    let file = dir.path().join("resumed");
+    let part_file = dir.path().join("resumed.part");
+    let part_meta_file = dir.path().join("resumed.part.meta");
+    let sha1 = *b"\xd3\x48\x6a\xe9\x13\x6e\x78\x56\xbc\x42\x21\x23\x85\xea\x79\x70\x94\x47\x58\x02";
+
+    /// Download handler that notifies once the first bytes are received.
+    struct ReceivedHandler(Option<mpsc::Sender<()>>);
+
+    impl download::Handler for ReceivedHandler {
+        fn on_progress(&mut self, _count: u32, _total_count: u32, size: u64, _total_size: u64) {
+            if size > 0 && let Some(tx) = self.0.take() {
+                let _ = tx.send(());
+            }
+        }
+    }
+
+    // The download is interrupted, the partial file should be kept with its metadata.
+    {
+        // The server only interrupts the body once the client has received the first
+        // bytes, the timeout only prevents the test from hanging if it never happens.
+        let (received_tx, received_rx) = mpsc::channel::<()>();
+        let received_rx = Mutex::new(received_rx);
+
+        let mock = server.mock("GET", "/resumed")
+            .match_header("Range", Matcher::Missing)
+            .with_status(200)
+            .with_header("Etag", "\"0123456789\"")
+            .with_chunked_body(move |w| {
+                w.write_all(b"Hello ")?;
+                w.flush()?;
+                let _ = received_rx.lock().unwrap().recv_timeout(Duration::from_secs(10));
+                Err(io::ErrorKind::TimedOut.into())
+            })
+            .create();
+
+        download::single(url.clone(), file.clone())
+            .set_expected_size(Some(12))
+            .set_expected_sha1(Some(sha1))
+            .set_max_retry(0)
+            .download(ReceivedHandler(Some(received_tx)))
+            .unwrap_err();
         assert!(!file.exists());
-        assert!(!cache_file.exists());
+        assert_eq!(fs::read_to_string(&part_file).unwrap(), "Hello ");
+        assert!(part_meta_file.is_file());
+        mock.assert();
+    }
+
+    // The download is resumed from the partial file, and fully checked.
+    {
+        let mock = server.mock("GET", "/resumed")
+            .match_header("Range", "bytes=6-")
+            .match_header("If-Range", "\"0123456789\"")
+            .with_status(206)
+            .with_header("Etag", "\"0123456789\"")
+            .with_header("Content-Range", "bytes 6-11/12")
+            .with_body("world!")
+            .create();
+
+        let result = download::single(url.clone(), file.clone())
+            .set_expected_size(Some(12))
+            .set_expected_sha1(Some(sha1))
+            .download(())
+            .unwrap();
+        assert_eq!(result.size(), 12);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "Hello world!");
+        assert!(!part_file.exists());
+        assert!(!part_meta_file.exists());
+        mock.assert();
+    }
+
+    // If the server sends the whole resource, because it changed, the partial file
+    // is discarded.
+    {
+        fs::write(&part_file, "Hallo ").unwrap();
+        fs::write(&part_meta_file, format!(r#"{{"url":"{url}","etag":"\"0123456789\"","last_modified":null}}"#)).unwrap();
+
+        let mock = server.mock("GET", "/resumed")
+            .match_header("Range", "bytes=6-")
+            .with_status(200)
+            .with_body("Hello world!")
+            .create();
+
+        download::single(url.clone(), file.clone())
+            .set_expected_size(Some(12))
+            .set_expected_sha1(Some(sha1))
+            .download(())
+            .unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "Hello world!");
+        assert!(!part_file.exists());
+        assert!(!part_meta_file.exists());
+        mock.assert();
+    }
+
+}
+
+#[test]
+fn atomic() {
+
+    let mut server = Server::new();
+    let url = format!("{}/atomic", server.url());
+    let dir = tempfile::Builder::new()
+        .prefix("")
+        .suffix(".download")
+        .tempdir_in(env!("CARGO_TARGET_TMPDIR"))
+        .unwrap();
+
+    let file = dir.path().join("atomic");
+    let dir_entries = || fs::read_dir(dir.path()).unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+
+    // A download that fails its checks should not leave any temporary file.
+    {
+        let mock = server.mock("GET", "/atomic")
+            .with_status(200)
+            .with_body("Hello world?")
+            .create();
+
+        download::single(url.clone(), file.clone())
+            .set_expected_sha1(Some(*b"\xd3\x48\x6a\xe9\x13\x6e\x78\x56\xbc\x42\x21\x23\x85\xea\x79\x70\x94\x47\x58\x02"))
+            .download(())
+            .unwrap_err();
+        assert!(dir_entries().is_empty(), "{:?}", dir_entries());
+        mock.assert();
+    }
+
+    // A successful download should only leave the final file.
+    {
+        let mock = server.mock("GET", "/atomic")
+            .with_status(200)
+            .with_body("Hello world!")
+            .create();
+
+        download::single(url.clone(), file.clone())
+            .set_expected_sha1(Some(*b"\xd3\x48\x6a\xe9\x13\x6e\x78\x56\xbc\x42\x21\x23\x85\xea\x79\x70\x94\x47\x58\x02"))
+            .download(())
+            .unwrap();
+        assert_eq!(dir_entries(), ["atomic"]);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "Hello world!");
+        mock.assert();
+    }
+
+}
+
+#[test]
+fn mirror() {
+
+    let mut mirror_server = Server::new();
+    let mut batch = TestBatch::new();
+
+    let mut config = download::Config::new();
+    config.add_mirror(format!("{}/", batch.server.url()), format!("{}/mirror/", mirror_server.url()));
+
+    assert_eq!(config.mirror_urls("https://example.com/foo"), ["https://example.com/foo"]);
+    assert_eq!(config.mirror_urls(&format!("{}/foo", batch.server.url())), [
+        format!("{}/mirror/foo", mirror_server.url()),
+        format!("{}/foo", batch.server.url()),
+    ]);
+
+    batch.inner.set_config(config.clone());
+
+    // The first entry is served by the mirror.
+    let (mock_mirrored, _) = batch.push("mirrored");
+    let mock_mirrored = mock_mirrored.expect(0).create();
+    let mock_mirrored_mirror = mirror_server.mock("GET", "/mirror/mirrored")
+        .with_status(200)
+        .with_body("Hello world!")
+        .create();
+
+    // The second entry is missing on the mirror, the original URL is used.
+    let (mock_fallback, _) = batch.push("fallback");
+    let mock_fallback = mock_fallback
+        .with_status(200)
+        .with_body("Hello world!")
+        .create();
+    let mock_fallback_mirror = mirror_server.mock("GET", "/mirror/fallback")
+        .with_status(404)
+        .create();
+
+    let batch_result = batch.inner.download(()).unwrap();
+    assert!(!batch_result.has_errors());
+    for mock in [&mock_mirrored, &mock_mirrored_mirror, &mock_fallback, &mock_fallback_mirror] {
+        mock.assert();
+    }
+
+    // Without fallback, the original URL is never requested.
+    config.set_mirror_fallback(false);
+    assert_eq!(config.mirror_urls(&format!("{}/foo", batch.server.url())), [
+        format!("{}/mirror/foo", mirror_server.url()),
+    ]);
+
+    let mock_fallback = batch.server.mock("GET", "/fallback")
+        .expect(0)
+        .create();
+
+    let err = download::single(format!("{}/fallback", batch.server.url()), batch.dir.path().join("fallback"))
+        .set_config(config)
+        .download(())
+        .unwrap_err();
+    assert!(matches!(err.kind(), EntryErrorKind::InvalidStatus(404)));
+    mock_fallback.assert();
+
+}
+
+#[test]
+fn fallback() {
+
+    let mut batch = TestBatch::new();
+    let server_url = batch.server.url();
+
+    // The main URL is not found, the first fallback has an invalid checksum and the
+    // second fallback is valid.
+    let (mock, entry) = batch.push("fallback");
+    entry
+        .set_expected_sha1(Some(*b"\xd3\x48\x6a\xe9\x13\x6e\x78\x56\xbc\x42\x21\x23\x85\xea\x79\x70\x94\x47\x58\x02"))
+        .add_fallback_url(format!("{server_url}/fallback1"))
+        .add_fallback_url(format!("{server_url}/fallback2"));
+    let mock = mock.with_status(404).create();
+    let mock1 = batch.server.mock("GET", "/fallback1")
+        .with_status(200)
+        .with_body("Hello world?")
+        .create();
+    let mock2 = batch.server.mock("GET", "/fallback2")
+        .with_status(200)
+        .with_body("Hello world!")
+        .create();
+
+    // All sources are failing, without retry on the transient status.
+    let (mock_failing, entry) = batch.push("failing");
+    entry
+        .set_max_retry(0)
+        .add_fallback_url(format!("{server_url}/failing1"));
+    let mock_failing = mock_failing.with_status(404).create();
+    let mock_failing1 = batch.server.mock("GET", "/failing1")
+        .with_status(500)
+        .create();
+
+    let batch_result = batch.inner.download(()).unwrap();
+    assert_eq!(batch_result.errors_count(), 1);
+
+    let success = batch_result.entry(0).unwrap();
+    assert_eq!(success.source_index(), 2);
+    assert_eq!(success.source_url(), format!("{server_url}/fallback2"));
+    assert_eq!(fs::read_to_string(success.file()).unwrap(), "Hello world!");
+
+    let error = batch_result.entry(1).unwrap_err();
+    assert_eq!(error.source_url(), format!("{server_url}/failing1"));
+    assert!(matches!(error.kind(), EntryErrorKind::InvalidStatus(500)));
+    let attempts = error.attempts()
+        .map(|(url, kind)| (url.to_string(), kind.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(attempts, [
+        (format!("{server_url}/failing"), "invalid status: 404".to_string()),
+        (format!("{server_url}/failing1"), "invalid status: 500".to_string()),
+    ]);
+
+    for mock in [&mock, &mock1, &mock2, &mock_failing, &mock_failing1] {
+        mock.assert();
+    }
+
+}
+
+#[test]
+fn digest() {
+
+    let sha256 = *b"\xc0\x53\x5e\x4b\xe2\xb7\x9f\xfd\x93\x29\x13\x05\x43\x6b\xf8\x89\x31\x4e\x4a\x3f\xae\xc0\x5e\xcf\xfc\xbb\x7d\xf3\x1a\xd9\xe5\x1a";
+
+    let mut batch = TestBatch::new();
+
+    let (mock_valid, entry) = batch.push("valid");
+    entry.set_expected_digest(Some(download::Digest::Sha256(sha256)));
+    let mock_valid = mock_valid
+        .with_status(200)
+        .with_body("Hello world!")
+        .create();
+
+    let (mock_invalid, entry) = batch.push("invalid");
+    entry.set_expected_digest(Some(download::Digest::Sha256(sha256)));
+    let mock_invalid = mock_invalid
+        .with_status(200)
+        .with_body("Hello world?")
+        .create();
+
+    let batch_result = batch.inner.download(()).unwrap();
+    assert_eq!(batch_result.errors_count(), 1);
+
+    let success = batch_result.entry(0).unwrap();
+    assert_eq!(success.digest(), download::Digest::Sha256(sha256));
+    assert_eq!(success.sha1(), b"\xd3\x48\x6a\xe9\x13\x6e\x78\x56\xbc\x42\x21\x23\x85\xea\x79\x70\x94\x47\x58\x02");
+
+    let error = batch_result.entry(1).unwrap_err();
+    assert!(matches!(error.kind(), EntryErrorKind::InvalidDigest));
+    assert!(!error.file().exists());
+
+    mock_valid.assert();
+    mock_invalid.assert();
+
+    assert!(download::Digest::Sha256(sha256).check_reader(&b"Hello world!"[..]).unwrap());
+    assert!(!download::Digest::Sha256(sha256).check_reader(&b"Hello world?"[..]).unwrap());
+
+}
+
+#[test]
+fn retry() {
+
+    let mut batch = TestBatch::new();
+
+    let mut policy = RetryPolicy::new();
+    policy.set_base_delay(Duration::from_millis(10));
+    let mut config = download::Config::new();
+    config.set_retry_policy(policy);
+    batch.inner.set_config(config);
+
+    // The server is first unavailable and then responds.
+    let (mock_unavailable, _) = batch.push("retry");
+    let mock_unavailable = mock_unavailable
+        .with_status(503)
+        .with_header("Retry-After", "0")
+        .expect(1)
+        .create();
+    let mock = batch.server.mock("GET", "/retry")
+        .with_status(200)
+        .with_body("Hello world!")
+        .expect(1)
+        .create();
+
+    // The server keeps failing, the entry is retried the maximum number of times.
+    let (mock_failing, entry) = batch.push("failing");
+    entry.set_max_retry(1);
+    let mock_failing = mock_failing
+        .with_status(500)
+        .expect(2)
+        .create();
+
+    let batch_result = batch.inner.download(()).unwrap();
+    assert_eq!(batch_result.errors_count(), 1);
+
+    let success = batch_result.entry(0).unwrap();
+    assert_eq!(fs::read_to_string(success.file()).unwrap(), "Hello world!");
+
+    let error = batch_result.entry(1).unwrap_err();
+    assert!(matches!(error.kind(), EntryErrorKind::InvalidStatus(500)));
+
+    for mock in [&mock_unavailable, &mock, &mock_failing] {
+        mock.assert();
+    }
+
+}
+
+#[test]
+fn rate_limit() {
+
+    let mut batch = TestBatch::new();
+
+    // Two entries of 4 KiB sharing a limit of 8 KiB/s, the limit starts with nothing
+    // available so we should wait for about one second.
+    let rate_limit = RateLimit::new(Some(8 * 1024));
+    let mut config = download::Config::new();
+    config.set_rate_limit(Some(rate_limit.clone()));
+    batch.inner.set_config(config);
+
+    let mut mocks = Vec::new();
+    for i in 0..2 {
+        let (mock, _) = batch.push(&format!("entry{i}"));
+        mocks.push(mock
+            .with_status(200)
+            .with_body(vec![0u8; 4 * 1024])
+            .create());
+    }
+
+    let start = Instant::now();
+    let batch_result = batch.inner.download(()).unwrap();
+    assert!(!batch_result.has_errors());
+    assert!(start.elapsed() >= Duration::from_millis(800));
+
+    for mock in &mocks {
         mock.assert();
     }
 
+    // The limit can be changed from the handle while downloading, the entry would 
+    // take more than one hour without the limit being removed.
+    rate_limit.set_limit(Some(1));
+    assert_eq!(rate_limit.limit(), Some(1));
+
+    let (mock, _) = batch.push("entry2");
+    let mock = mock
+        .with_status(200)
+        .with_body(vec![0u8; 4 * 1024])
+        .create();
+
+    let handle = std::thread::spawn({
+        let rate_limit = rate_limit.clone();
+        move || {
+            std::thread::sleep(Duration::from_millis(200));
+            rate_limit.set_limit(None);
+        }
+    });
+
+    let start = Instant::now();
+    let batch_result = batch.inner.download(()).unwrap();
+    assert!(!batch_result.has_errors());
+    assert!(start.elapsed() < Duration::from_secs(10));
+    handle.join().unwrap();
+    mock.assert();
+
+}
+
+#[test]
+fn events() {
+
+    #[derive(Default)]
+    struct EventHandler {
+        events: Vec<(usize, String)>,
+        size: u64,
+    }
+
+    impl download::Handler for EventHandler {
+        fn on_progress(&mut self, count: u32, total_count: u32, size: u64, total_size: u64) {
+            let _ = (count, total_count, size, total_size);
+        }
+        fn on_entry_event(&mut self, index: usize, event: EntryEvent<'_>) {
+            match event {
+                EntryEvent::Started { .. } => self.events.push((index, "started".to_string())),
+                EntryEvent::Progress { size, .. } => self.size = self.size.max(size),
+                EntryEvent::Retried { retry, .. } => self.events.push((index, format!("retried {retry}"))),
+                EntryEvent::Completed { size } => self.events.push((index, format!("completed {size}"))),
+                EntryEvent::Failed { error } => self.events.push((index, format!("failed {error}"))),
+                _ => (),
+            }
+        }
+    }
+
+    let mut batch = TestBatch::new();
+
+    let mut policy = RetryPolicy::new();
+    policy.set_base_delay(Duration::from_millis(10));
+    let mut config = download::Config::new();
+    config.set_retry_policy(policy);
+    batch.inner.set_config(config);
+
+    let (mock, _) = batch.push("success");
+    let mock = mock.with_status(200).with_body("Hello world!").create();
+
+    let (mock_failing, entry) = batch.push("failing");
+    entry.set_max_retry(1);
+    let mock_failing = mock_failing.with_status(503).expect(2).create();
+
+    let mut handler = EventHandler::default();
+    let batch_result = batch.inner.download(&mut handler).unwrap();
+    assert_eq!(batch_result.errors_count(), 1);
+    assert_eq!(handler.size, 12);
+
+    let mut events = handler.events;
+    events.sort();
+    assert_eq!(events, [
+        (0, "completed 12".to_string()),
+        (0, "started".to_string()),
+        (1, "failed invalid status: 503".to_string()),
+        (1, "retried 1".to_string()),
+        (1, "started".to_string()),
+    ]);
+
+    mock.assert();
+    mock_failing.assert();
+
+}
+
+#[test]
+fn local() {
+
+    let mut batch = TestBatch::new();
+    let server_url = batch.server.url();
+    let host = reqwest::Url::parse(&server_url).unwrap().host_str().unwrap().to_string();
+
+    // Prepare the local root with some files.
+    let local_root = batch.dir.path().join("local");
+    fs::create_dir_all(local_root.join(&host).join("dir")).unwrap();
+    fs::write(local_root.join(&host).join("dir").join("local"), "Hello world!").unwrap();
+    fs::write(local_root.join(&host).join("invalid"), "Hello world?").unwrap();
+    fs::write(batch.dir.path().join("source"), "Hello world!").unwrap();
+
+    let mut config = download::Config::new();
+    config.set_local_root(Some(local_root.clone()));
+    batch.inner.set_config(config);
+
+    // The file is found in the local root, no request is made.
+    let (mock_local, entry) = batch.push("dir/local");
+    entry.set_expected_sha1(Some(*b"\xd3\x48\x6a\xe9\x13\x6e\x78\x56\xbc\x42\x21\x23\x85\xea\x79\x70\x94\x47\x58\x02"));
+    let mock_local = mock_local.expect(0).create();
+
+    // The local file is invalid, so it's requested.
+    let (mock_invalid, entry) = batch.push("invalid");
+    entry.set_expected_sha1(Some(*b"\xd3\x48\x6a\xe9\x13\x6e\x78\x56\xbc\x42\x21\x23\x85\xea\x79\x70\x94\x47\x58\x02"));
+    let mock_invalid = mock_invalid.with_status(200).with_body("Hello world!").create();
+
+    // A file URL given directly.
+    let source_url = reqwest::Url::from_file_path(batch.dir.path().join("source")).unwrap();
+    batch.inner.push(source_url.to_string(), batch.dir.path().join("copied"));
+
+    // A missing file URL.
+    let missing_url = reqwest::Url::from_file_path(batch.dir.path().join("missing")).unwrap();
+    batch.inner.push(missing_url.to_string(), batch.dir.path().join("missing_copied"));
+
+    let batch_result = batch.inner.download(()).unwrap();
+    assert_eq!(batch_result.errors_count(), 1);
+
+    let success = batch_result.entry(0).unwrap();
+    assert!(success.source_url().starts_with("file:"));
+    assert_eq!(fs::read_to_string(success.file()).unwrap(), "Hello world!");
+
+    let success = batch_result.entry(1).unwrap();
+    assert_eq!(success.source_url(), format!("{server_url}/invalid"));
+    assert_eq!(fs::read_to_string(success.file()).unwrap(), "Hello world!");
+
+    let success = batch_result.entry(2).unwrap();
+    assert_eq!(success.size(), 12);
+    assert_eq!(fs::read_to_string(success.file()).unwrap(), "Hello world!");
+
+    let error = batch_result.entry(3).unwrap_err();
+    assert!(matches!(error.kind(), EntryErrorKind::SourceFileNotFound));
+
+    mock_local.assert();
+    mock_invalid.assert();
+
+}
+
+#[test]
+fn local_link() {
+
+    let dir = tempfile::Builder::new()
+        .prefix("")
+        .suffix(".download")
+        .tempdir_in(env!("CARGO_TARGET_TMPDIR"))
+        .unwrap();
+
+    let source = dir.path().join("source");
+    fs::write(&source, "Hello world!").unwrap();
+
+    let mut config = download::Config::new();
+    config.set_local_link(true);
+
+    let source_url = reqwest::Url::from_file_path(&source).unwrap();
+    let mut result = download::single(source_url.to_string(), dir.path().join("linked"))
+        .set_config(config)
+        .set_keep_open()
+        .download(())
+        .unwrap();
+
+    // The handle of the linked file is read-only, so the source can't be modified.
+    let mut handle = result.take_handle().unwrap();
+    assert!(handle.write_all(b"Goodbye world!").is_err());
+
+    let mut buf = String::new();
+    handle.read_to_string(&mut buf).unwrap();
+    assert_eq!(buf, "Hello world!");
+    assert_eq!(fs::read_to_string(&source).unwrap(), "Hello world!");
+
+}
+
+#[test]
+fn offline() {
+
+    let mut batch = TestBatch::new();
+
+    // First download the cached entry online, in order to initialize its cache.
+    let (mock, entry) = batch.push("cached");
+    entry.set_use_cache();
+    let mock = mock.with_status(200).with_header("Etag", "0123456789").with_body("Hello world!").create();
+    batch.inner.download(()).unwrap().into_result().unwrap();
+    mock.assert();
+
+    let mut batch = TestBatch { inner: Batch::new(), ..batch };
+    let mut config = download::Config::new();
+    config.set_offline(true);
+    batch.inner.set_config(config);
+
+    // The cached entry is returned as-is.
+    let (mock_cached, entry) = batch.push("cached");
+    entry.set_use_cache();
+    let mock_cached = mock_cached.expect(0).create();
+
+    // The entry is not cached, so it can't be downloaded.
+    let (mock_missing, _) = batch.push("missing");
+    let mock_missing = mock_missing.expect(0).create();
+
+    let batch_result = batch.inner.download(()).unwrap();
+    assert_eq!(batch_result.errors_count(), 1);
+
+    let success = batch_result.entry(0).unwrap();
+    assert_eq!(success.size(), 12);
+    assert_eq!(fs::read_to_string(success.file()).unwrap(), "Hello world!");
+
+    let error = batch_result.entry(1).unwrap_err();
+    assert!(matches!(error.kind(), EntryErrorKind::Offline));
+
+    mock_cached.assert();
+    mock_missing.assert();
+
 }
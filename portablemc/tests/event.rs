@@ -6,7 +6,8 @@ use std::{env, fs, io};
 
 use regex::Regex;
 
-use portablemc::base::{self, JvmPolicy};
+use portablemc::base::{self, InstallSide, JvmPolicy, PlanCategory};
+use portablemc::{moj, fabric, forge};
 
 
 macro_rules! def_checks {
@@ -25,11 +26,81 @@ def_checks![
     recurse, 
     client_not_found,
     libraries,
+    server_not_found,
+    server_libraries,
 ];
 
 /// Common function to check a predefined version, placed in the "data" directory, and
-/// the triggering order of its events.
+/// the triggering order of its events. Versions prefixed with `server_` are installed
+/// on the server side.
 fn check(version: &str) {
+
+    let (expected_logs, tmp_main_dir) = prepare(version);
+
+    // Now run the installer and store its actual logs...
+    let mut actual_logs = Vec::new();
+    let mut inst = base::Installer::new(version);
+    inst.set_main_dir(tmp_main_dir.to_path_buf());
+    inst.set_jvm_policy(JvmPolicy::Static(PathBuf::new()));
+    if version.starts_with("server_") {
+        inst.set_side(InstallSide::Server);
+    }
+    match inst.install(TestHandler { logs: &mut actual_logs }) {
+        Ok(_game) => {}
+        Err(base::Error::DownloadResourcesCancelled {  }) => {}
+        Err(e) => {
+            actual_logs.push(format!("{e:?}"));
+        }
+    }
+
+    assert_logs_eq(expected_logs, actual_logs, &tmp_main_dir);
+
+    // Only remove it here so when the test did not panic.
+    fs::remove_dir_all(&tmp_main_dir).unwrap();
+
+}
+
+/// Check that the async installation can be spawned on a multi-thread runtime and 
+/// that it gives the same events as the blocking one.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn install_async() {
+
+    let version = "libraries";
+    let (expected_logs, tmp_main_dir) = prepare(version);
+
+    let mut inst = base::Installer::new(version);
+    inst.set_main_dir(tmp_main_dir.to_path_buf());
+    inst.set_jvm_policy(JvmPolicy::Static(PathBuf::new()));
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let actual_logs = rt.block_on(rt.spawn(async move {
+        let mut actual_logs = Vec::new();
+        match inst.install_async(TestHandler { logs: &mut actual_logs }).await {
+            Ok(_game) => {}
+            Err(base::Error::DownloadResourcesCancelled {  }) => {}
+            Err(e) => {
+                actual_logs.push(format!("{e:?}"));
+            }
+        }
+        actual_logs
+    })).unwrap();
+
+    assert_logs_eq(expected_logs, actual_logs, &tmp_main_dir);
+    fs::remove_dir_all(&tmp_main_dir).unwrap();
+
+    // Installers wrapping the base one should also give sendable futures, they are 
+    // not polled here because it would require network.
+    fn assert_send<T: Send>(_: T) {}
+    assert_send(moj::Installer::new_with_release().install_async(()));
+    assert_send(fabric::Installer::new_with_stable(fabric::Loader::Fabric).install_async(()));
+    assert_send(forge::Installer::new(forge::Loader::Forge, "1.21").install_async(()));
+
+}
+
+/// Load the expected logs for the given predefined version and prepare a temporary main
+/// directory where its metadata is copied.
+fn prepare(version: &str) -> (Vec<String>, PathBuf) {
     
     let data_dir = {
         let mut buf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -66,23 +137,171 @@ fn check(version: &str) {
     fs::create_dir_all(&tmp_version_dir).unwrap();
     fs::copy(&metadata_file, &tmp_metadata_file).unwrap();
 
-    // Now run the installer and store its actual logs...
-    let mut actual_logs = Vec::new();
+    (expected_logs, tmp_main_dir)
+
+}
+
+/// Check that the install plan of a version reports each missing resource
+/// in its category, for both sides, without downloading anything.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn plan() {
+
+    let version = "plan";
+    let metadata = r#"{
+        "id": "plan",
+        "mainClass": "net.minecraft.client.main.Main",
+        "downloads": {
+            "client": { "url": "https://mock.com/client.jar", "size": 100 },
+            "server": { "url": "https://mock.com/server.jar", "size": 200 }
+        },
+        "logging": {
+            "client": {
+                "argument": "-Dlog4j.configurationFile=${path}",
+                "file": { "id": "client.xml", "url": "https://mock.com/client.xml", "size": 10 },
+                "type": "log4j2-xml"
+            }
+        },
+        "libraries": [
+            { "name": "mock:lib0:1.0.0", "url": "https://mock.com/" }
+        ]
+    }"#;
+
+    fs::create_dir_all(env!("CARGO_TARGET_TMPDIR")).unwrap();
+    let tmp_main_dir = tempfile::Builder::new()
+        .prefix("")
+        .suffix(".plan")
+        .tempdir_in(env!("CARGO_TARGET_TMPDIR"))
+        .unwrap();
+
+    let tmp_version_dir = tmp_main_dir.path().join("versions").join(version);
+    fs::create_dir_all(&tmp_version_dir).unwrap();
+    fs::write(tmp_version_dir.join(format!("{version}.json")), metadata).unwrap();
+
     let mut inst = base::Installer::new(version);
-    inst.set_main_dir(tmp_main_dir.to_path_buf());
+    inst.set_main_dir(tmp_main_dir.path().to_path_buf());
     inst.set_jvm_policy(JvmPolicy::Static(PathBuf::new()));
-    match inst.install(TestHandler { logs: &mut actual_logs }) {
-        Ok(_game) => {}
-        Err(base::Error::DownloadResourcesCancelled {  }) => {}
-        Err(e) => {
-            actual_logs.push(format!("{e:?}"));
-        }
+
+    let plan = inst.plan(()).unwrap();
+    let categories = plan.entries().iter().map(|e| e.category).collect::<Vec<_>>();
+    assert_eq!(categories, [PlanCategory::Client, PlanCategory::Libraries, PlanCategory::Logger]);
+    assert_eq!(plan.iter_category(PlanCategory::Libraries).next().unwrap().url, "https://mock.com/mock/lib0/1.0.0/lib0-1.0.0.jar");
+    assert_eq!(plan.iter_category(PlanCategory::Client).next().unwrap().file, tmp_version_dir.join(format!("{version}.jar")));
+    assert_eq!(plan.total_size(), 110);
+
+    // The planned URLs are the ones that would be requested, after mirror rules.
+    let mut download_config = inst.download_config().clone();
+    download_config.add_mirror("https://mock.com/mock/", "https://mirror.com/");
+    inst.set_download_config(download_config);
+    let plan = inst.plan(()).unwrap();
+    assert_eq!(plan.iter_category(PlanCategory::Libraries).next().unwrap().url, "https://mirror.com/lib0/1.0.0/lib0-1.0.0.jar");
+    assert_eq!(plan.iter_category(PlanCategory::Client).next().unwrap().url, "https://mock.com/client.jar");
+
+    inst.set_side(InstallSide::Server);
+    let plan = inst.plan(()).unwrap();
+    let categories = plan.entries().iter().map(|e| e.category).collect::<Vec<_>>();
+    assert_eq!(categories, [PlanCategory::Server]);
+    assert_eq!(plan.total_size(), 200);
+
+    // Nothing should have been downloaded.
+    assert!(!tmp_version_dir.join(format!("{version}.jar")).exists());
+    assert!(!tmp_main_dir.path().join("libraries").exists());
+
+}
+
+/// Check that the plan of a loader that is not installed gives the reason to install
+/// it, without running its installer.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn plan_forge_not_installed() {
+
+    fs::create_dir_all(env!("CARGO_TARGET_TMPDIR")).unwrap();
+    let tmp_main_dir = tempfile::Builder::new()
+        .prefix("")
+        .suffix(".plan")
+        .tempdir_in(env!("CARGO_TARGET_TMPDIR"))
+        .unwrap();
+
+    let mut inst = forge::Installer::new(forge::Loader::Forge, "1.21-51.0.0");
+    inst.mojang_mut().base_mut().set_main_dir(tmp_main_dir.path().to_path_buf());
+
+    match inst.plan(()) {
+        Err(forge::Error::NotInstalled { reason: forge::InstallReason::MissingVersionMetadata }) => {}
+        res => panic!("unexpected result: {res:?}"),
     }
 
-    assert_logs_eq(expected_logs, actual_logs, &tmp_main_dir);
+    assert!(!tmp_main_dir.path().join("versions").join("forge-1.21-51.0.0").exists());
 
-    // Only remove it here so when the test did not panic.
-    fs::remove_dir_all(&tmp_main_dir).unwrap();
+}
+
+/// Check that a fully installed version can be installed offline without any server,
+/// and that missing resources are reported when offline.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn offline() {
+
+    let version = "offline";
+    let metadata = r#"{
+        "id": "offline",
+        "mainClass": "net.minecraft.client.main.Main",
+        "downloads": {
+            "client": { 
+                "url": "https://mock.com/client.jar", 
+                "size": 12, 
+                "sha1": "d3486ae9136e7856bc42212385ea797094475802" 
+            }
+        },
+        "libraries": [
+            { 
+                "name": "mock:lib0:1.0.0", 
+                "downloads": {
+                    "artifact": {
+                        "path": "mock/lib0/1.0.0/lib0-1.0.0.jar",
+                        "url": "https://mock.com/mock/lib0/1.0.0/lib0-1.0.0.jar",
+                        "size": 12,
+                        "sha1": "d3486ae9136e7856bc42212385ea797094475802"
+                    }
+                }
+            }
+        ]
+    }"#;
+
+    fs::create_dir_all(env!("CARGO_TARGET_TMPDIR")).unwrap();
+    let tmp_main_dir = tempfile::Builder::new()
+        .prefix("")
+        .suffix(".offline")
+        .tempdir_in(env!("CARGO_TARGET_TMPDIR"))
+        .unwrap();
+
+    let tmp_version_dir = tmp_main_dir.path().join("versions").join(version);
+    let tmp_lib_file = tmp_main_dir.path().join("libraries/mock/lib0/1.0.0/lib0-1.0.0.jar");
+    fs::create_dir_all(&tmp_version_dir).unwrap();
+    fs::create_dir_all(tmp_lib_file.parent().unwrap()).unwrap();
+    fs::create_dir_all(tmp_main_dir.path().join("assets")).unwrap();
+    fs::write(tmp_version_dir.join(format!("{version}.json")), metadata).unwrap();
+    fs::write(tmp_version_dir.join(format!("{version}.jar")), "Hello world!").unwrap();
+    fs::write(&tmp_lib_file, "Hello world!").unwrap();
+    fs::write(tmp_main_dir.path().join("java"), "").unwrap();
+
+    let mut inst = moj::Installer::new(version);
+    inst.base_mut().set_main_dir(tmp_main_dir.path().to_path_buf());
+    inst.base_mut().set_jvm_policy(JvmPolicy::Static(tmp_main_dir.path().join("java")));
+    inst.base_mut().set_offline(true);
+    assert!(inst.base().download_config().offline());
+
+    let game = inst.install(()).unwrap();
+    assert_eq!(game.main_class, "net.minecraft.client.main.Main");
+
+    // Now the library is missing and can't be downloaded.
+    fs::remove_file(&tmp_lib_file).unwrap();
+    match inst.install(()) {
+        Err(moj::Error::Base(base::Error::Offline { resources })) => {
+            assert_eq!(resources.len(), 1);
+            assert_eq!(resources[0].url, "https://mock.com/mock/lib0/1.0.0/lib0-1.0.0.jar");
+            assert_eq!(resources[0].file.as_deref(), Some(&*tmp_lib_file));
+        }
+        res => panic!("unexpected result: {res:?}"),
+    }
 
 }
 
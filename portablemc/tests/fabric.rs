@@ -0,0 +1,111 @@
+//! Installation tests of Fabric versions, against a local mock of the Fabric API.
+
+use std::{env, fs};
+
+use portablemc::base::{InstallSide, JvmPolicy};
+use portablemc::moj::FetchExclude;
+use portablemc::fabric;
+
+use mockito::Server;
+
+
+const GAME_VERSION: &str = "1.21";
+const LOADER_VERSION: &str = "0.16.0";
+const MAIN_CLASS: &str = "net.fabricmc.loader.impl.launch.knot.KnotServer";
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn server() {
+
+    fs::create_dir_all(env!("CARGO_TARGET_TMPDIR")).unwrap();
+    let tmp_main_dir = tempfile::Builder::new()
+        .prefix("")
+        .suffix(".fabric")
+        .tempdir_in(env!("CARGO_TARGET_TMPDIR"))
+        .unwrap();
+
+    let mut server = Server::new();
+
+    // The vanilla version is already installed, only its server JAR is downloaded.
+    let vanilla_dir = tmp_main_dir.path().join("versions").join(GAME_VERSION);
+    fs::create_dir_all(&vanilla_dir).unwrap();
+    fs::write(vanilla_dir.join(format!("{GAME_VERSION}.json")), format!(r#"{{
+        "id": "{GAME_VERSION}",
+        "mainClass": "net.minecraft.client.main.Main",
+        "downloads": {{
+            "client": {{ "url": "{url}/client.jar" }},
+            "server": {{ "url": "{url}/server.jar" }}
+        }},
+        "libraries": [
+            {{ "name": "mock:client-only:1.0.0", "url": "{url}/maven/" }}
+        ]
+    }}"#, url = server.url())).unwrap();
+
+    let server_jar_mock = server.mock("GET", "/server.jar")
+        .with_body("server")
+        .expect(1)
+        .create();
+
+    let profile_mock = server.mock("GET", format!("/v2/versions/loader/{GAME_VERSION}/{LOADER_VERSION}/server/json").as_str())
+        .with_body(format!(r#"{{
+            "id": "fabric-loader-{LOADER_VERSION}-{GAME_VERSION}",
+            "inheritsFrom": "{GAME_VERSION}",
+            "mainClass": "{MAIN_CLASS}",
+            "libraries": [
+                {{ "name": "net.fabricmc:fabric-loader:{LOADER_VERSION}", "url": "https://maven.fabricmc.net/" }}
+            ]
+        }}"#))
+        .expect(1)
+        .create();
+
+    let client_profile_mock = server.mock("GET", format!("/v2/versions/loader/{GAME_VERSION}/{LOADER_VERSION}/profile/json").as_str())
+        .expect(0)
+        .create();
+
+    let loader_mock = server.mock("GET", format!("/maven/net/fabricmc/fabric-loader/{LOADER_VERSION}/fabric-loader-{LOADER_VERSION}.jar").as_str())
+        .with_body("loader")
+        .expect(1)
+        .create();
+
+    fs::write(tmp_main_dir.path().join("java"), "").unwrap();
+
+    let mut inst = fabric::Installer::new(fabric::Loader::Fabric, GAME_VERSION, LOADER_VERSION);
+    inst.mojang_mut().add_fetch_exclude(FetchExclude::All);
+    let base = inst.mojang_mut().base_mut();
+    base.set_main_dir(tmp_main_dir.path().to_path_buf());
+    base.set_jvm_policy(JvmPolicy::Static(tmp_main_dir.path().join("java")));
+    base.set_side(InstallSide::Server);
+
+    let mut download_config = base.download_config().clone();
+    download_config
+        .add_mirror("https://meta.fabricmc.net/v2/", format!("{}/v2/", server.url()))
+        .add_mirror("https://maven.fabricmc.net/", format!("{}/maven/", server.url()))
+        .set_mirror_fallback(false);
+    base.set_download_config(download_config);
+
+    let game = inst.install(()).unwrap();
+
+    // The server profile has been written as the root version.
+    let root_version = format!("fabric-{GAME_VERSION}-{LOADER_VERSION}-server");
+    assert!(tmp_main_dir.path().join("versions").join(&root_version).join(format!("{root_version}.json")).is_file());
+
+    let server_file = vanilla_dir.join(format!("{GAME_VERSION}-server.jar"));
+    let loader_file = tmp_main_dir.path().join(format!("libraries/net/fabricmc/fabric-loader/{LOADER_VERSION}/fabric-loader-{LOADER_VERSION}.jar"));
+    assert_eq!(fs::read(&server_file).unwrap(), b"server");
+    assert_eq!(fs::read(&loader_file).unwrap(), b"loader");
+
+    // The loader runs the server from the class path, the vanilla libraries are ignored.
+    assert_eq!(game.main_class, MAIN_CLASS);
+    assert_eq!(game.jvm_args.len(), 2);
+    assert_eq!(game.jvm_args[0], "-cp");
+    let class_files = env::split_paths(&game.jvm_args[1]).collect::<Vec<_>>();
+    assert_eq!(class_files, [server_file.canonicalize().unwrap(), loader_file.canonicalize().unwrap()]);
+    assert_eq!(game.game_args, ["nogui"]);
+    assert_eq!(game.mc_dir, tmp_main_dir.path().join("server").canonicalize().unwrap());
+
+    server_jar_mock.assert();
+    profile_mock.assert();
+    client_profile_mock.assert();
+    loader_mock.assert();
+
+}
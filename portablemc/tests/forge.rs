@@ -0,0 +1,261 @@
+//! Installation tests of Forge server versions, against a local mock of the Forge maven.
+
+use std::io::{Cursor, Write as _};
+use std::{env, fs};
+
+use portablemc::base::{InstallSide, JvmPolicy};
+use portablemc::moj::FetchExclude;
+use portablemc::forge;
+
+use mockito::{Mock, Server};
+use tempfile::TempDir;
+use zip::ZipWriter;
+
+
+const GAME_VERSION: &str = "1.21";
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn server_args_file() {
+
+    const LOADER_VERSION: &str = "1.21-51.0.0";
+    const MAIN_CLASS: &str = "cpw.mods.bootstraplauncher.BootstrapLauncher";
+
+    let mut server = Server::new();
+    let (tmp_main_dir, server_jar_mock) = prepare(&mut server);
+
+    // The arguments file references the launcher JAR downloaded from the maven, and
+    // the server JAR that is generated by processors in real installers, here it's
+    // extracted from the installer. The class path is given like the real files do.
+    let launcher_path = "cpw/mods/bootstraplauncher/1.0.0/bootstraplauncher-1.0.0.jar";
+    let forge_path = format!("net/minecraftforge/forge/{LOADER_VERSION}/forge-{LOADER_VERSION}-server.jar");
+    let args = format!("-Djava.net.preferIPv6Addresses=system \
+        -p libraries/{launcher_path} \
+        --add-modules ALL-MODULE-PATH \
+        --add-opens java.base/java.util.jar=cpw.mods.securejarhandler \
+        -DlibraryDirectory=libraries \
+        -cp libraries/{forge_path} \
+        {MAIN_CLASS} --launchTarget forge_server\n");
+
+    let installer = make_zip(&[
+        ("install_profile.json", install_profile(None, &[
+            ("cpw.mods:bootstraplauncher:1.0.0", "https://maven.minecraftforge.net/cpw/mods/bootstraplauncher/1.0.0/bootstraplauncher-1.0.0.jar"),
+            (&format!("net.minecraftforge:forge:{LOADER_VERSION}:server"), ""),
+        ]).as_bytes()),
+        ("version.json", version_metadata(LOADER_VERSION).as_bytes()),
+        ("data/unix_args.txt", args.as_bytes()),
+        ("data/win_args.txt", args.as_bytes()),
+        (&format!("maven/{forge_path}"), b"forge"),
+    ]);
+
+    let installer_mock = installer_mock(&mut server, LOADER_VERSION, installer);
+    let launcher_mock = server.mock("GET", format!("/forge/{launcher_path}").as_str())
+        .with_body("launcher")
+        .expect(1)
+        .create();
+
+    let game = install(&server, &tmp_main_dir, LOADER_VERSION);
+
+    let root_version = format!("forge-{LOADER_VERSION}-server");
+    assert!(tmp_main_dir.path().join("versions").join(&root_version).join(format!("{root_version}.json")).is_file());
+
+    let libs_dir = tmp_main_dir.path().join("libraries").canonicalize().unwrap();
+    assert_eq!(fs::read(libs_dir.join(launcher_path)).unwrap(), b"launcher");
+    assert_eq!(fs::read(libs_dir.join(&forge_path)).unwrap(), b"forge");
+
+    // The main class is found after the class path, only the libraries paths have
+    // been rewritten, the remaining arguments are given to the server.
+    let libs_dir = libs_dir.display();
+    assert_eq!(game.main_class, MAIN_CLASS);
+    assert_eq!(game.jvm_args, [
+        "-Djava.net.preferIPv6Addresses=system".to_string(),
+        "-p".to_string(),
+        format!("{libs_dir}/{launcher_path}"),
+        "--add-modules".to_string(),
+        "ALL-MODULE-PATH".to_string(),
+        "--add-opens".to_string(),
+        "java.base/java.util.jar=cpw.mods.securejarhandler".to_string(),
+        format!("-DlibraryDirectory={libs_dir}"),
+        "-cp".to_string(),
+        format!("{libs_dir}/{forge_path}"),
+    ]);
+    assert_eq!(game.game_args, ["--launchTarget", "forge_server", "nogui"]);
+    assert_eq!(game.mc_dir, tmp_main_dir.path().join("server").canonicalize().unwrap());
+
+    server_jar_mock.assert();
+    installer_mock.assert();
+    launcher_mock.assert();
+
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn server_main_class() {
+
+    const LOADER_VERSION: &str = "1.21-51.0.1";
+    const MAIN_CLASS: &str = "net.minecraftforge.server.ServerMain";
+
+    let mut server = Server::new();
+    let (tmp_main_dir, server_jar_mock) = prepare(&mut server);
+
+    // Without arguments file, the universal JAR is run with its main class, and its
+    // class path gives the libraries, relative to the server directory.
+    let universal_name = format!("net.minecraftforge:forge:{LOADER_VERSION}:universal");
+    let universal_path = format!("net/minecraftforge/forge/{LOADER_VERSION}/forge-{LOADER_VERSION}-universal.jar");
+    let asm_path = "org/ow2/asm/asm/9.7/asm-9.7.jar";
+    let manifest = format!("Manifest-Version: 1.0\r\n\
+        Main-Class: {MAIN_CLASS}\r\n\
+        Class-Path: libraries/{asm_path}\r\n\
+        \r\n");
+
+    let universal = make_zip(&[
+        ("META-INF/MANIFEST.MF", manifest.as_bytes()),
+    ]);
+
+    let installer = make_zip(&[
+        ("install_profile.json", install_profile(Some(&universal_name), &[
+            ("org.ow2.asm:asm:9.7", "https://maven.minecraftforge.net/org/ow2/asm/asm/9.7/asm-9.7.jar"),
+        ]).as_bytes()),
+        ("version.json", version_metadata(LOADER_VERSION).as_bytes()),
+        (&format!("maven/{universal_path}"), &universal),
+    ]);
+
+    let installer_mock = installer_mock(&mut server, LOADER_VERSION, installer);
+    let asm_mock = server.mock("GET", format!("/forge/{asm_path}").as_str())
+        .with_body("asm")
+        .expect(1)
+        .create();
+
+    let game = install(&server, &tmp_main_dir, LOADER_VERSION);
+
+    let root_version = format!("forge-{LOADER_VERSION}-server");
+    assert!(tmp_main_dir.path().join("versions").join(&root_version).join(format!("{root_version}.json")).is_file());
+
+    let libs_dir = tmp_main_dir.path().join("libraries").canonicalize().unwrap();
+    let server_file = tmp_main_dir.path().join("versions").join(GAME_VERSION).join(format!("{GAME_VERSION}-server.jar"));
+    assert_eq!(fs::read(libs_dir.join(asm_path)).unwrap(), b"asm");
+
+    // The class path is given by the installer, with the vanilla server JAR.
+    assert_eq!(game.main_class, MAIN_CLASS);
+    assert_eq!(game.jvm_args.len(), 2);
+    assert_eq!(game.jvm_args[0], "-cp");
+    let class_files = env::split_paths(&game.jvm_args[1]).collect::<Vec<_>>();
+    assert_eq!(class_files, [
+        server_file.canonicalize().unwrap(),
+        libs_dir.join(&universal_path),
+        libs_dir.join(asm_path),
+    ]);
+    assert_eq!(game.game_args, ["nogui"]);
+
+    server_jar_mock.assert();
+    installer_mock.assert();
+    asm_mock.assert();
+
+}
+
+/// Create the temporary main directory with the vanilla version already installed,
+/// only its server JAR will be downloaded, from the returned mock.
+fn prepare(server: &mut Server) -> (TempDir, Mock) {
+
+    fs::create_dir_all(env!("CARGO_TARGET_TMPDIR")).unwrap();
+    let tmp_main_dir = tempfile::Builder::new()
+        .prefix("")
+        .suffix(".forge")
+        .tempdir_in(env!("CARGO_TARGET_TMPDIR"))
+        .unwrap();
+
+    let vanilla_dir = tmp_main_dir.path().join("versions").join(GAME_VERSION);
+    fs::create_dir_all(&vanilla_dir).unwrap();
+    fs::write(vanilla_dir.join(format!("{GAME_VERSION}.json")), format!(r#"{{
+        "id": "{GAME_VERSION}",
+        "mainClass": "net.minecraft.client.main.Main",
+        "downloads": {{
+            "client": {{ "url": "{url}/client.jar" }},
+            "server": {{ "url": "{url}/server.jar" }}
+        }},
+        "libraries": []
+    }}"#, url = server.url())).unwrap();
+
+    fs::write(tmp_main_dir.path().join("java"), "").unwrap();
+
+    let server_jar_mock = server.mock("GET", "/server.jar")
+        .with_body("server")
+        .expect(1)
+        .create();
+
+    (tmp_main_dir, server_jar_mock)
+
+}
+
+/// Install the given loader version for the server, with the Forge maven mirrored.
+fn install(server: &Server, tmp_main_dir: &TempDir, loader_version: &str) -> portablemc::base::Game {
+
+    let mut inst = forge::Installer::new(forge::Loader::Forge, loader_version);
+    inst.mojang_mut().add_fetch_exclude(FetchExclude::All);
+    let base = inst.mojang_mut().base_mut();
+    base.set_main_dir(tmp_main_dir.path().to_path_buf());
+    base.set_jvm_policy(JvmPolicy::Static(tmp_main_dir.path().join("java")));
+    base.set_side(InstallSide::Server);
+
+    let mut download_config = base.download_config().clone();
+    download_config
+        .add_mirror("https://maven.minecraftforge.net/", format!("{}/forge/", server.url()))
+        .set_mirror_fallback(false);
+    base.set_download_config(download_config);
+
+    inst.install(()).unwrap()
+
+}
+
+/// Create the mock serving the installer of the given loader version.
+fn installer_mock(server: &mut Server, loader_version: &str, installer: Vec<u8>) -> Mock {
+    server.mock("GET", format!("/forge/net/minecraftforge/forge/{loader_version}/forge-{loader_version}-installer.jar").as_str())
+        .with_body(installer)
+        .expect(1)
+        .create()
+}
+
+/// Return a modern install profile without processors, with the given optional path
+/// of the universal JAR and the given libraries, extracted if their URL is empty.
+fn install_profile(path: Option<&str>, libraries: &[(&str, &str)]) -> String {
+
+    let libraries = libraries.iter()
+        .map(|(name, url)| format!(r#"{{ "name": "{name}", "downloads": {{ "artifact": {{ "url": "{url}" }} }} }}"#))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let path = path.map(|path| format!(r#""path": "{path}","#)).unwrap_or_default();
+
+    format!(r#"{{
+        "minecraft": "{GAME_VERSION}",
+        {path}
+        "json": "/version.json",
+        "libraries": [{libraries}],
+        "processors": [],
+        "data": {{}}
+    }}"#)
+
+}
+
+/// Return the client version metadata of the installer, it's rewritten for the server,
+/// its main class is never the one of the server.
+fn version_metadata(loader_version: &str) -> String {
+    format!(r#"{{
+        "id": "forge-{loader_version}",
+        "inheritsFrom": "{GAME_VERSION}",
+        "mainClass": "net.minecraftforge.bootstrap.ForgeBootstrap",
+        "libraries": []
+    }}"#)
+}
+
+/// Create an uncompressed archive with the given entries.
+fn make_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored);
+    for &(name, data) in entries {
+        writer.start_file(name, options).unwrap();
+        writer.write_all(data).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
@@ -0,0 +1,383 @@
+//! Installation tests of JVMs, against local mocks of the Mojang and Adoptium APIs.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::fs;
+
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use portablemc::base::{self, JvmPolicy};
+use portablemc::download;
+use portablemc::jvm::AdoptiumProvider;
+
+use tempfile::TempDir;
+
+use mockito::{Matcher, Mock, Server, ServerGuard};
+
+
+const VERSION: &str = "jvm_provider";
+const RELEASE_NAME: &str = "jdk-21.0.5+11";
+
+/// Create a temporary main directory with a minimal version requiring Java 21.
+fn prepare() -> TempDir {
+
+    fs::create_dir_all(env!("CARGO_TARGET_TMPDIR")).unwrap();
+    let tmp_main_dir = tempfile::Builder::new()
+        .prefix("")
+        .suffix(".jvm")
+        .tempdir_in(env!("CARGO_TARGET_TMPDIR"))
+        .unwrap();
+
+    // These directories are required to exist when finalizing the installation.
+    for dir in ["libraries", "assets"] {
+        fs::create_dir_all(tmp_main_dir.path().join(dir)).unwrap();
+    }
+
+    let version_dir = tmp_main_dir.path().join("versions").join(VERSION);
+    fs::create_dir_all(&version_dir).unwrap();
+    fs::write(version_dir.join(format!("{VERSION}.json")), format!(r#"{{
+        "id": "{VERSION}",
+        "mainClass": "net.minecraft.client.main.Main",
+        "javaVersion": {{ "component": "java-runtime-delta", "majorVersion": 21 }}
+    }}"#)).unwrap();
+    fs::write(version_dir.join(format!("{VERSION}.jar")), b"").unwrap();
+
+    tmp_main_dir
+
+}
+
+/// The path of the JVM executable, relative to the JVM home directory.
+fn bin_file() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        PathBuf::from("Contents/Home/bin/java")
+    } else if cfg!(windows) {
+        PathBuf::from("bin/javaw.exe")
+    } else {
+        PathBuf::from("bin/java")
+    }
+}
+
+/// Build a `.tar.gz` archive of a fake JVM, with a single top-level directory.
+fn build_archive() -> Vec<u8> {
+
+    let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+
+    let mut add_file = |path: &Path, data: &[u8], mode: u32| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(mode);
+        header.set_cksum();
+        builder.append_data(&mut header, Path::new(&format!("{RELEASE_NAME}-jre")).join(path), data).unwrap();
+    };
+
+    add_file(&bin_file(), b"#!/bin/sh\n", 0o755);
+    add_file(Path::new("release"), b"JAVA_VERSION=\"21.0.5\"\n", 0o644);
+
+    builder.into_inner().unwrap().finish().unwrap()
+
+}
+
+/// Mock the assets endpoint of the Adoptium API for the given image type, returning a
+/// single asset pointing to the archive.
+fn mock_assets(server: &mut ServerGuard, image_type: &str, archive: &[u8]) -> Mock {
+
+    let body = format!(r#"[{{
+        "binary": {{
+            "package": {{
+                "link": "{url}/archive.tar.gz",
+                "name": "OpenJDK21U-{image_type}_hotspot_21.0.5_11.tar.gz",
+                "size": {size},
+                "checksum": "{checksum:x}"
+            }}
+        }},
+        "release_name": "{RELEASE_NAME}",
+        "version": {{ "openjdk_version": "21.0.5+11-LTS" }}
+    }}]"#,
+        url = server.url(),
+        size = archive.len(),
+        checksum = Sha256::digest(archive));
+
+    server.mock("GET", "/v3/assets/latest/21/hotspot")
+        .match_query(Matcher::UrlEncoded("image_type".to_string(), image_type.to_string()))
+        .with_body(body)
+        .create()
+
+}
+
+/// The path of the JVM executable of a Mojang-provided JVM, relative to its directory.
+fn mojang_bin_file() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        PathBuf::from("jre.bundle/Contents/Home/bin/java")
+    } else if cfg!(windows) {
+        PathBuf::from("bin/javaw.exe")
+    } else {
+        PathBuf::from("bin/java")
+    }
+}
+
+/// Compress the given data with LZMA, as Mojang-provided JVM files.
+fn compress_lzma(data: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+    let mut writer = lzma_rust2::LzmaWriter::new_use_header(Vec::new(), &lzma_rust2::LzmaOptions::default(), None).unwrap();
+    writer.write_all(data).unwrap();
+    writer.finish().unwrap()
+}
+
+/// Format the download of the given data at the given URL, as in Mojang manifests.
+fn mojang_download(url: &str, data: &[u8]) -> String {
+    format!(r#"{{ "url": "{url}", "size": {size}, "sha1": "{sha1:x}" }}"#,
+        size = data.len(),
+        sha1 = Sha1::digest(data))
+}
+
+fn adoptium(base_url: String) -> JvmPolicy {
+    let mut provider = AdoptiumProvider::new();
+    provider.set_base_url(base_url);
+    JvmPolicy::Provider(Arc::new(provider))
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn adoptium_install() {
+
+    let tmp_main_dir = prepare();
+    let mut server = Server::new();
+    let archive = build_archive();
+
+    let assets_mock = mock_assets(&mut server, "jre", &archive).expect(1);
+    let archive_mock = server.mock("GET", "/archive.tar.gz")
+        .with_body(&archive)
+        .expect(1)
+        .create();
+
+    let mut inst = base::Installer::new(VERSION);
+    inst.set_main_dir(tmp_main_dir.path().to_path_buf());
+    inst.set_jvm_policy(adoptium(format!("{}/v3/", server.url())));
+
+    let jvm_dir = tmp_main_dir.path().join("jvm").join("adoptium").join(format!("{RELEASE_NAME}-jre"));
+    let jvm_file = jvm_dir.join(bin_file());
+
+    let game = inst.install(()).unwrap();
+    assert_eq!(game.jvm_file, jvm_file);
+    assert!(jvm_file.is_file());
+    assert!(jvm_dir.join("release").is_file());
+    // The archive has been removed once extracted, and the JVM has been recorded.
+    assert!(!jvm_dir.parent().unwrap().join(format!("{RELEASE_NAME}-jre.tar.gz")).exists());
+    assert!(jvm_dir.parent().unwrap().join("21.json").is_file());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(jvm_file.metadata().unwrap().permissions().mode() & 0o777, 0o755);
+    }
+
+    // The second installation should not request the provider nor download the 
+    // archive again.
+    let game = inst.install(()).unwrap();
+    assert_eq!(game.jvm_file, jvm_file);
+
+    assets_mock.assert();
+    archive_mock.assert();
+
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn adoptium_jdk_fallback() {
+
+    let tmp_main_dir = prepare();
+    let mut server = Server::new();
+    let archive = build_archive();
+
+    let jre_mock = server.mock("GET", "/v3/assets/latest/21/hotspot")
+        .match_query(Matcher::UrlEncoded("image_type".to_string(), "jre".to_string()))
+        .with_body("[]")
+        .create();
+    let jdk_mock = mock_assets(&mut server, "jdk", &archive);
+    server.mock("GET", "/archive.tar.gz")
+        .with_body(&archive)
+        .create();
+
+    let mut inst = base::Installer::new(VERSION);
+    inst.set_main_dir(tmp_main_dir.path().to_path_buf());
+    inst.set_jvm_policy(adoptium(format!("{}/v3", server.url())));
+
+    let game = inst.install(()).unwrap();
+    assert!(game.jvm_file.starts_with(tmp_main_dir.path().join("jvm").join("adoptium").join(format!("{RELEASE_NAME}-jdk"))));
+    assert!(game.jvm_file.is_file());
+
+    jre_mock.assert();
+    jdk_mock.assert();
+
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn chain_providers() {
+
+    /// Collect the providers that failed.
+    struct FailedHandler(Vec<String>);
+    impl base::Handler for FailedHandler {
+        fn on_event(&mut self, event: base::Event) {
+            if let base::Event::WarnJvmProviderFailed { provider, .. } = event {
+                self.0.push(provider.to_string());
+            }
+        }
+    }
+
+    let tmp_main_dir = prepare();
+    let mut server = Server::new();
+    let archive = build_archive();
+
+    // The first provider fails with an invalid response, and the second one has no JVM.
+    let failing_mock = server.mock("GET", Matcher::Regex("^/failing/".to_string()))
+        .with_body("invalid")
+        .expect(1)
+        .create();
+    let missing_mock = server.mock("GET", Matcher::Regex("^/missing/".to_string()))
+        .with_status(404)
+        .expect(2)
+        .create();
+    mock_assets(&mut server, "jre", &archive);
+    server.mock("GET", "/archive.tar.gz")
+        .with_body(&archive)
+        .create();
+
+    let mut inst = base::Installer::new(VERSION);
+    inst.set_main_dir(tmp_main_dir.path().to_path_buf());
+    inst.set_jvm_policy(JvmPolicy::Chain(vec![
+        adoptium(format!("{}/failing", server.url())),
+        adoptium(format!("{}/missing", server.url())),
+        adoptium(format!("{}/v3", server.url())),
+    ]));
+
+    let mut handler = FailedHandler(Vec::new());
+    let game = inst.install(&mut handler).unwrap();
+    assert!(game.jvm_file.is_file());
+    assert_eq!(handler.0, ["adoptium"]);
+    failing_mock.assert();
+    missing_mock.assert();
+
+    // When offline, the installation fails with the provider's request, instead of
+    // trying the next provider, a new main directory is used because the JVM 
+    // installed above would be used.
+    let tmp_main_dir = prepare();
+    inst.set_main_dir(tmp_main_dir.path().to_path_buf());
+    let mut download_config = inst.download_config().clone();
+    download_config.set_offline(true);
+    inst.set_download_config(download_config);
+
+    let mut handler = FailedHandler(Vec::new());
+    match inst.install(&mut handler) {
+        Err(base::Error::Offline { resources }) => {
+            assert_eq!(resources.len(), 1);
+            assert!(resources[0].url.starts_with(&format!("{}/failing/", server.url())));
+        }
+        res => panic!("unexpected result: {res:?}"),
+    }
+    assert!(handler.0.is_empty());
+
+    inst.set_download_config(download::Config::new());
+
+    // If no provider has a JVM, then the installation fails.
+    let tmp_main_dir = prepare();
+    inst.set_main_dir(tmp_main_dir.path().to_path_buf());
+    inst.set_jvm_policy(JvmPolicy::Chain(vec![
+        adoptium(format!("{}/missing", server.url())),
+    ]));
+
+    match inst.install(()) {
+        Err(base::Error::JvmNotFound { major_version: 21 }) => {}
+        res => panic!("unexpected result: {res:?}"),
+    }
+
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn mojang_lzma() {
+
+    let tmp_main_dir = prepare();
+    let mut server = Server::new();
+    let url = server.url();
+
+    let java = b"#!/bin/sh\n".repeat(100);
+    let modules = b"modules".repeat(100);
+    let java_lzma = compress_lzma(&java);
+    // Corrupted LZMA data, but valid against the manifest, it fails to decompress.
+    let modules_lzma = compress_lzma(&modules);
+    let modules_lzma = &modules_lzma[..modules_lzma.len() / 2];
+    let bin_file = mojang_bin_file();
+    let bin_file_str = bin_file.to_str().unwrap();
+
+    // The JVM executable is decompressed from its LZMA file, the raw file is downloaded
+    // for the modules because its LZMA file is corrupted.
+    let manifest = format!(r#"{{ "files": {{
+        "{bin_file_str}": {{ "type": "file", "executable": true, "downloads": {{
+            "raw": {java_raw}, "lzma": {java_lzma}
+        }} }},
+        "lib/modules": {{ "type": "file", "downloads": {{
+            "raw": {modules_raw}, "lzma": {modules_lzma}
+        }} }}
+    }} }}"#,
+        java_raw = mojang_download(&format!("{url}/raw/java"), &java),
+        java_lzma = mojang_download(&format!("{url}/lzma/java"), &java_lzma),
+        modules_raw = mojang_download(&format!("{url}/raw/modules"), &modules),
+        modules_lzma = mojang_download(&format!("{url}/lzma/modules"), modules_lzma));
+
+    // The same variant is given for all platforms, the host one is picked.
+    let variant = format!(r#"{{ "java-runtime-delta": [{{
+        "availability": {{ "group": 1, "progress": 100 }},
+        "manifest": {manifest},
+        "version": {{ "name": "21.0.5", "released": "2024-10-15T00:00:00+00:00" }}
+    }}] }}"#,
+        manifest = mojang_download(&format!("{url}/manifest.json"), manifest.as_bytes()));
+    let platforms = ["linux", "linux-i386", "mac-os", "mac-os-arm64", "windows-x64", "windows-x86", "windows-arm64"]
+        .map(|platform| format!(r#""{platform}": {variant}"#))
+        .join(",");
+
+    server.mock("GET", "/meta/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json")
+        .with_body(format!("{{{platforms}}}"))
+        .create();
+    server.mock("GET", "/manifest.json")
+        .with_body(&manifest)
+        .create();
+
+    let java_lzma_mock = server.mock("GET", "/lzma/java").with_body(&java_lzma).expect(1).create();
+    let java_raw_mock = server.mock("GET", "/raw/java").expect(0).create();
+    let modules_lzma_mock = server.mock("GET", "/lzma/modules").with_body(modules_lzma).expect(1).create();
+    let modules_raw_mock = server.mock("GET", "/raw/modules").with_body(&modules).expect(1).create();
+
+    let mut inst = base::Installer::new(VERSION);
+    inst.set_main_dir(tmp_main_dir.path().to_path_buf());
+    inst.set_jvm_policy(JvmPolicy::Mojang);
+
+    let mut download_config = inst.download_config().clone();
+    download_config
+        .add_mirror("https://piston-meta.mojang.com/", format!("{url}/meta/"))
+        .set_mirror_fallback(false);
+    inst.set_download_config(download_config);
+
+    let game = match inst.install(()) {
+        Ok(game) => game,
+        // The host has no Mojang-provided JVM.
+        Err(base::Error::JvmNotFound { .. }) => return,
+        Err(e) => panic!("unexpected error: {e:?}"),
+    };
+
+    let jvm_dir = tmp_main_dir.path().join("jvm").join("java-runtime-delta");
+    assert_eq!(game.jvm_file, jvm_dir.join(&bin_file));
+    assert_eq!(fs::read(jvm_dir.join(&bin_file)).unwrap(), java);
+    assert_eq!(fs::read(jvm_dir.join("lib/modules")).unwrap(), modules);
+    // No LZMA file is left once decompressed.
+    assert!(!jvm_dir.join("lib/modules.lzma").exists());
+    assert!(!jvm_dir.join(&bin_file).with_extension("lzma").exists());
+
+    java_lzma_mock.assert();
+    java_raw_mock.assert();
+    modules_lzma_mock.assert();
+    modules_raw_mock.assert();
+
+}